@@ -0,0 +1,179 @@
+//! The `__schema` and `__type` root fields, built from `TYPES` so that
+//! GraphiQL and codegen clients can load the schema. Every introspection
+//! object is built whole and the selection is then picked out of it.
+
+use serde_json::{json, Map, Value};
+
+use super::{schema::TYPES, ObjectType, Selection};
+use crate::error::ApiError;
+
+/// The scalars the fields use, with their description.
+const SCALARS: &[(&str, Option<&str>)] = &[
+    ("String", None),
+    ("Int", None),
+    ("Boolean", None),
+    ("U64", Some("An integer that may not fit in 32 bits.")),
+    ("BigDecimal", Some("A decimal serialized as a string.")),
+    ("JSON", None),
+];
+
+/// A reference to `ty` as written in the schema, e.g. `[Transaction!]!`.
+fn type_ref(ty: &str) -> Value {
+    if let Some(inner) = ty.strip_suffix('!') {
+        return json!({
+            "__typename": "__Type",
+            "kind": "NON_NULL",
+            "name": null,
+            "ofType": type_ref(inner),
+        });
+    }
+    if let Some(inner) = ty.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        return json!({
+            "__typename": "__Type",
+            "kind": "LIST",
+            "name": null,
+            "ofType": type_ref(inner),
+        });
+    }
+    let kind = if TYPES.iter().any(|t| t.name == ty) {
+        "OBJECT"
+    } else {
+        "SCALAR"
+    };
+    json!({ "__typename": "__Type", "kind": kind, "name": ty, "ofType": null })
+}
+
+fn object(ty: &ObjectType) -> Value {
+    let fields = ty
+        .fields
+        .iter()
+        .map(|field| {
+            let args = field
+                .args
+                .iter()
+                .map(|(name, ty)| {
+                    json!({
+                        "__typename": "__InputValue",
+                        "name": name,
+                        "description": null,
+                        "type": type_ref(ty),
+                        "defaultValue": null,
+                        "isDeprecated": false,
+                        "deprecationReason": null,
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "__typename": "__Field",
+                "name": field.name,
+                "description": null,
+                "args": args,
+                "type": type_ref(field.ty),
+                "isDeprecated": false,
+                "deprecationReason": null,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "__typename": "__Type",
+        "kind": "OBJECT",
+        "name": ty.name,
+        "description": null,
+        "fields": fields,
+        "interfaces": [],
+        "possibleTypes": null,
+        "enumValues": null,
+        "inputFields": null,
+        "ofType": null,
+        "specifiedByURL": null,
+    })
+}
+
+fn scalar(name: &str, description: Option<&str>) -> Value {
+    json!({
+        "__typename": "__Type",
+        "kind": "SCALAR",
+        "name": name,
+        "description": description,
+        "fields": null,
+        "interfaces": null,
+        "possibleTypes": null,
+        "enumValues": null,
+        "inputFields": null,
+        "ofType": null,
+        "specifiedByURL": null,
+    })
+}
+
+fn types() -> Vec<Value> {
+    SCALARS
+        .iter()
+        .map(|(name, description)| scalar(name, *description))
+        .chain(TYPES.iter().map(object))
+        .collect()
+}
+
+/// The `__Schema`, no directive is supported.
+fn schema() -> Value {
+    let query = TYPES.iter().find(|t| t.name == "Query").map(object);
+    json!({
+        "__typename": "__Schema",
+        "description": null,
+        "queryType": query,
+        "mutationType": null,
+        "subscriptionType": null,
+        "types": types(),
+        "directives": [],
+    })
+}
+
+/// Picks `selections` out of a built introspection value.
+fn project(value: &Value, selections: &[Selection]) -> Result<Value, ApiError> {
+    let object = match value {
+        Value::Array(items) => {
+            return items.iter().map(|v| project(v, selections)).collect();
+        }
+        Value::Object(object) => object,
+        other => return Ok(other.clone()),
+    };
+
+    let typename = object["__typename"].as_str().unwrap_or_default();
+    let mut out = Map::new();
+    for selection in selections {
+        if selection.on.as_deref().is_some_and(|on| on != typename) {
+            continue;
+        }
+        let value = object.get(&selection.name).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "cannot query field {} on type {}",
+                selection.name, typename
+            ))
+        })?;
+        let value = if selection.children.is_empty() {
+            value.clone()
+        } else {
+            project(value, &selection.children)?
+        };
+        out.insert(selection.key.clone(), value);
+    }
+    Ok(Value::Object(out))
+}
+
+/// Resolves an introspection field of the query root.
+pub fn resolve(selection: &Selection) -> Result<Value, ApiError> {
+    match selection.name.as_str() {
+        "__schema" => project(&schema(), &selection.children),
+        "__type" => {
+            let name = selection.args.get("name").and_then(Value::as_str);
+            match types().into_iter().find(|t| t["name"].as_str() == name) {
+                Some(ty) => project(&ty, &selection.children),
+                None => Ok(Value::Null),
+            }
+        }
+        name => Err(ApiError::BadRequest(format!(
+            "cannot query field {} on type Query",
+            name
+        ))),
+    }
+}
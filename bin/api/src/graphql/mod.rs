@@ -0,0 +1,563 @@
+//! A small GraphQL executor over the indexed tables. Queries are run one
+//! level at a time: every relation is loaded for all the parents of a level
+//! with a single query, so a page of blocks with their transactions and
+//! inputs costs three queries, not one per row.
+
+use std::collections::{BTreeMap, HashMap};
+
+use diesel::PgConnection;
+use graphql_parser::query::{
+    Definition, Document, OperationDefinition, Selection as ParsedSelection, SelectionSet,
+    TypeCondition, Value as ParsedValue,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::error::ApiError;
+
+mod introspection;
+mod schema;
+
+pub use schema::sdl;
+
+/// The deepest selection a query may nest.
+const MAX_DEPTH: usize = 10;
+/// The deepest selection under `__schema` or `__type`, which load no rows.
+/// The introspection query of GraphiQL nests a type reference 8 deep.
+const MAX_INTROSPECTION_DEPTH: usize = 20;
+/// The most fields a query may select once its fragments are expanded, so
+/// that fragments spreading each other can't make lowering exponential.
+const MAX_SELECTIONS: usize = 2_000;
+/// The most rows a query may load. `complexity` estimates it before any row
+/// is loaded and `execute` stops a query going over it.
+const MAX_COMPLEXITY: u64 = 10_000;
+
+/// What a relation resolved to for one parent.
+pub enum Related {
+    One(Option<Value>),
+    Many(Vec<Value>),
+}
+
+pub type Loader =
+    fn(&mut PgConnection, &[Value], &Map<String, Value>) -> Result<Vec<Related>, ApiError>;
+
+pub enum Kind {
+    /// A column of the parent row, read from its json `key`.
+    Scalar(&'static str),
+    /// Rows of `target`, `fanout` is how many to expect per parent when
+    /// the field takes no `limit`.
+    Relation {
+        target: &'static str,
+        fanout: u64,
+        load: Loader,
+    },
+}
+
+pub struct Field {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub args: &'static [(&'static str, &'static str)],
+    pub kind: Kind,
+}
+
+pub struct ObjectType {
+    pub name: &'static str,
+    pub fields: &'static [Field],
+}
+
+impl ObjectType {
+    fn field(&self, name: &str) -> Result<&Field, ApiError> {
+        self.fields.iter().find(|f| f.name == name).ok_or_else(|| {
+            ApiError::BadRequest(format!("cannot query field {} on type {}", name, self.name))
+        })
+    }
+}
+
+fn object_type(name: &str) -> &'static ObjectType {
+    schema::TYPES
+        .iter()
+        .find(|t| t.name == name)
+        .expect("relation to an undeclared type")
+}
+
+/// A field of the query with its fragments expanded and its variables
+/// substituted.
+struct Selection {
+    key: String,
+    name: String,
+    args: Map<String, Value>,
+    on: Option<String>,
+    children: Vec<Selection>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub query: String,
+    #[serde(default)]
+    pub variables: Option<Map<String, Value>>,
+    #[serde(default)]
+    pub operation_name: Option<String>,
+}
+
+struct Lowering<'a, 'd> {
+    fragments: HashMap<&'d str, (&'d str, &'d SelectionSet<'a, String>)>,
+    variables: Map<String, Value>,
+    /// The fields lowered so far, every spread of a fragment counts again.
+    lowered: usize,
+}
+
+impl<'a, 'd> Lowering<'a, 'd> {
+    fn value(&self, value: &ParsedValue<'a, String>) -> Value {
+        match value {
+            ParsedValue::Variable(name) => self.variables.get(name).cloned().unwrap_or_default(),
+            ParsedValue::Int(n) => n.as_i64().map_or(Value::Null, |n| json!(n)),
+            ParsedValue::Float(f) => json!(f),
+            ParsedValue::String(s) | ParsedValue::Enum(s) => json!(s),
+            ParsedValue::Boolean(b) => json!(b),
+            ParsedValue::Null => Value::Null,
+            ParsedValue::List(list) => list.iter().map(|v| self.value(v)).collect(),
+            ParsedValue::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.value(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn selections(
+        &mut self,
+        set: &'d SelectionSet<'a, String>,
+        on: Option<&str>,
+        depth: usize,
+        max_depth: usize,
+        out: &mut Vec<Selection>,
+    ) -> Result<(), ApiError> {
+        // a leaf field has an empty set one level below it
+        if depth > max_depth && !set.items.is_empty() {
+            return Err(ApiError::BadRequest(format!(
+                "query is nested deeper than {}",
+                max_depth
+            )));
+        }
+
+        for item in &set.items {
+            match item {
+                ParsedSelection::Field(field) => {
+                    self.lowered += 1;
+                    if self.lowered > MAX_SELECTIONS {
+                        return Err(ApiError::BadRequest(format!(
+                            "query selects more than {} fields",
+                            MAX_SELECTIONS
+                        )));
+                    }
+                    let max_depth = match field.name.as_str() {
+                        "__schema" | "__type" => MAX_INTROSPECTION_DEPTH,
+                        _ => max_depth,
+                    };
+                    let mut children = vec![];
+                    self.selections(
+                        &field.selection_set,
+                        None,
+                        depth + 1,
+                        max_depth,
+                        &mut children,
+                    )?;
+                    let key = field.alias.clone().unwrap_or_else(|| field.name.clone());
+
+                    // the same field selected twice, e.g. by two fragments
+                    if let Some(existing) = out.iter_mut().find(|s| s.key == key) {
+                        existing.children.extend(children);
+                        continue;
+                    }
+                    out.push(Selection {
+                        key,
+                        name: field.name.clone(),
+                        args: field
+                            .arguments
+                            .iter()
+                            .map(|(name, value)| (name.clone(), self.value(value)))
+                            .collect(),
+                        on: on.map(str::to_string),
+                        children,
+                    });
+                }
+                ParsedSelection::InlineFragment(fragment) => {
+                    let on = match &fragment.type_condition {
+                        Some(TypeCondition::On(on)) => Some(on.as_str()),
+                        None => on,
+                    };
+                    self.selections(&fragment.selection_set, on, depth, max_depth, out)?;
+                }
+                ParsedSelection::FragmentSpread(spread) => {
+                    let (on, set) = *self
+                        .fragments
+                        .get(spread.fragment_name.as_str())
+                        .ok_or_else(|| {
+                            ApiError::BadRequest(format!(
+                                "unknown fragment {}",
+                                spread.fragment_name
+                            ))
+                        })?;
+                    // a fragment spreading itself is caught by the depth limit
+                    self.selections(set, Some(on), depth + 1, max_depth, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn lower<'a>(
+    document: &Document<'a, String>,
+    request: &Request,
+) -> Result<Vec<Selection>, ApiError> {
+    let mut lowering = Lowering {
+        fragments: HashMap::new(),
+        variables: Map::new(),
+        lowered: 0,
+    };
+    let mut operations = vec![];
+    for definition in &document.definitions {
+        match definition {
+            Definition::Fragment(fragment) => {
+                let TypeCondition::On(on) = &fragment.type_condition;
+                lowering.fragments.insert(
+                    fragment.name.as_str(),
+                    (on.as_str(), &fragment.selection_set),
+                );
+            }
+            Definition::Operation(operation) => operations.push(operation),
+        }
+    }
+
+    let operation = match &request.operation_name {
+        Some(name) => operations
+            .into_iter()
+            .find(|o| matches!(o, OperationDefinition::Query(q) if q.name.as_ref() == Some(name)))
+            .ok_or_else(|| ApiError::BadRequest(format!("unknown operation {}", name)))?,
+        None if operations.len() == 1 => operations[0],
+        None => {
+            return Err(ApiError::BadRequest(
+                "operationName is required with several operations".to_string(),
+            ))
+        }
+    };
+
+    let set = match operation {
+        OperationDefinition::SelectionSet(set) => set,
+        OperationDefinition::Query(query) => {
+            let provided = request.variables.clone().unwrap_or_default();
+            for definition in &query.variable_definitions {
+                let value = match (provided.get(&definition.name), &definition.default_value) {
+                    (Some(value), _) => value.clone(),
+                    (None, Some(default)) => lowering.value(default),
+                    (None, None) => Value::Null,
+                };
+                lowering.variables.insert(definition.name.clone(), value);
+            }
+            &query.selection_set
+        }
+        _ => {
+            return Err(ApiError::BadRequest(
+                "only queries are supported".to_string(),
+            ))
+        }
+    };
+
+    let mut selections = vec![];
+    lowering.selections(set, None, 1, MAX_DEPTH, &mut selections)?;
+    Ok(selections)
+}
+
+/// An estimate of the rows a selection loads: every relation costs its rows
+/// times the cost of what is selected on them. A relation taking no `limit`
+/// counts its expected `fanout`, which a large block exceeds.
+fn complexity(ty: &ObjectType, selections: &[Selection]) -> Result<u64, ApiError> {
+    let mut total = 0u64;
+    for selection in selections {
+        if selection.on.as_deref().is_some_and(|on| on != ty.name) {
+            continue;
+        }
+        // `__typename` and introspection load no rows
+        if selection.name.starts_with("__") {
+            continue;
+        }
+        let field = ty.field(&selection.name)?;
+        if let Kind::Relation { target, fanout, .. } = field.kind {
+            // a page without a limit still loads the default page size
+            let rows = if field.args.iter().any(|(arg, _)| *arg == "limit") {
+                schema::limit(&selection.args) as u64
+            } else {
+                fanout
+            };
+            total = total.saturating_add(
+                rows.saturating_mul(1 + complexity(object_type(target), &selection.children)?),
+            );
+        }
+    }
+    Ok(total)
+}
+
+fn check_args(field: &Field, selection: &Selection) -> Result<(), ApiError> {
+    for name in selection.args.keys() {
+        if !field.args.iter().any(|(arg, _)| arg == name) {
+            return Err(ApiError::BadRequest(format!(
+                "unknown argument {} on field {}",
+                name, field.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Resolves `selections` on every parent at once, `loaded` counts the rows
+/// loaded by the whole query.
+fn execute(
+    connection: &mut PgConnection,
+    ty: &ObjectType,
+    parents: &[Value],
+    selections: &[Selection],
+    loaded: &mut u64,
+) -> Result<Vec<Map<String, Value>>, ApiError> {
+    let mut out = vec![Map::new(); parents.len()];
+
+    for selection in selections {
+        // every type is concrete, a fragment on another type never applies
+        if selection.on.as_deref().is_some_and(|on| on != ty.name) {
+            continue;
+        }
+        if selection.name == "__typename" {
+            for row in out.iter_mut() {
+                row.insert(selection.key.clone(), json!(ty.name));
+            }
+            continue;
+        }
+        if ty.name == "Query" && selection.name.starts_with("__") {
+            let value = introspection::resolve(selection)?;
+            for row in out.iter_mut() {
+                row.insert(selection.key.clone(), value.clone());
+            }
+            continue;
+        }
+
+        let field = ty.field(&selection.name)?;
+        check_args(field, selection)?;
+        match &field.kind {
+            Kind::Scalar(key) => {
+                if !selection.children.is_empty() {
+                    return Err(ApiError::BadRequest(format!(
+                        "field {} of type {} has no subfields",
+                        field.name, ty.name
+                    )));
+                }
+                for (row, parent) in out.iter_mut().zip(parents) {
+                    row.insert(
+                        selection.key.clone(),
+                        parent.get(key).cloned().unwrap_or_default(),
+                    );
+                }
+            }
+            Kind::Relation { target, load, .. } => {
+                if selection.children.is_empty() {
+                    return Err(ApiError::BadRequest(format!(
+                        "field {} of type {} must have a selection of subfields",
+                        field.name, ty.name
+                    )));
+                }
+
+                let related = load(connection, parents, &selection.args)?;
+                let rows = related
+                    .iter()
+                    .flat_map(|r| match r {
+                        Related::One(one) => one.iter().cloned().collect::<Vec<_>>(),
+                        Related::Many(many) => many.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                *loaded += rows.len() as u64;
+                if *loaded > MAX_COMPLEXITY {
+                    return Err(ApiError::BadRequest(format!(
+                        "query loaded more than {} rows",
+                        MAX_COMPLEXITY
+                    )));
+                }
+                let mut resolved = execute(
+                    connection,
+                    object_type(target),
+                    &rows,
+                    &selection.children,
+                    loaded,
+                )?
+                .into_iter()
+                .map(Value::Object);
+
+                for (row, related) in out.iter_mut().zip(related) {
+                    let value = match related {
+                        Related::One(None) => Value::Null,
+                        Related::One(Some(_)) => resolved.next().unwrap_or_default(),
+                        Related::Many(many) => resolved.by_ref().take(many.len()).collect(),
+                    };
+                    row.insert(selection.key.clone(), value);
+                }
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Runs a query, any error fails the whole request as `{"errors": [..]}`.
+pub fn run(connection: &mut PgConnection, request: &Request) -> Value {
+    let result = graphql_parser::parse_query::<String>(&request.query)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))
+        .and_then(|document| lower(&document, request))
+        .and_then(|selections| {
+            let query = object_type("Query");
+            let cost = complexity(query, &selections)?;
+            if cost > MAX_COMPLEXITY {
+                return Err(ApiError::BadRequest(format!(
+                    "query may load {} rows, the limit is {}",
+                    cost, MAX_COMPLEXITY
+                )));
+            }
+            execute(connection, query, &[Value::Null], &selections, &mut 0)
+        });
+
+    match result {
+        Ok(mut data) => json!({ "data": data.pop() }),
+        Err(e) => {
+            if let ApiError::Internal(cause) = &e {
+                tracing::error!("{}", cause);
+            }
+            json!({ "data": null, "errors": [{ "message": e.to_string() }] })
+        }
+    }
+}
+
+/// Groups serialized `rows` by the json of their `row_key`, which the
+/// `parent_key` of a parent is matched against.
+fn index_rows<R: serde::Serialize>(
+    rows: Vec<R>,
+    row_key: &str,
+) -> Result<BTreeMap<String, Vec<Value>>, ApiError> {
+    let mut index = BTreeMap::<String, Vec<Value>>::new();
+    for row in rows {
+        let row = serde_json::to_value(row).map_err(|e| ApiError::Internal(e.to_string()))?;
+        index.entry(row[row_key].to_string()).or_default().push(row);
+    }
+    Ok(index)
+}
+
+pub fn one_by<R: serde::Serialize>(
+    parents: &[Value],
+    parent_key: &str,
+    rows: Vec<R>,
+    row_key: &str,
+) -> Result<Vec<Related>, ApiError> {
+    // several parents may share a row, e.g. the block of many transactions
+    let index = index_rows(rows, row_key)?;
+    Ok(parents
+        .iter()
+        .map(|p| {
+            Related::One(
+                index
+                    .get(&p[parent_key].to_string())
+                    .and_then(|rows| rows.first().cloned()),
+            )
+        })
+        .collect())
+}
+
+pub fn many_by<R: serde::Serialize>(
+    parents: &[Value],
+    parent_key: &str,
+    rows: Vec<R>,
+    row_key: &str,
+) -> Result<Vec<Related>, ApiError> {
+    let index = index_rows(rows, row_key)?;
+    Ok(parents
+        .iter()
+        .map(|p| {
+            Related::Many(
+                index
+                    .get(&p[parent_key].to_string())
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lowered(query: &str) -> Result<Vec<Selection>, ApiError> {
+        let request = Request {
+            query: query.to_string(),
+            variables: None,
+            operation_name: None,
+        };
+        let document = graphql_parser::parse_query::<String>(query).unwrap();
+        lower(&document, &request)
+    }
+
+    fn cost(query: &str) -> u64 {
+        complexity(object_type("Query"), &lowered(query).unwrap()).unwrap()
+    }
+
+    fn rejected(query: &str, reason: &str) -> bool {
+        matches!(lowered(query), Err(ApiError::BadRequest(e)) if e.contains(reason))
+    }
+
+    #[test]
+    fn fragments_spread_into_each_other_are_capped() {
+        // every fragment spreads the next one 100 times, 100^6 fields
+        let fragments = (0..6)
+            .map(|i| {
+                let spreads = vec![format!("...F{}", i + 1); 100].join(" ");
+                format!("fragment F{} on Block {{ {} }}", i, spreads)
+            })
+            .collect::<String>();
+        let query = format!(
+            "{{ block(height: 1) {{ ...F0 }} }} {} fragment F6 on Block {{ id }}",
+            fragments
+        );
+        assert!(rejected(&query, "fields"));
+    }
+
+    #[test]
+    fn nesting_is_capped() {
+        let nested = |levels: usize| {
+            "{ block(height: 1) ".to_string()
+                + &"{ coinbase { block ".repeat(levels)
+                + "{ id }"
+                + &" } }".repeat(levels)
+                + " }"
+        };
+        assert!(lowered(&nested(4)).is_ok());
+        assert!(rejected(&nested(5), "nested"));
+    }
+
+    #[test]
+    fn complexity_multiplies_limits_and_fanouts() {
+        // 5 blocks, 50 transactions each with 10 inputs
+        assert_eq!(cost("{ blocks(limit: 5) { items { height } } }"), 5 * 2);
+        assert_eq!(
+            cost("{ blocks(limit: 5) { items { transactions { inputs { amount } } } } }"),
+            5 * (1 + (1 + 50 * (1 + 10))),
+        );
+        // a missing limit loads the default page
+        assert_eq!(cost("{ blocks { items { height } } }"), 20 * 2);
+    }
+
+    #[test]
+    fn complexity_skips_fragments_on_other_types() {
+        assert_eq!(
+            cost("{ blocks(limit: 1) { items { ... on Transaction { inputs { amount } } } } }"),
+            2
+        );
+        assert_eq!(cost("{ __schema { types { name } } __typename }"), 0);
+    }
+}
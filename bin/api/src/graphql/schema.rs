@@ -0,0 +1,855 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use diesel::PgConnection;
+use models::{
+    account::{get_account, get_accounts_by_ids},
+    address_transaction::get_address_transactions_by_addresses,
+    assets::{get_alive_assets_by_owners, get_balances_by_owners},
+    block::{get_block_by_height, get_block_by_id, get_blocks, get_blocks_by_heights},
+    call::{get_calls_by_transactions, get_contract_calls_by_contracts},
+    coinbase::{get_coinbase, get_coinbases_by_heights},
+    contract::{get_contract, get_contracts_by_ids},
+    contract_balance::get_balances_by_contracts,
+    pagination::{page_size, Page},
+    token_transfer::{get_token_transfers_by_addresses, get_token_transfers_by_transactions},
+    transaction::{
+        get_transaction, get_transactions, get_transactions_by_heights, get_transactions_by_ids,
+    },
+    transaction_failure::get_failures_by_transactions,
+    tx_input::get_inputs_by_transactions,
+    tx_output::get_outputs_by_transactions,
+};
+use serde_json::{json, Map, Value};
+
+use super::{many_by, one_by, Field, Kind, Loader, ObjectType, Related};
+use crate::{error::ApiError, handlers::page, routes::parse_id};
+
+const fn scalar(name: &'static str, ty: &'static str, key: &'static str) -> Field {
+    Field {
+        name,
+        ty,
+        args: &[],
+        kind: Kind::Scalar(key),
+    }
+}
+
+const fn relation(
+    name: &'static str,
+    ty: &'static str,
+    args: &'static [(&'static str, &'static str)],
+    target: &'static str,
+    fanout: u64,
+    load: Loader,
+) -> Field {
+    Field {
+        name,
+        ty,
+        args,
+        kind: Kind::Relation {
+            target,
+            fanout,
+            load,
+        },
+    }
+}
+
+const PAGE_ARGS: &[(&str, &str)] = &[("before", "String"), ("limit", "Int")];
+
+fn arg<'a>(args: &'a Map<String, Value>, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(Value::as_str)
+}
+
+fn hex_arg(args: &Map<String, Value>, name: &str) -> Result<Option<String>, ApiError> {
+    arg(args, name).map(parse_id).transpose()
+}
+
+/// The page size a relation taking a `limit` loads, 20 when it is absent.
+pub(super) fn limit(args: &Map<String, Value>) -> i64 {
+    page_size(args.get("limit").and_then(Value::as_i64).unwrap_or(20))
+}
+
+fn before<C: FromStr>(args: &Map<String, Value>) -> Result<Option<C>, ApiError> {
+    arg(args, "before")
+        .map(|c| {
+            c.parse()
+                .map_err(|_| ApiError::BadRequest(format!("invalid cursor {}", c)))
+        })
+        .transpose()
+}
+
+fn keys(parents: &[Value], key: &str) -> Vec<String> {
+    let mut keys = parents
+        .iter()
+        .filter_map(|p| p[key].as_str().map(str::to_string))
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+fn heights(parents: &[Value], key: &str) -> Vec<i64> {
+    let mut heights = parents
+        .iter()
+        .filter_map(|p| p[key].as_i64())
+        .collect::<Vec<_>>();
+    heights.sort();
+    heights.dedup();
+    heights
+}
+
+fn root(value: Option<Value>) -> Result<Vec<Related>, ApiError> {
+    Ok(vec![Related::One(value)])
+}
+
+// --- root fields, resolved once on the single `Query` parent
+
+fn query_block(
+    connection: &mut PgConnection,
+    _: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let block = match (
+        args.get("height").and_then(Value::as_i64),
+        hex_arg(args, "id")?,
+    ) {
+        (Some(height), _) => get_block_by_height(connection, height)?,
+        (None, Some(id)) => get_block_by_id(connection, &id)?,
+        (None, None) => {
+            return Err(ApiError::BadRequest(
+                "block needs a height or an id".to_string(),
+            ))
+        }
+    };
+    root(block.map(|b| json!(b)))
+}
+
+fn query_blocks(
+    connection: &mut PgConnection,
+    _: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    root(Some(page(get_blocks(
+        connection,
+        before(args)?,
+        limit(args),
+    )?)?))
+}
+
+fn query_transaction(
+    connection: &mut PgConnection,
+    _: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let id = hex_arg(args, "id")?.unwrap_or_default();
+    root(get_transaction(connection, &id)?.map(|t| json!(t)))
+}
+
+fn query_transactions(
+    connection: &mut PgConnection,
+    _: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    root(Some(page(get_transactions(
+        connection,
+        before(args)?,
+        limit(args),
+    )?)?))
+}
+
+fn query_coinbase(
+    connection: &mut PgConnection,
+    _: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let id = hex_arg(args, "id")?.unwrap_or_default();
+    root(get_coinbase(connection, &id)?.map(|c| json!(c)))
+}
+
+fn query_contract(
+    connection: &mut PgConnection,
+    _: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let id = hex_arg(args, "id")?.unwrap_or_default();
+    root(get_contract(connection, &id)?.map(|c| json!(c)))
+}
+
+fn query_account(
+    connection: &mut PgConnection,
+    _: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let address = hex_arg(args, "address")?.unwrap_or_default();
+    root(get_account(connection, &address)?.map(|a| json!(a)))
+}
+
+// --- relations, batched over every parent of a level
+
+fn page_items(
+    _: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    Ok(parents
+        .iter()
+        .map(|p| Related::Many(p["items"].as_array().cloned().unwrap_or_default()))
+        .collect())
+}
+
+fn block_by_height(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let blocks = get_blocks_by_heights(connection, &heights(parents, "height"))?;
+    one_by(parents, "height", blocks, "height")
+}
+
+fn block_transactions(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let transactions = get_transactions_by_heights(connection, &heights(parents, "height"))?;
+    many_by(parents, "height", transactions, "height")
+}
+
+fn block_coinbase(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let coinbases = get_coinbases_by_heights(connection, &heights(parents, "height"))?;
+    one_by(parents, "height", coinbases, "height")
+}
+
+fn transaction_call(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let calls = get_calls_by_transactions(connection, &keys(parents, "id"))?;
+    one_by(parents, "id", calls, "transaction_id")
+}
+
+fn transaction_failure(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let failures = get_failures_by_transactions(connection, &keys(parents, "id"))?;
+    one_by(parents, "id", failures, "transaction_id")
+}
+
+fn transaction_inputs(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let inputs = get_inputs_by_transactions(connection, &keys(parents, "id"))?;
+    many_by(parents, "id", inputs, "transaction_id")
+}
+
+fn transaction_outputs(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let outputs = get_outputs_by_transactions(connection, &keys(parents, "id"))?;
+    many_by(parents, "id", outputs, "transaction_id")
+}
+
+fn transaction_token_transfers(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let transfers = get_token_transfers_by_transactions(connection, &keys(parents, "id"))?;
+    many_by(parents, "id", transfers, "transaction_id")
+}
+
+fn transaction_by_id(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let transactions = get_transactions_by_ids(connection, &keys(parents, "transaction_id"))?;
+    one_by(parents, "transaction_id", transactions, "id")
+}
+
+fn call_contract(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let contracts = get_contracts_by_ids(connection, &keys(parents, "receiver"))?;
+    one_by(parents, "receiver", contracts, "contract_hash")
+}
+
+fn contract_balances(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let balances = get_balances_by_contracts(connection, &keys(parents, "contract_hash"))?;
+    many_by(parents, "contract_hash", balances, "contract_hash")
+}
+
+// paginated relations apply the same cursor and limit to the page of every
+// parent, loaded with one windowed query
+fn pages<T, C>(
+    parents: &[Value],
+    parent_key: &str,
+    pages: BTreeMap<String, Page<T, C>>,
+) -> Result<Vec<Related>, ApiError>
+where
+    T: serde::Serialize,
+    C: std::fmt::Display,
+{
+    let pages = pages
+        .into_iter()
+        .map(|(key, p)| Ok((key, page(p)?)))
+        .collect::<Result<BTreeMap<_, _>, ApiError>>()?;
+    // several parents may share a key, e.g. the calls to one contract
+    let empty = json!({ "items": [], "next": null });
+    Ok(parents
+        .iter()
+        .map(|p| {
+            let page_of = pages
+                .get(p[parent_key].as_str().unwrap_or_default())
+                .unwrap_or(&empty);
+            Related::One(Some(page_of.clone()))
+        })
+        .collect())
+}
+
+fn contract_calls(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let calls = get_contract_calls_by_contracts(
+        connection,
+        &keys(parents, "contract_hash"),
+        before(args)?,
+        limit(args),
+    )?;
+    pages(parents, "contract_hash", calls)
+}
+
+fn account_by_id(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let accounts = get_accounts_by_ids(connection, &keys(parents, "assets_owner"))?;
+    one_by(parents, "assets_owner", accounts, "account_hash")
+}
+
+fn account_balances(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    _: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let balances = get_balances_by_owners(connection, &keys(parents, "account_hash"))?
+        .into_iter()
+        .map(|(owner, asset_id, amount)| {
+            json!({ "owner": owner, "asset_id": asset_id, "amount": amount })
+        })
+        .collect::<Vec<_>>();
+    many_by(parents, "account_hash", balances, "owner")
+}
+
+fn account_utxos(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let utxos = get_alive_assets_by_owners(
+        connection,
+        &keys(parents, "account_hash"),
+        hex_arg(args, "assetId")?.as_deref(),
+        before(args)?,
+        limit(args),
+    )?;
+    pages(parents, "account_hash", utxos)
+}
+
+fn account_transfers(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let transfers =
+        get_token_transfers_by_addresses(connection, &keys(parents, "account_hash"), limit(args))?;
+    Ok(parents
+        .iter()
+        .map(|p| {
+            let transfers = transfers
+                .get(p["account_hash"].as_str().unwrap_or_default())
+                .map(|t| t.iter().map(|t| json!(t)).collect())
+                .unwrap_or_default();
+            Related::Many(transfers)
+        })
+        .collect())
+}
+
+fn account_transactions(
+    connection: &mut PgConnection,
+    parents: &[Value],
+    args: &Map<String, Value>,
+) -> Result<Vec<Related>, ApiError> {
+    let transactions = get_address_transactions_by_addresses(
+        connection,
+        &keys(parents, "account_hash"),
+        before(args)?,
+        limit(args),
+    )?
+    .into_iter()
+    .map(|(address, page)| (address, page.map(|(_, t)| t)))
+    .collect();
+    pages(parents, "account_hash", transactions)
+}
+
+pub const TYPES: &[ObjectType] = &[
+    ObjectType {
+        name: "Query",
+        fields: &[
+            relation(
+                "block",
+                "Block",
+                &[("height", "U64"), ("id", "String")],
+                "Block",
+                1,
+                query_block,
+            ),
+            relation(
+                "blocks",
+                "BlockPage!",
+                PAGE_ARGS,
+                "BlockPage",
+                1,
+                query_blocks,
+            ),
+            relation(
+                "transaction",
+                "Transaction",
+                &[("id", "String!")],
+                "Transaction",
+                1,
+                query_transaction,
+            ),
+            relation(
+                "transactions",
+                "TransactionPage!",
+                PAGE_ARGS,
+                "TransactionPage",
+                1,
+                query_transactions,
+            ),
+            relation(
+                "coinbase",
+                "Coinbase",
+                &[("id", "String!")],
+                "Coinbase",
+                1,
+                query_coinbase,
+            ),
+            relation(
+                "contract",
+                "Contract",
+                &[("id", "String!")],
+                "Contract",
+                1,
+                query_contract,
+            ),
+            relation(
+                "account",
+                "Account",
+                &[("address", "String!")],
+                "Account",
+                1,
+                query_account,
+            ),
+        ],
+    },
+    ObjectType {
+        name: "Block",
+        fields: &[
+            scalar("id", "String!", "id"),
+            scalar("height", "U64!", "height"),
+            scalar("daHeight", "U64!", "da_height"),
+            scalar("timestamp", "U64!", "timestamp"),
+            scalar("applicationHash", "String!", "application_hash"),
+            scalar(
+                "outputMessagesRootHash",
+                "String!",
+                "output_messages_root_hash",
+            ),
+            scalar("transactionsRoot", "String!", "transactions_root"),
+            scalar("prevRoot", "String!", "prev_root"),
+            scalar("eventInboxRoot", "String!", "event_inbox_root"),
+            scalar("transactionCount", "U64!", "transaction_count"),
+            scalar("outputMessageCount", "U64!", "output_message_count"),
+            scalar(
+                "consensusParametersVersion",
+                "U64!",
+                "consensus_parameters_version",
+            ),
+            scalar(
+                "stateTransitionBytecodeVersion",
+                "U64!",
+                "state_transition_bytecode_version",
+            ),
+            scalar("consensus", "JSON", "consensus"),
+            scalar("producer", "String", "producer"),
+            scalar("coinbaseContract", "String", "coinbase"),
+            scalar("coinbaseAmount", "U64", "coinbase_amount"),
+            relation(
+                "transactions",
+                "[Transaction!]!",
+                &[],
+                "Transaction",
+                50,
+                block_transactions,
+            ),
+            relation("coinbase", "Coinbase", &[], "Coinbase", 1, block_coinbase),
+        ],
+    },
+    ObjectType {
+        name: "Coinbase",
+        fields: &[
+            scalar("id", "String!", "id"),
+            scalar("height", "U64!", "height"),
+            scalar("txIndex", "U64!", "tx_index"),
+            scalar("daHeight", "U64!", "da_height"),
+            scalar("blockHash", "String!", "block_hash"),
+            scalar("amount", "U64", "amount"),
+            scalar("contract", "String", "coinbase"),
+            scalar("timestamp", "U64", "timestamp"),
+            relation("block", "Block!", &[], "Block", 1, block_by_height),
+        ],
+    },
+    ObjectType {
+        name: "Transaction",
+        fields: &[
+            scalar("id", "String!", "id"),
+            scalar("height", "U64!", "height"),
+            scalar("txIndex", "U64!", "tx_index"),
+            scalar("daHeight", "U64!", "da_height"),
+            scalar("blockHash", "String!", "block_hash"),
+            scalar("txType", "String", "tx_type"),
+            scalar("gasLimit", "U64!", "gas_limit"),
+            scalar("gasPrice", "U64!", "gas_price"),
+            scalar("gasUsed", "U64!", "gas_used"),
+            scalar("fee", "U64!", "fee"),
+            scalar("timestamp", "U64!", "timestamp"),
+            scalar("sender", "String", "sender"),
+            scalar("status", "String!", "status"),
+            scalar("reason", "String!", "reason"),
+            scalar("receipts", "JSON", "receipts"),
+            scalar("scriptHash", "String", "script_hash"),
+            scalar("scriptCategory", "String", "script_category"),
+            relation("block", "Block!", &[], "Block", 1, block_by_height),
+            relation("call", "Call", &[], "Call", 1, transaction_call),
+            relation("failure", "Failure", &[], "Failure", 1, transaction_failure),
+            relation("inputs", "[Input!]!", &[], "Input", 10, transaction_inputs),
+            relation(
+                "outputs",
+                "[Output!]!",
+                &[],
+                "Output",
+                10,
+                transaction_outputs,
+            ),
+            relation(
+                "tokenTransfers",
+                "[TokenTransfer!]!",
+                &[],
+                "TokenTransfer",
+                10,
+                transaction_token_transfers,
+            ),
+        ],
+    },
+    ObjectType {
+        name: "Call",
+        fields: &[
+            scalar("transactionId", "String!", "transaction_id"),
+            scalar("height", "U64!", "height"),
+            scalar("txIndex", "U64!", "tx_index"),
+            scalar("callType", "String!", "call_type"),
+            scalar("gasLimit", "U64!", "gas_limit"),
+            scalar("gasPrice", "U64!", "gas_price"),
+            scalar("gasUsed", "U64!", "gas_used"),
+            scalar("sender", "String!", "sender"),
+            scalar("receiver", "String!", "receiver"),
+            scalar("amount", "U64", "amount"),
+            scalar("assetId", "String", "asset_id"),
+            scalar("payload", "String", "payload"),
+            scalar("payloadData", "String", "payload_data"),
+            scalar("receiptIndex", "U64", "receipt_index"),
+            scalar("timestamp", "U64!", "timestamp"),
+            relation(
+                "transaction",
+                "Transaction",
+                &[],
+                "Transaction",
+                1,
+                transaction_by_id,
+            ),
+            relation("contract", "Contract", &[], "Contract", 1, call_contract),
+        ],
+    },
+    ObjectType {
+        name: "Input",
+        fields: &[
+            scalar("transactionId", "String!", "transaction_id"),
+            scalar("inputIndex", "U64!", "input_index"),
+            scalar("inputType", "String!", "input_type"),
+            scalar("utxoId", "String", "utxo_id"),
+            scalar("owner", "String", "owner"),
+            scalar("sender", "String", "sender"),
+            scalar("assetId", "String", "asset_id"),
+            scalar("amount", "U64", "amount"),
+            scalar("txPointerHeight", "U64", "tx_pointer_height"),
+            scalar("txPointerIndex", "U64", "tx_pointer_index"),
+            scalar("contractId", "String", "contract_id"),
+            scalar("nonce", "String", "nonce"),
+            scalar("height", "U64!", "height"),
+            scalar("timestamp", "U64!", "timestamp"),
+        ],
+    },
+    ObjectType {
+        name: "Output",
+        fields: &[
+            scalar("transactionId", "String!", "transaction_id"),
+            scalar("outputIndex", "U64!", "output_index"),
+            scalar("outputType", "String!", "output_type"),
+            scalar("utxoId", "String", "utxo_id"),
+            scalar("to", "String", "to_address"),
+            scalar("assetId", "String", "asset_id"),
+            scalar("amount", "U64", "amount"),
+            scalar("inputIndex", "U64", "input_index"),
+            scalar("contractId", "String", "contract_id"),
+            scalar("height", "U64!", "height"),
+            scalar("timestamp", "U64!", "timestamp"),
+        ],
+    },
+    ObjectType {
+        name: "TokenTransfer",
+        fields: &[
+            scalar("transactionId", "String!", "transaction_id"),
+            scalar("transferIndex", "U64!", "transfer_index"),
+            scalar("transferType", "String!", "transfer_type"),
+            scalar("outputIndex", "U64", "output_index"),
+            scalar("receiptIndex", "U64", "receipt_index"),
+            scalar("assetId", "String!", "asset_id"),
+            scalar("from", "String", "from_address"),
+            scalar("to", "String", "to_address"),
+            scalar("amount", "U64!", "amount"),
+            scalar("height", "U64!", "height"),
+            scalar("txIndex", "U64!", "tx_index"),
+            scalar("timestamp", "U64!", "timestamp"),
+            relation(
+                "transaction",
+                "Transaction",
+                &[],
+                "Transaction",
+                1,
+                transaction_by_id,
+            ),
+        ],
+    },
+    ObjectType {
+        name: "Failure",
+        fields: &[
+            scalar("transactionId", "String!", "transaction_id"),
+            scalar("kind", "String!", "kind"),
+            scalar("receiptIndex", "U64", "receipt_index"),
+            scalar("contractId", "String", "contract_id"),
+            scalar("panicReason", "String", "panic_reason"),
+            scalar("revertCode", "String", "revert_code"),
+            scalar("signal", "String", "signal"),
+            scalar("loggedError", "JSON", "logged_error"),
+            scalar("message", "String!", "message"),
+            scalar("nodeReason", "String!", "node_reason"),
+        ],
+    },
+    ObjectType {
+        name: "Contract",
+        fields: &[
+            scalar("id", "String!", "contract_hash"),
+            scalar("transactionId", "String!", "transaction_id"),
+            scalar("sender", "String!", "sender"),
+            scalar("bytecode", "String!", "bytecode"),
+            scalar("bytecodeLength", "U64!", "bytecoin_length"),
+            scalar("storageSlots", "JSON", "storage_slots"),
+            scalar("timestamp", "U64!", "timestamp"),
+            relation(
+                "balances",
+                "[ContractBalance!]!",
+                &[],
+                "ContractBalance",
+                10,
+                contract_balances,
+            ),
+            relation(
+                "calls",
+                "CallPage!",
+                PAGE_ARGS,
+                "CallPage",
+                1,
+                contract_calls,
+            ),
+        ],
+    },
+    ObjectType {
+        name: "ContractBalance",
+        fields: &[
+            scalar("contractId", "String!", "contract_hash"),
+            scalar("assetId", "String!", "asset_id"),
+            scalar("amount", "BigDecimal!", "amount"),
+            scalar("blockHeight", "U64!", "block_height"),
+        ],
+    },
+    ObjectType {
+        name: "Account",
+        fields: &[
+            scalar("address", "String!", "account_hash"),
+            scalar("accountType", "String!", "account_type"),
+            scalar("accountName", "String", "account_name"),
+            scalar("verified", "Boolean!", "verified"),
+            scalar("gasUsed", "U64!", "gas_used"),
+            scalar("feePaid", "U64!", "fee_paid"),
+            scalar("transactionsCount", "U64!", "transactions_count"),
+            scalar("tokenTransfersCount", "U64!", "token_transfers_count"),
+            scalar("senderCount", "U64!", "sender_count"),
+            scalar("receiverCount", "U64!", "recever_count"),
+            scalar("firstSeenHeight", "U64", "first_seen_height"),
+            scalar("lastSeenHeight", "U64", "last_seen_height"),
+            relation(
+                "balances",
+                "[Balance!]!",
+                &[],
+                "Balance",
+                10,
+                account_balances,
+            ),
+            relation(
+                "utxos",
+                "UtxoPage!",
+                &[
+                    ("assetId", "String"),
+                    ("before", "String"),
+                    ("limit", "Int"),
+                ],
+                "UtxoPage",
+                1,
+                account_utxos,
+            ),
+            relation(
+                "transfers",
+                "[TokenTransfer!]!",
+                &[("limit", "Int")],
+                "TokenTransfer",
+                20,
+                account_transfers,
+            ),
+            relation(
+                "transactions",
+                "TransactionPage!",
+                PAGE_ARGS,
+                "TransactionPage",
+                1,
+                account_transactions,
+            ),
+        ],
+    },
+    ObjectType {
+        name: "Balance",
+        fields: &[
+            scalar("owner", "String!", "owner"),
+            scalar("assetId", "String!", "asset_id"),
+            scalar("amount", "BigDecimal!", "amount"),
+        ],
+    },
+    ObjectType {
+        name: "Utxo",
+        fields: &[
+            scalar("utxoId", "String!", "assets_utxo_id"),
+            scalar("assetId", "String!", "assets_id"),
+            scalar("owner", "String!", "assets_owner"),
+            scalar("amount", "U64!", "amount"),
+            scalar("createHeight", "U64!", "create_height"),
+            scalar("createTransactionId", "String!", "create_tx_hash"),
+            relation("account", "Account", &[], "Account", 1, account_by_id),
+        ],
+    },
+    ObjectType {
+        name: "BlockPage",
+        fields: &[
+            relation("items", "[Block!]!", &[], "Block", 1, page_items),
+            scalar("next", "String", "next"),
+        ],
+    },
+    ObjectType {
+        name: "TransactionPage",
+        fields: &[
+            relation(
+                "items",
+                "[Transaction!]!",
+                &[],
+                "Transaction",
+                1,
+                page_items,
+            ),
+            scalar("next", "String", "next"),
+        ],
+    },
+    ObjectType {
+        name: "CallPage",
+        fields: &[
+            relation("items", "[Call!]!", &[], "Call", 1, page_items),
+            scalar("next", "String", "next"),
+        ],
+    },
+    ObjectType {
+        name: "UtxoPage",
+        fields: &[
+            relation("items", "[Utxo!]!", &[], "Utxo", 1, page_items),
+            scalar("next", "String", "next"),
+        ],
+    },
+];
+
+/// The schema in the GraphQL schema language, for clients and codegen.
+pub fn sdl() -> String {
+    let mut sdl = "\"\"\"An integer that may not fit in 32 bits.\"\"\"\nscalar U64\n\
+        \"\"\"A decimal serialized as a string.\"\"\"\nscalar BigDecimal\n\
+        scalar JSON\n\nschema {\n  query: Query\n}\n"
+        .to_string();
+
+    for ty in TYPES {
+        sdl.push_str(&format!("\ntype {} {{\n", ty.name));
+        for field in ty.fields {
+            let args = field
+                .args
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, ty))
+                .collect::<Vec<_>>();
+            if args.is_empty() {
+                sdl.push_str(&format!("  {}: {}\n", field.name, field.ty));
+            } else {
+                sdl.push_str(&format!(
+                    "  {}({}): {}\n",
+                    field.name,
+                    args.join(", "),
+                    field.ty
+                ));
+            }
+        }
+        sdl.push_str("}\n");
+    }
+    sdl
+}
@@ -0,0 +1,307 @@
+use std::fmt::Display;
+
+use diesel::PgConnection;
+use models::{
+    account::get_account,
+    address_transaction::{get_address_transaction_count, get_address_transactions},
+    asset_holder::{get_asset_holder_stats, get_asset_holders},
+    assets::{get_alive_assets, get_owner_balances, get_owner_balances_at_height},
+    block::{get_block_by_height, get_block_by_id, get_blocks, get_indexed_height, Block},
+    call::{get_contract_calls, get_transaction_call},
+    chain_stats::{get_chain_stats, Granularity},
+    coinbase::{get_block_coinbase, get_coinbase},
+    consensus_parameters::{get_consensus_parameters, get_consensus_parameters_changes},
+    contract::get_contract,
+    contract_balance::get_contract_balances,
+    message::{get_message_proof, get_messages_by_address},
+    pagination::{Cursor, Page, UtxoCursor},
+    search::search as search_entities,
+    token_transfer::get_transaction_token_transfers,
+    transaction::{get_block_transactions, get_transaction, get_transactions},
+    transaction_failure::get_transaction_failure,
+    tx_input::get_transaction_inputs,
+    tx_output::get_transaction_outputs,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{error::ApiError, routes::Params};
+
+pub fn page<T: Serialize, C: Display>(page: Page<T, C>) -> Result<Value, ApiError> {
+    Ok(json!({
+        "items": page.items,
+        "next": page.next.map(|c| c.to_string()),
+    }))
+}
+
+fn found<T: Serialize>(value: Option<T>, what: String) -> Result<Value, ApiError> {
+    value.map(|v| json!(v)).ok_or(ApiError::NotFound(what))
+}
+
+// a block is addressed by its height or its id
+fn find_block(connection: &mut PgConnection, params: &Params) -> Result<Block, ApiError> {
+    let block = params.path("block");
+    match block.parse::<i64>() {
+        Ok(height) => get_block_by_height(connection, height)?,
+        Err(_) => get_block_by_id(connection, &params.hex("block")?)?,
+    }
+    .ok_or_else(|| ApiError::NotFound(format!("block {}", block)))
+}
+
+pub fn blocks(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    page(get_blocks(
+        connection,
+        params.before::<Cursor>()?,
+        params.limit()?,
+    )?)
+}
+
+pub fn block(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    Ok(json!(find_block(connection, params)?))
+}
+
+pub fn block_transactions(
+    connection: &mut PgConnection,
+    params: &Params,
+) -> Result<Value, ApiError> {
+    let block = find_block(connection, params)?;
+    Ok(json!({
+        "coinbase": get_block_coinbase(connection, block.height)?,
+        "transactions": get_block_transactions(connection, block.height)?,
+    }))
+}
+
+pub fn transactions(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    page(get_transactions(
+        connection,
+        params.before::<Cursor>()?,
+        params.limit()?,
+    )?)
+}
+
+pub fn transaction(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    let id = params.hex("transaction")?;
+
+    // mint transactions are only kept in `coinbases`
+    let mut detail = match get_transaction(connection, &id)? {
+        Some(transaction) => json!({
+            "transaction": transaction,
+            "call": get_transaction_call(connection, &id)?,
+            "failure": get_transaction_failure(connection, &id)?,
+        }),
+        None => json!({
+            "coinbase": get_coinbase(connection, &id)?
+                .ok_or_else(|| ApiError::NotFound(format!("transaction {}", id)))?,
+        }),
+    };
+    detail["inputs"] = json!(get_transaction_inputs(connection, &id)?);
+    detail["outputs"] = json!(get_transaction_outputs(connection, &id)?);
+    detail["token_transfers"] = json!(get_transaction_token_transfers(connection, &id)?);
+
+    Ok(detail)
+}
+
+pub fn contract(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    let id = params.hex("contract")?;
+    found(get_contract(connection, &id)?, format!("contract {}", id))
+}
+
+pub fn contract_calls(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    page(get_contract_calls(
+        connection,
+        &params.hex("contract")?,
+        params.before::<Cursor>()?,
+        params.limit()?,
+    )?)
+}
+
+pub fn contract_balances(
+    connection: &mut PgConnection,
+    params: &Params,
+) -> Result<Value, ApiError> {
+    Ok(json!(get_contract_balances(
+        connection,
+        &params.hex("contract")?
+    )?))
+}
+
+pub fn account(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    let id = params.hex("account")?;
+    found(get_account(connection, &id)?, format!("account {}", id))
+}
+
+pub fn account_transactions(
+    connection: &mut PgConnection,
+    params: &Params,
+) -> Result<Value, ApiError> {
+    let account = params.hex("account")?;
+    let history = get_address_transactions(
+        connection,
+        &account,
+        params.before::<Cursor>()?,
+        params.limit()?,
+    )?;
+
+    let mut value = page(history.map(|(entry, transaction)| {
+        let mut transaction = json!(transaction);
+        transaction["roles"] = json!(entry.roles);
+        transaction
+    }))?;
+    value["total"] = json!(get_address_transaction_count(connection, &account)?);
+    Ok(value)
+}
+
+pub fn account_utxos(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    page(get_alive_assets(
+        connection,
+        &params.hex("account")?,
+        params.query_hex("asset_id")?.as_deref(),
+        params.before::<UtxoCursor>()?,
+        params.limit()?,
+    )?)
+}
+
+pub fn account_balances(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    let account = params.hex("account")?;
+    let asset_id = params.query_hex("asset_id")?;
+    let height = params
+        .query
+        .get("height")
+        .map(|h| {
+            h.parse::<i64>()
+                .map_err(|_| ApiError::BadRequest(format!("invalid height {}", h)))
+        })
+        .transpose()?;
+
+    let mut balances = match height {
+        Some(height) => {
+            // blocks are committed out of order, the coins of a block below
+            // `height` that is not indexed yet would be missing
+            if get_indexed_height(connection)?
+                .filter(|indexed| *indexed >= height)
+                .is_none()
+            {
+                return Err(ApiError::NotFound(format!("block {}", height)));
+            }
+            get_owner_balances_at_height(connection, &account, asset_id.as_deref(), height)?
+        }
+        None => {
+            let mut balances = get_owner_balances(connection, &account)?;
+            if let Some(asset_id) = &asset_id {
+                balances.retain(|(id, _)| id == asset_id);
+            }
+            balances
+        }
+    };
+    // an asset asked for is listed even when nothing is held
+    if let Some(asset_id) = asset_id {
+        if balances.is_empty() {
+            balances.push((asset_id, Default::default()));
+        }
+    }
+
+    Ok(json!(balances
+        .into_iter()
+        .map(|(asset_id, amount)| json!({ "asset_id": asset_id, "amount": amount }))
+        .collect::<Vec<_>>()))
+}
+
+pub fn account_messages(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    page(get_messages_by_address(
+        connection,
+        &params.hex("account")?,
+        params.before::<UtxoCursor>()?,
+        params.limit()?,
+    )?)
+}
+
+pub fn asset_holders(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    page(get_asset_holders(
+        connection,
+        &params.hex("asset")?,
+        params.before::<i64>()?,
+        params.limit()?,
+    )?)
+}
+
+pub fn asset_holder_stats(
+    connection: &mut PgConnection,
+    params: &Params,
+) -> Result<Value, ApiError> {
+    page(get_asset_holder_stats(
+        connection,
+        &params.hex("asset")?,
+        params.before::<i64>()?,
+        params.limit()?,
+    )?)
+}
+
+pub fn chain_stats(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    let granularity = params.path("granularity");
+    let granularity = granularity.parse::<Granularity>().map_err(|_| {
+        ApiError::BadRequest(format!("{} is not one of minute, hour or day", granularity))
+    })?;
+    let stats = get_chain_stats(
+        connection,
+        granularity,
+        params.before::<i64>()?,
+        params.limit()?,
+    )?;
+
+    page(stats.map(|s| {
+        let mut value = json!(s);
+        value["average_block_time"] = json!(s.average_block_time());
+        value["tps"] = json!(s.tps());
+        value
+    }))
+}
+
+fn version(value: &str) -> Result<i64, ApiError> {
+    value
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("invalid version {}", value)))
+}
+
+pub fn consensus_parameters(
+    connection: &mut PgConnection,
+    params: &Params,
+) -> Result<Value, ApiError> {
+    let version = version(params.path("version"))?;
+    found(
+        get_consensus_parameters(connection, version)?,
+        format!("consensus parameters version {}", version),
+    )
+}
+
+pub fn consensus_parameters_changes(
+    connection: &mut PgConnection,
+    params: &Params,
+) -> Result<Value, ApiError> {
+    let to = version(params.path("version"))?;
+    let from = params
+        .query
+        .get("from")
+        .map(|v| version(v))
+        .transpose()?
+        .unwrap_or(to - 1);
+    found(
+        get_consensus_parameters_changes(connection, from, to)?,
+        format!("consensus parameters versions {} and {}", from, to),
+    )
+}
+
+pub fn message_proof(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    let nonce = params.hex("nonce")?;
+    found(
+        get_message_proof(connection, &nonce)?,
+        format!("withdrawal {}", nonce),
+    )
+}
+
+pub fn search(connection: &mut PgConnection, params: &Params) -> Result<Value, ApiError> {
+    let query = params
+        .query
+        .get("q")
+        .ok_or_else(|| ApiError::BadRequest("q is required".to_string()))?;
+    Ok(json!(search_entities(connection, query, params.limit()?)?))
+}
@@ -0,0 +1,177 @@
+use std::{collections::HashMap, convert::Infallible, net::SocketAddr};
+
+use diesel::{r2d2::ConnectionManager, PgConnection};
+use error::ApiError;
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use models::PgSqlPool;
+use routes::Params;
+use tracing_subscriber::FmtSubscriber;
+
+mod error;
+mod graphql;
+mod handlers;
+mod openapi;
+mod routes;
+
+pub fn json_response(status: StatusCode, value: &serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Body::from(value.to_string()))
+        .unwrap()
+}
+
+/// Answers a CORS preflight, browsers send one before posting json to
+/// `/graphql` from another origin.
+fn preflight() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, OPTIONS")
+        .header(header::ACCESS_CONTROL_ALLOW_HEADERS, "content-type")
+        .header(header::ACCESS_CONTROL_MAX_AGE, "86400")
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Largest GraphQL request body accepted.
+const MAX_BODY: u64 = 64 * 1024;
+
+async fn graphql(pool: PgSqlPool, req: Request<Body>) -> Result<Response<Body>, ApiError> {
+    let request = match *req.method() {
+        Method::GET => serde_urlencoded::from_str::<HashMap<String, String>>(
+            req.uri().query().unwrap_or_default(),
+        )
+        .map_err(|e| ApiError::BadRequest(e.to_string()))
+        .and_then(|mut query| {
+            Ok(graphql::Request {
+                query: query
+                    .remove("query")
+                    .ok_or_else(|| ApiError::BadRequest("query is required".to_string()))?,
+                variables: query
+                    .get("variables")
+                    .map(|v| serde_json::from_str(v))
+                    .transpose()
+                    .map_err(|e| ApiError::BadRequest(e.to_string()))?,
+                operation_name: query.remove("operationName"),
+            })
+        })?,
+        Method::POST => {
+            // a body without a length could be of any size
+            let size = hyper::body::HttpBody::size_hint(req.body()).upper();
+            if !matches!(size, Some(size) if size <= MAX_BODY) {
+                return Err(ApiError::BadRequest(format!(
+                    "the body must be at most {} bytes",
+                    MAX_BODY
+                )));
+            }
+            let body = hyper::body::to_bytes(req.into_body())
+                .await
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            serde_json::from_slice(&body).map_err(|e| ApiError::BadRequest(e.to_string()))?
+        }
+        Method::OPTIONS => return Ok(preflight()),
+        _ => return Err(ApiError::MethodNotAllowed),
+    };
+
+    let value = tokio::task::spawn_blocking(move || {
+        let mut connection = pool.get().map_err(|e| ApiError::Internal(e.to_string()))?;
+        Ok::<_, ApiError>(graphql::run(&mut connection, &request))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))??;
+
+    Ok(json_response(StatusCode::OK, &value))
+}
+
+async fn route(pool: PgSqlPool, req: Request<Body>) -> Result<Response<Body>, ApiError> {
+    if req.uri().path() == "/graphql" {
+        return graphql(pool, req).await;
+    }
+    if req.method() != Method::GET {
+        return Err(ApiError::MethodNotAllowed);
+    }
+
+    let path = req.uri().path();
+    if path == "/openapi.json" {
+        return Ok(json_response(StatusCode::OK, &openapi::document()));
+    }
+    if path == "/graphql/schema" {
+        return Ok(Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain")
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(Body::from(graphql::sdl()))
+            .unwrap());
+    }
+
+    let (route, captured) =
+        routes::find(path).ok_or_else(|| ApiError::NotFound(format!("route {}", path)))?;
+    let query = serde_urlencoded::from_str::<HashMap<String, String>>(
+        req.uri().query().unwrap_or_default(),
+    )
+    .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let params = Params {
+        path: captured,
+        query,
+    };
+
+    // diesel is blocking, keep it off the reactor
+    let value = tokio::task::spawn_blocking(move || {
+        let mut connection = pool.get().map_err(|e| ApiError::Internal(e.to_string()))?;
+        (route.handler)(&mut connection, &params)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))??;
+
+    Ok(json_response(StatusCode::OK, &value))
+}
+
+async fn handle(pool: PgSqlPool, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    Ok(route(pool, req)
+        .await
+        .unwrap_or_else(ApiError::into_response))
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+
+    let subscriber = FmtSubscriber::builder()
+        .with_ansi(true)
+        .with_level(true)
+        .with_line_number(true)
+        .finish();
+
+    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+
+    let manager = ConnectionManager::<PgConnection>::new(std::env::var("DATABASE_URL").unwrap());
+    let pool: PgSqlPool = diesel::r2d2::Pool::builder()
+        .build(manager)
+        .expect("Failed to create pool");
+
+    let addr: SocketAddr = std::env::var("API_ADDR")
+        .unwrap_or_else(|_| "0.0.0.0:8080".to_string())
+        .parse()
+        .expect("invalid API_ADDR");
+
+    let make_service = make_service_fn(move |_| {
+        let pool = pool.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(pool.clone(), req))) }
+    });
+
+    tracing::info!("listening on {}", addr);
+    Server::bind(&addr)
+        .serve(make_service)
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c()
+                .await
+                .expect("failed to install CTRL+C signal handler");
+        })
+        .await
+        .expect("server failed");
+}
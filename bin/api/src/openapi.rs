@@ -0,0 +1,91 @@
+use serde_json::{json, Map, Value};
+
+use crate::routes::ROUTES;
+
+fn error_response(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            "application/json": {
+                "schema": { "$ref": "#/components/schemas/Error" }
+            }
+        }
+    })
+}
+
+/// The OpenAPI 3 document of every route, built from the route table so it
+/// can not drift from what is served.
+pub fn document() -> Value {
+    let mut paths = Map::new();
+
+    for route in ROUTES {
+        let mut parameters = route
+            .path
+            .split('/')
+            .filter_map(|s| s.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect::<Vec<_>>();
+        parameters.extend(route.query.iter().map(|param| {
+            json!({
+                "name": param.name,
+                "in": "query",
+                "required": false,
+                "description": param.description,
+                "schema": { "type": "string" },
+            })
+        }));
+
+        paths.insert(
+            route.path.to_string(),
+            json!({
+                "get": {
+                    "summary": route.summary,
+                    "parameters": parameters,
+                    "responses": {
+                        "200": {
+                            "description": "ok",
+                            "content": { "application/json": {} }
+                        },
+                        "400": error_response("invalid id, cursor or limit"),
+                        "404": error_response("not indexed"),
+                    }
+                }
+            }),
+        );
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "fuelscan",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Ids are 32 bytes hex, accepted with or without `0x` and \
+                returned lowercase without it. Lists are paginated newest first, pass the \
+                `next` cursor of a page as `before` to get the following one.",
+        },
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "properties": {
+                        "error": {
+                            "type": "object",
+                            "properties": {
+                                "code": { "type": "string" },
+                                "message": { "type": "string" },
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,305 @@
+use std::{collections::HashMap, str::FromStr};
+
+use diesel::PgConnection;
+use models::{id::normalize_id, pagination::page_size};
+
+use crate::{error::ApiError, handlers};
+
+pub type Handler = fn(&mut PgConnection, &Params) -> Result<serde_json::Value, ApiError>;
+
+/// A query parameter as documented in the OpenAPI document.
+pub struct QueryParam {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+const BEFORE: QueryParam = QueryParam {
+    name: "before",
+    description: "the `next` cursor of the previous page",
+};
+const LIMIT: QueryParam = QueryParam {
+    name: "limit",
+    description: "the page size, 20 by default and at most 100",
+};
+const PAGE: &[QueryParam] = &[BEFORE, LIMIT];
+
+/// A `GET` endpoint, `{name}` segments of the path are captured.
+pub struct Route {
+    pub path: &'static str,
+    pub summary: &'static str,
+    pub query: &'static [QueryParam],
+    pub handler: Handler,
+}
+
+pub const ROUTES: &[Route] = &[
+    Route {
+        path: "/blocks",
+        summary: "The latest blocks, newest first",
+        query: PAGE,
+        handler: handlers::blocks,
+    },
+    Route {
+        path: "/blocks/{block}",
+        summary: "A block by height or id",
+        query: &[],
+        handler: handlers::block,
+    },
+    Route {
+        path: "/blocks/{block}/transactions",
+        summary: "The transactions of a block in chain order",
+        query: &[],
+        handler: handlers::block_transactions,
+    },
+    Route {
+        path: "/transactions",
+        summary: "The latest transactions, newest first",
+        query: PAGE,
+        handler: handlers::transactions,
+    },
+    Route {
+        path: "/transactions/{transaction}",
+        summary: "A transaction with its inputs, outputs, call and token transfers",
+        query: &[],
+        handler: handlers::transaction,
+    },
+    Route {
+        path: "/contracts/{contract}",
+        summary: "A deployed contract",
+        query: &[],
+        handler: handlers::contract,
+    },
+    Route {
+        path: "/contracts/{contract}/calls",
+        summary: "The calls made to a contract, newest first",
+        query: PAGE,
+        handler: handlers::contract_calls,
+    },
+    Route {
+        path: "/contracts/{contract}/balances",
+        summary: "The assets held by a contract",
+        query: &[],
+        handler: handlers::contract_balances,
+    },
+    Route {
+        path: "/accounts/{account}",
+        summary: "An account and its activity counters",
+        query: &[],
+        handler: handlers::account,
+    },
+    Route {
+        path: "/accounts/{account}/transactions",
+        summary: "The transactions an account takes part in, newest first, with its roles in each \
+            and the total count",
+        query: PAGE,
+        handler: handlers::account_transactions,
+    },
+    Route {
+        path: "/accounts/{account}/utxos",
+        summary: "The unspent coins of an account, newest first",
+        query: &[
+            QueryParam {
+                name: "asset_id",
+                description: "only the coins of this asset",
+            },
+            BEFORE,
+            LIMIT,
+        ],
+        handler: handlers::account_utxos,
+    },
+    Route {
+        path: "/accounts/{account}/balances",
+        summary: "The unspent amount of every asset of an account, now or at the end of a block",
+        query: &[
+            QueryParam {
+                name: "height",
+                description: "the balances at the end of the block at this height",
+            },
+            QueryParam {
+                name: "asset_id",
+                description: "only the balance of this asset",
+            },
+        ],
+        handler: handlers::account_balances,
+    },
+    Route {
+        path: "/accounts/{account}/messages",
+        summary: "The bridge messages an account sent or received, newest first",
+        query: PAGE,
+        handler: handlers::account_messages,
+    },
+    Route {
+        path: "/assets/{asset}/holders",
+        summary: "The holders of an asset by rank as of the last refresh, with their share of \
+            supply",
+        query: PAGE,
+        handler: handlers::asset_holders,
+    },
+    Route {
+        path: "/assets/{asset}/holder-stats",
+        summary: "The holder count, concentration and balance distribution of an asset at every \
+            refresh, newest first",
+        query: PAGE,
+        handler: handlers::asset_holder_stats,
+    },
+    Route {
+        path: "/stats/{granularity}",
+        summary: "Blocks, transactions, active addresses, new contracts, gas, fees, average \
+            block time and tps per minute, hour or day, newest first",
+        query: PAGE,
+        handler: handlers::chain_stats,
+    },
+    Route {
+        path: "/consensus-parameters/{version}",
+        summary: "The consensus parameters of a version and the upgrade that set them",
+        query: &[],
+        handler: handlers::consensus_parameters,
+    },
+    Route {
+        path: "/consensus-parameters/{version}/changes",
+        summary: "Every parameter a version changed, from the version before or another one",
+        query: &[QueryParam {
+            name: "from",
+            description: "the version to compare with, the one before by default",
+        }],
+        handler: handlers::consensus_parameters_changes,
+    },
+    Route {
+        path: "/messages/{nonce}/proof",
+        summary: "The proof needed to relay a withdrawal on the L1",
+        query: &[],
+        handler: handlers::message_proof,
+    },
+    Route {
+        path: "/search",
+        summary: "Every block, transaction, contract, address, asset and utxo a height or a \
+            full or partial hash may identify",
+        query: &[
+            QueryParam {
+                name: "q",
+                description: "a block height or at least 4 hex digits of a hash",
+            },
+            QueryParam {
+                name: "limit",
+                description: "the most results of each kind, 20 by default and at most 100",
+            },
+        ],
+        handler: handlers::search,
+    },
+];
+
+/// The route matching `path` and the values of its captured segments.
+pub fn find(path: &str) -> Option<(&'static Route, Vec<(&'static str, String)>)> {
+    let segments = path.trim_end_matches('/').split('/').collect::<Vec<_>>();
+
+    ROUTES.iter().find_map(|route| {
+        let template = route.path.split('/').collect::<Vec<_>>();
+        if template.len() != segments.len() {
+            return None;
+        }
+
+        let mut captured = vec![];
+        for (t, s) in template.iter().zip(&segments) {
+            match t.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+                Some(name) if !s.is_empty() => captured.push((name, s.to_string())),
+                None if t == s => {}
+                _ => return None,
+            }
+        }
+        Some((route, captured))
+    })
+}
+
+/// A full id in any case, with or without `0x`, as it is stored.
+pub fn parse_id(value: &str) -> Result<String, ApiError> {
+    normalize_id(value).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+pub struct Params {
+    pub path: Vec<(&'static str, String)>,
+    pub query: HashMap<String, String>,
+}
+
+impl Params {
+    pub fn path(&self, name: &str) -> &str {
+        self.path
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn hex(&self, name: &str) -> Result<String, ApiError> {
+        parse_id(self.path(name))
+    }
+
+    pub fn query_hex(&self, name: &str) -> Result<Option<String>, ApiError> {
+        self.query.get(name).map(|v| parse_id(v)).transpose()
+    }
+
+    pub fn limit(&self) -> Result<i64, ApiError> {
+        self.query.get("limit").map_or(Ok(page_size(20)), |l| {
+            l.parse()
+                .map(page_size)
+                .map_err(|_| ApiError::BadRequest(format!("invalid limit {}", l)))
+        })
+    }
+
+    pub fn before<C: FromStr>(&self) -> Result<Option<C>, ApiError> {
+        self.query
+            .get("before")
+            .map(|c| {
+                c.parse()
+                    .map_err(|_| ApiError::BadRequest(format!("invalid cursor {}", c)))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(query: &[(&str, &str)]) -> Params {
+        Params {
+            path: vec![],
+            query: query
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn paths_capture_their_segments() {
+        let (route, captured) = find("/accounts/0xab/transactions/").unwrap();
+        assert_eq!(route.path, "/accounts/{account}/transactions");
+        assert_eq!(captured, [("account", "0xab".to_string())]);
+
+        let (route, _) = find("/blocks").unwrap();
+        assert_eq!(route.path, "/blocks");
+    }
+
+    #[test]
+    fn unknown_paths_and_empty_segments_match_nothing() {
+        assert!(find("/nothing").is_none());
+        assert!(find("/blocks/1/nothing").is_none());
+        assert!(find("/contracts//calls").is_none());
+    }
+
+    #[test]
+    fn malformed_ids_are_bad_requests() {
+        assert_eq!(parse_id(&"AB".repeat(32)).unwrap(), "ab".repeat(32));
+        assert!(matches!(parse_id("0x12"), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn limits_are_clamped() {
+        assert_eq!(params(&[]).limit().unwrap(), 20);
+        assert_eq!(params(&[("limit", "1000")]).limit().unwrap(), 100);
+        assert_eq!(params(&[("limit", "0")]).limit().unwrap(), 1);
+        assert!(matches!(
+            params(&[("limit", "x")]).limit(),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
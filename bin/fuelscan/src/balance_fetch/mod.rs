@@ -0,0 +1,85 @@
+use models::{balance::fetch_address_coin_balances, block::get_indexed_height, PgSqlPool};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::{select, sync::broadcast};
+use tracing::{error, info, trace};
+
+#[derive(Debug, Error)]
+pub enum BalanceFetcherError {
+    #[error("failed to get connection from pool: {0}")]
+    GetPgSqlPoolFailed(String),
+    #[error("failed to read indexed height: {0}")]
+    IndexedHeight(String),
+    #[error("failed to fetch address coin balances: {0}")]
+    FetchBalances(String),
+}
+
+/// Fills in the value of the `address_coin_balances` rows written by the
+/// block handlers once every block up to their height has been committed.
+pub struct BalanceFetcher {
+    db_client: PgSqlPool,
+    batch_fetch_size: i64,
+    shutdown: broadcast::Sender<()>,
+}
+
+impl Drop for BalanceFetcher {
+    fn drop(&mut self) {
+        trace!("BalanceFetcher drop");
+    }
+}
+
+impl BalanceFetcher {
+    pub fn new(
+        db_client: PgSqlPool,
+        batch_fetch_size: i64,
+        shutdown: broadcast::Sender<()>,
+    ) -> Self {
+        Self {
+            db_client,
+            batch_fetch_size,
+            shutdown,
+        }
+    }
+
+    fn fetch_balances(&self) -> Result<usize, BalanceFetcherError> {
+        let mut conn = self
+            .db_client
+            .get()
+            .map_err(|e| BalanceFetcherError::GetPgSqlPoolFailed(e.to_string()))?;
+
+        let Some(height) = get_indexed_height(&mut conn)
+            .map_err(|e| BalanceFetcherError::IndexedHeight(e.to_string()))?
+        else {
+            return Ok(0);
+        };
+
+        fetch_address_coin_balances(&mut conn, height, self.batch_fetch_size)
+            .map_err(|e| BalanceFetcherError::FetchBalances(e.to_string()))
+    }
+
+    pub async fn start(&mut self) -> Result<(), BalanceFetcherError> {
+        let mut shutdown = self.shutdown.subscribe();
+
+        loop {
+            let wait = match self.fetch_balances() {
+                Ok(0) => Duration::from_secs(1),
+                Ok(n) => {
+                    info!("Fetched {} daily address coin balances", n);
+                    Duration::ZERO
+                }
+                Err(e) => {
+                    error!("fetch_balances failed {}, retrying", e.to_string());
+                    Duration::from_secs(1)
+                }
+            };
+
+            select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.recv() => {
+                    trace!("BalanceFetcher shutdown");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
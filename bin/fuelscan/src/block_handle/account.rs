@@ -1,48 +1,252 @@
+use fuel_core_client::client::types::block::Header;
+use fuel_core_types::fuel_tx::{
+    field::InputContract, Address, ContractId, Input, Output, Receipt,
+    Transaction as FuelTransaction,
+};
 use models::{
     account::{Account, AccountType},
     call::{Call, CallType},
+    token_transfer::TokenTransfer,
+    transaction::Transaction,
 };
-use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::block_read::BlockBodies;
+
+use super::process::{tx_inputs, tx_outputs};
+
+// a known kind always wins over a plain wallet, an address only shows up as a
+// predicate or contract when it is spent or called.
+fn add_participant(
+    participants: &mut HashMap<String, AccountType>,
+    address: String,
+    account_type: AccountType,
+) {
+    if address == Address::zeroed().to_string() {
+        return;
+    }
+    match participants.entry(address) {
+        Entry::Occupied(mut e) => {
+            if *e.get() == AccountType::Account {
+                e.insert(account_type);
+            }
+        }
+        Entry::Vacant(e) => {
+            e.insert(account_type);
+        }
+    }
+}
+
+fn input_participants(participants: &mut HashMap<String, AccountType>, inputs: &[Input]) {
+    for input in inputs {
+        match input {
+            Input::CoinSigned(c) => {
+                add_participant(participants, c.owner.to_string(), AccountType::Account)
+            }
+            Input::CoinPredicate(c) => {
+                add_participant(participants, c.owner.to_string(), AccountType::Predicate)
+            }
+            Input::Contract(c) => add_participant(
+                participants,
+                c.contract_id.to_string(),
+                AccountType::Contract,
+            ),
+            Input::MessageCoinSigned(m) => {
+                add_participant(participants, m.recipient.to_string(), AccountType::Account)
+            }
+            Input::MessageDataSigned(m) => {
+                add_participant(participants, m.recipient.to_string(), AccountType::Account)
+            }
+            Input::MessageCoinPredicate(m) => add_participant(
+                participants,
+                m.recipient.to_string(),
+                AccountType::Predicate,
+            ),
+            Input::MessageDataPredicate(m) => add_participant(
+                participants,
+                m.recipient.to_string(),
+                AccountType::Predicate,
+            ),
+        }
+    }
+}
+
+fn output_participants(participants: &mut HashMap<String, AccountType>, outputs: &[Output]) {
+    for output in outputs {
+        match output {
+            Output::Coin { to, .. } | Output::Change { to, .. } | Output::Variable { to, .. } => {
+                add_participant(participants, to.to_string(), AccountType::Account)
+            }
+            Output::ContractCreated { contract_id, .. } => {
+                add_participant(participants, contract_id.to_string(), AccountType::Contract)
+            }
+            // the contract itself is already in the inputs
+            Output::Contract(_) => {}
+        }
+    }
+}
+
+fn receipt_participants(participants: &mut HashMap<String, AccountType>, receipts: &[Receipt]) {
+    for receipt in receipts {
+        if let Some(id) = receipt.id().or(receipt.contract_id()) {
+            if *id != ContractId::zeroed() {
+                add_participant(participants, id.to_string(), AccountType::Contract);
+            }
+        }
+        match receipt {
+            Receipt::Call { to, .. } | Receipt::Transfer { to, .. } => {
+                add_participant(participants, to.to_string(), AccountType::Contract)
+            }
+            Receipt::TransferOut { to, .. } => {
+                add_participant(participants, to.to_string(), AccountType::Account)
+            }
+            // the recipient of a message out lives on the L1
+            Receipt::MessageOut { sender, .. } => {
+                add_participant(participants, sender.to_string(), AccountType::Account)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Every address or contract referenced by any input, output or receipt of
+/// `tx`, classified by the way it is referenced.
+pub fn tx_participants(
+    tx: &FuelTransaction,
+    receipts: Option<&Vec<Receipt>>,
+) -> HashMap<String, AccountType> {
+    let mut participants = HashMap::new();
+
+    input_participants(&mut participants, tx_inputs(tx));
+    output_participants(&mut participants, tx_outputs(tx));
+    if let FuelTransaction::Mint(m) = tx {
+        add_participant(
+            &mut participants,
+            m.input_contract().contract_id.to_string(),
+            AccountType::Contract,
+        );
+    }
 
-pub fn process_account(calls: &Vec<Call>) -> Vec<Account> {
+    if let Some(receipts) = receipts {
+        receipt_participants(&mut participants, receipts);
+    }
+
+    participants
+}
+
+// per block statistics, batch_insert_accounts adds them to the running totals.
+pub fn process_account(
+    header: &Header,
+    bodies: &BlockBodies,
+    transactions: &Vec<Transaction>,
+    calls: &Vec<Call>,
+    token_transfers: &[TokenTransfer],
+) -> Vec<Account> {
     let mut accounts = Vec::new();
 
-    //TODO simple solution, if there have a contract call, maybe will create ohther account.
+    let height = Some(header.height as i64);
+    let participants = bodies
+        .par_iter()
+        .filter_map(|(_, tx, receipts)| {
+            tx.as_ref()
+                .map(|tx| tx_participants(&tx.transaction, receipts.as_ref()))
+        })
+        .collect::<Vec<_>>();
+
+    // an account is counted once per transaction it takes part in
+    for tx_participants in participants {
+        for (account_hash, account_type) in tx_participants {
+            accounts.push(Account {
+                account_hash,
+                account_type,
+                transactions_count: 1,
+                first_seen_height: height,
+                last_seen_height: height,
+                ..Default::default()
+            });
+        }
+    }
+
+    for tx in transactions {
+        let Some(sender) = tx.sender.clone() else {
+            continue;
+        };
+        accounts.push(Account {
+            account_hash: sender,
+            account_type: AccountType::Account,
+            gas_used: tx.gas_used,
+            fee_paid: tx.fee,
+            sender_count: 1,
+            first_seen_height: Some(tx.height),
+            last_seen_height: Some(tx.height),
+            ..Default::default()
+        });
+    }
+
     for call in calls {
-        let mut caller = Account::default();
-        caller.account_hash = call.sender.clone();
-        caller.account_type = AccountType::Account;
-        caller.gas_used = call.gas_used;
-        caller.sender_count = 1;
-        accounts.push(caller);
-        if call.call_type == CallType::Contract {
-            let mut receiver = Account::default();
-            receiver.account_hash = call.receiver.clone();
-            receiver.account_type = AccountType::Contract;
-            receiver.gas_used = call.gas_used;
-            receiver.recever_count = 1;
-            accounts.push(receiver);
+        if call.receiver.is_empty() {
+            continue;
+        }
+        let (account_type, gas_used) = if call.call_type == CallType::Contract {
+            (AccountType::Contract, call.gas_used)
         } else {
-            let mut receiver = Account::default();
-            receiver.account_hash = call.receiver.clone();
-            receiver.account_type = AccountType::Account;
-            receiver.gas_used = call.gas_used;
-            receiver.recever_count = 1;
-            accounts.push(receiver);
+            (AccountType::Account, 0)
+        };
+        accounts.push(Account {
+            account_hash: call.receiver.clone(),
+            account_type,
+            gas_used,
+            recever_count: 1,
+            first_seen_height: Some(call.height),
+            last_seen_height: Some(call.height),
+            ..Default::default()
+        });
+    }
+
+    // both sides of a transfer count it, a self transfer counts once
+    for transfer in token_transfers {
+        let mut sides = vec![&transfer.from_address, &transfer.to_address];
+        sides.dedup();
+        for account_hash in sides.into_iter().flatten() {
+            accounts.push(Account {
+                account_hash: account_hash.clone(),
+                token_transfers_count: 1,
+                ..Default::default()
+            });
         }
     }
+
     // fillter save hash and put all count in to one
-    let mut account_map = std::collections::HashMap::new();
+    let mut account_map = HashMap::new();
 
     for account in accounts {
-        let account_hash = account.account_hash.clone();
-        if account_map.contains_key(&account_hash) {
-            let acc: &mut Account = account_map.get_mut(&account_hash).unwrap();
-            acc.gas_used += account.gas_used;
-            acc.sender_count += account.sender_count;
-            acc.recever_count += account.recever_count;
-        } else {
-            account_map.insert(account_hash, account);
+        match account_map.entry(account.account_hash.clone()) {
+            Entry::Occupied(mut e) => {
+                let acc: &mut Account = e.get_mut();
+                if acc.account_type == AccountType::Account {
+                    acc.account_type = account.account_type;
+                }
+                acc.gas_used += account.gas_used;
+                acc.fee_paid += account.fee_paid;
+                acc.transactions_count += account.transactions_count;
+                acc.token_transfers_count += account.token_transfers_count;
+                acc.sender_count += account.sender_count;
+                acc.recever_count += account.recever_count;
+                acc.first_seen_height = acc
+                    .first_seen_height
+                    .into_iter()
+                    .chain(account.first_seen_height)
+                    .min();
+                acc.last_seen_height = acc
+                    .last_seen_height
+                    .into_iter()
+                    .chain(account.last_seen_height)
+                    .max();
+            }
+            Entry::Vacant(e) => {
+                e.insert(account);
+            }
         }
     }
 
@@ -0,0 +1,86 @@
+use std::collections::{BTreeMap, HashMap};
+
+use fuel_core_types::fuel_tx::Address;
+use models::{
+    address_transaction::{AddressRole, AddressTransaction},
+    call::{Call, CallType},
+    token_transfer::TokenTransfer,
+    transaction::Transaction,
+    tx_input::TxInput,
+    tx_output::{OutputType, TxOutput},
+};
+
+/// One row per address and transaction of the block, with every role the
+/// address plays in it. Mint transactions have no history rows, like they
+/// have no `transactions` row. Change outputs go back to the sender and make
+/// nobody a recipient. Only a contract call makes its receiver a contract
+/// called, the receiver of a transfer is a plain recipient.
+pub fn process_address_transactions(
+    transactions: &[Transaction],
+    tx_inputs: &[TxInput],
+    tx_outputs: &[TxOutput],
+    calls: &[Call],
+    token_transfers: &[TokenTransfer],
+) -> Vec<AddressTransaction> {
+    let mut roles = BTreeMap::<(String, String), Vec<AddressRole>>::new();
+    let mut add = |address: &Option<String>, transaction_id: &str, role: AddressRole| {
+        if let Some(address) = address.as_ref().filter(|a| !a.is_empty()) {
+            roles
+                .entry((address.clone(), transaction_id.to_string()))
+                .or_default()
+                .push(role);
+        }
+    };
+
+    for t in transactions {
+        add(&t.sender, &t.id, AddressRole::FeePayer);
+    }
+    for i in tx_inputs {
+        add(&i.owner, &i.transaction_id, AddressRole::Sender);
+        add(
+            &i.contract_id,
+            &i.transaction_id,
+            AddressRole::ContractCalled,
+        );
+    }
+    for o in tx_outputs {
+        if matches!(o.output_type, OutputType::Coin | OutputType::Variable) {
+            add(&o.to_address, &o.transaction_id, AddressRole::Recipient);
+        }
+    }
+    for t in token_transfers {
+        add(&t.from_address, &t.transaction_id, AddressRole::Sender);
+        add(&t.to_address, &t.transaction_id, AddressRole::Recipient);
+    }
+    for c in calls.iter().filter(|c| c.call_type == CallType::Contract) {
+        add(
+            &Some(c.receiver.clone()),
+            &c.transaction_id,
+            AddressRole::ContractCalled,
+        );
+    }
+
+    let transactions = transactions
+        .iter()
+        .map(|t| (t.id.as_str(), t))
+        .collect::<HashMap<_, _>>();
+    let zero = Address::zeroed().to_string();
+
+    roles
+        .into_iter()
+        .filter(|((address, _), _)| *address != zero)
+        .filter_map(|((address, transaction_id), mut roles)| {
+            let t = transactions.get(transaction_id.as_str())?;
+            roles.sort();
+            roles.dedup();
+            Some(AddressTransaction {
+                address,
+                roles,
+                height: t.height,
+                tx_index: t.tx_index,
+                timestamp: t.timestamp,
+                transaction_id,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+use bigdecimal::BigDecimal;
+use models::{asset_holder::AssetHolderChange, assets::Assets};
+
+// what the spent and created utxos of a block change in the balance of every
+// holder.
+pub fn process_asset_holders(
+    height: i64,
+    assets_delete: &[Assets],
+    assets_insert: &[Assets],
+) -> Vec<AssetHolderChange> {
+    let mut changes = BTreeMap::<(String, String), BigDecimal>::new();
+    let spent = assets_delete.iter().map(|a| (a, -a.amount));
+    let created = assets_insert.iter().map(|a| (a, a.amount));
+    for (a, amount) in spent.chain(created) {
+        if a.assets_owner.is_empty() || a.assets_id.is_empty() {
+            continue;
+        }
+        *changes
+            .entry((a.assets_id.clone(), a.assets_owner.clone()))
+            .or_default() += BigDecimal::from(amount);
+    }
+
+    changes
+        .into_iter()
+        .filter(|(_, balance)| *balance != BigDecimal::from(0))
+        .map(|((asset_id, owner), balance)| AssetHolderChange {
+            height,
+            asset_id,
+            owner,
+            balance,
+        })
+        .collect()
+}
@@ -2,7 +2,7 @@ use fuel_core_client::client::types::block::Header;
 use fuel_core_types::{
     fuel_tx::{
         field::{Inputs, MintAmount, MintAssetId, Outputs},
-        Create, Mint, Output, Script, Transaction, UniqueIdentifier, UtxoId,
+        AssetId, Mint, Output, Transaction, UniqueIdentifier, UtxoId,
     },
     fuel_types::ChainId,
 };
@@ -18,100 +18,42 @@ use super::CHAIN_ID;
 
 // delete inputs utxo_id
 // and store output utxo_id
-fn handle_script(s: &Script) -> Option<(Vec<Assets>, Vec<Assets>)> {
-    //let mut result = vec![];
-
-    let inputs = s.inputs();
-    let outputs = s.outputs();
-    let delete_assets = inputs
+fn handle_inputs_outputs<T>(tx: &T) -> Option<(Vec<Assets>, Vec<Assets>)>
+where
+    T: Inputs + Outputs + UniqueIdentifier,
+{
+    let tx_id = tx.id(&ChainId::new(CHAIN_ID));
+
+    let delete_assets = tx
+        .inputs()
         .par_iter()
         .filter_map(|i| {
             if i.is_coin_signed() || i.is_coin_predicate() || i.is_coin() {
                 let input_coin = i.utxo_id().expect("unreachable");
-                let mut asset = Assets::default();
-
-                asset.assets_utxo_id = format!("{:x}", input_coin);
-                asset.assets_owner = i
-                    .input_owner()
-                    .expect("failed find sender with input")
-                    .to_string();
-                // TODO: asset_id is not in input
-                //                asset.assets_id = i
-                //                    .asset_id()
-                //                    .expect("failed find asset_id with input")
-                //                    .to_string();
-                asset.asset_status = AssetStatus::Delete;
-                asset.delete_tx_hash = format!("{:x}", s.id(&ChainId::new(CHAIN_ID)));
-
-                Some(asset)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-
-    let insert_assets = outputs
-        .par_iter()
-        .enumerate()
-        .filter_map(|(output_index, o)| {
-            if o.is_coin()
-                || matches!(o, Output::Change { .. })
-                || matches!(o, Output::Variable { .. })
-            {
-                let mut asset = Assets::default();
-                //                asset.assets_id = o
-                //                    .asset_id()
-                //                    .expect("failed find asset_id with output")
-                //                    .to_string();
-                //
-                asset.assets_utxo_id = format!(
-                    "{:x}",
-                    UtxoId::new(s.id(&ChainId::new(CHAIN_ID)), (output_index as u8).into())
-                );
-
-                asset.assets_owner = o.to().expect("failed find to with output").to_string();
-                asset.amount = o.amount().expect("failed find amount with output") as i64;
-                Some(asset)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
 
-    Some((delete_assets, insert_assets))
-}
-
-fn handle_create(c: &Create) -> Option<(Vec<Assets>, Vec<Assets>)> {
-    let inputs = c.inputs();
-    let outputs = c.outputs();
-    let delete_assets = inputs
-        .par_iter()
-        .filter_map(|i| {
-            if i.is_coin_signed() || i.is_coin_predicate() || i.is_coin() {
-                let input_coin = i.utxo_id().expect("unreachable");
-                let mut asset = Assets::default();
-
-                asset.assets_utxo_id = format!("{:x}", input_coin);
-                asset.assets_owner = i
-                    .input_owner()
-                    .expect("failed find sender with input")
-                    .to_string();
-                //asset.assets_id = i
-                //    .asset_id()
-                //    .expect("failed find asset_id with input")
-                //    .to_string();
-
-                asset.asset_status = AssetStatus::Delete;
-                asset.delete_tx_hash = format!("{:x}", c.id(&ChainId::new(CHAIN_ID)));
-
-                Some(asset)
+                Some(Assets {
+                    assets_id: i
+                        .asset_id(&AssetId::BASE)
+                        .expect("failed find asset_id with input")
+                        .to_string(),
+                    assets_utxo_id: format!("{:x}", input_coin),
+                    assets_owner: i
+                        .input_owner()
+                        .expect("failed find sender with input")
+                        .to_string(),
+                    amount: i.amount().expect("failed find amount with input") as i64,
+                    asset_status: AssetStatus::Delete,
+                    delete_tx_hash: format!("{:x}", tx_id),
+                    ..Default::default()
+                })
             } else {
                 None
             }
         })
         .collect::<Vec<_>>();
 
-    let insert_assets = outputs
+    let insert_assets = tx
+        .outputs()
         .par_iter()
         .enumerate()
         .filter_map(|(output_index, o)| {
@@ -119,20 +61,20 @@ fn handle_create(c: &Create) -> Option<(Vec<Assets>, Vec<Assets>)> {
                 || matches!(o, Output::Change { .. })
                 || matches!(o, Output::Variable { .. })
             {
-                let mut asset = Assets::default();
-                asset.assets_id = o
-                    .asset_id()
-                    .expect("failed find asset_id with output")
-                    .to_string();
-
-                asset.assets_utxo_id = format!(
-                    "{:x}",
-                    UtxoId::new(c.id(&ChainId::new(CHAIN_ID)), (output_index as u8).into())
-                );
-                asset.create_tx_hash = c.id(&ChainId::new(CHAIN_ID)).to_string();
-                asset.assets_owner = o.to().expect("failed find to with output").to_string();
-                asset.amount = o.amount().expect("failed find amount with output") as i64;
-                Some(asset)
+                Some(Assets {
+                    assets_id: o
+                        .asset_id()
+                        .expect("failed find asset_id with output")
+                        .to_string(),
+                    assets_utxo_id: format!(
+                        "{:x}",
+                        UtxoId::new(tx_id, (output_index as u8).into())
+                    ),
+                    create_tx_hash: tx_id.to_string(),
+                    assets_owner: o.to().expect("failed find to with output").to_string(),
+                    amount: o.amount().expect("failed find amount with output") as i64,
+                    ..Default::default()
+                })
             } else {
                 None
             }
@@ -147,14 +89,15 @@ fn handle_mint(m: &Mint) -> Option<(Vec<Assets>, Vec<Assets>)> {
     let mut insert_assets = vec![];
 
     if *m.mint_amount() > 0 {
-        let mut asset = Assets::default();
-        asset.assets_id = m.mint_asset_id().to_string();
-        asset.assets_utxo_id = format!("{:x}", m.id(&ChainId::new(CHAIN_ID)));
-        asset.create_tx_hash = m.id(&ChainId::new(CHAIN_ID)).to_string();
-        // TODO: finish this
-        //asset.assets_owner = m;
-        asset.amount = *m.mint_amount() as i64;
-        insert_assets.push(asset);
+        insert_assets.push(Assets {
+            assets_id: m.mint_asset_id().to_string(),
+            assets_utxo_id: format!("{:x}", m.id(&ChainId::new(CHAIN_ID))),
+            create_tx_hash: m.id(&ChainId::new(CHAIN_ID)).to_string(),
+            // TODO: finish this
+            //assets_owner: m,
+            amount: *m.mint_amount() as i64,
+            ..Default::default()
+        });
     }
 
     Some((vec![], insert_assets))
@@ -165,11 +108,11 @@ pub fn assets_process(header: &Header, bodies: &BlockBodies) -> (Vec<Assets>, Ve
         .par_iter()
         .filter_map(|(_, maybe_tx, _)| {
             maybe_tx.as_ref().and_then(|tx| match &tx.transaction {
-                Transaction::Script(s) => handle_script(s),
-                Transaction::Create(c) => handle_create(c),
+                Transaction::Script(s) => handle_inputs_outputs(s),
+                Transaction::Create(c) => handle_inputs_outputs(c),
                 Transaction::Mint(m) => handle_mint(m),
-                Transaction::Upload(_) => None,
-                Transaction::Upgrade(_) => None,
+                Transaction::Upload(u) => handle_inputs_outputs(u),
+                Transaction::Upgrade(u) => handle_inputs_outputs(u),
             })
         })
         .collect::<Vec<(Vec<Assets>, Vec<Assets>)>>();
@@ -0,0 +1,30 @@
+use std::{collections::HashSet, time::SystemTime};
+
+use fuel_core_client::client::types::block::Header;
+use models::{assets::Assets, balance::AddressCoinBalance};
+
+// every owner whose utxos changed in this block gets a balance row at this
+// height, the value is computed later by the balance fetcher.
+pub fn process_balances(
+    header: &Header,
+    assets_delete: &[Assets],
+    assets_insert: &[Assets],
+) -> Vec<AddressCoinBalance> {
+    let touched = assets_delete
+        .iter()
+        .chain(assets_insert.iter())
+        .filter(|a| !a.assets_owner.is_empty() && !a.assets_id.is_empty())
+        .map(|a| (a.assets_owner.clone(), a.assets_id.clone()))
+        .collect::<HashSet<_>>();
+
+    touched
+        .into_iter()
+        .map(|(address_hash, asset_hash)| AddressCoinBalance {
+            address_hash,
+            asset_hash,
+            block_number: header.height as i64,
+            inserted_at: SystemTime::now(),
+            updated_at: SystemTime::now(),
+        })
+        .collect()
+}
@@ -1,7 +1,43 @@
-use fuel_core_client::client::types::block::Header;
+use fuel_core_client::client::types::block::{Consensus, Header};
+use fuel_core_types::{
+    fuel_crypto::Message,
+    fuel_tx::{Address, Input},
+};
 use models::block::Block;
+use serde_json::json;
 
-pub fn init_block_by_with_header(header: &Header) -> Block {
+fn consensus_json(consensus: &Consensus) -> Option<serde_json::Value> {
+    match consensus {
+        Consensus::Genesis(g) => Some(json!({
+            "type": "genesis",
+            "chain_config_hash": g.chain_config_hash.to_string(),
+            "coins_root": g.coins_root.to_string(),
+            "contracts_root": g.contracts_root.to_string(),
+            "messages_root": g.messages_root.to_string(),
+            "transactions_root": g.transactions_root.to_string(),
+        })),
+        Consensus::PoAConsensus(poa) => Some(json!({
+            "type": "poa",
+            "signature": poa.signature.to_string(),
+        })),
+        Consensus::Unknown => None,
+    }
+}
+
+/// The address of the key that signed the block id, the genesis block has
+/// no producer.
+pub fn block_producer(header: &Header, consensus: &Consensus) -> Option<Address> {
+    match consensus {
+        Consensus::PoAConsensus(poa) => poa
+            .signature
+            .recover(&Message::from_bytes(*header.id))
+            .ok()
+            .map(|public_key| Input::owner(&public_key)),
+        _ => None,
+    }
+}
+
+pub fn init_block_by_with_header(header: &Header, consensus: &Consensus) -> Block {
     Block {
         id: header.id.to_string(),
         height: header.height as i64,
@@ -15,6 +51,11 @@ pub fn init_block_by_with_header(header: &Header) -> Block {
         coinbase_amount: None,
         transaction_count: header.transactions_count as i64,
         output_message_count: header.message_receipt_count as i64,
-        timestamp: header.time.clone().to_unix(),
+        timestamp: header.time.to_unix(),
+        event_inbox_root: header.event_inbox_root.to_string(),
+        consensus_parameters_version: header.consensus_parameters_version as i64,
+        state_transition_bytecode_version: header.state_transition_bytecode_version as i64,
+        consensus: consensus_json(consensus),
+        producer: block_producer(header, consensus).map(|p| p.to_string()),
     }
 }
@@ -0,0 +1,35 @@
+use std::collections::BTreeSet;
+
+use bigdecimal::BigDecimal;
+use fuel_core_client::client::types::block::Header;
+use models::{
+    address_transaction::AddressTransaction, chain_stats::BlockStats, contract::Contract,
+    transaction::Transaction,
+};
+
+// the addresses come from the history rows, so an address is active in a
+// bucket exactly when the bucket holds a transaction of its history.
+pub fn process_chain_stats(
+    header: &Header,
+    transactions: &[Transaction],
+    contracts: &[Contract],
+    address_transactions: &[AddressTransaction],
+) -> BlockStats {
+    let addresses = address_transactions
+        .iter()
+        .map(|a| a.address.clone())
+        .collect::<BTreeSet<_>>();
+
+    BlockStats {
+        height: header.height as i64,
+        timestamp: header.time.to_unix(),
+        transaction_count: transactions.len() as i64,
+        new_contracts: contracts.len() as i64,
+        gas_used: transactions
+            .iter()
+            .map(|t| BigDecimal::from(t.gas_used))
+            .sum(),
+        fees: transactions.iter().map(|t| BigDecimal::from(t.fee)).sum(),
+        addresses: addresses.into_iter().collect(),
+    }
+}
@@ -0,0 +1,112 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use bigdecimal::{num_bigint::BigInt, BigDecimal};
+use fuel_core_client::client::types::{block::Header, TransactionStatus};
+use fuel_core_types::fuel_tx::{
+    field::{InputContract, MintAmount, MintAssetId},
+    AssetId, ContractId, ContractIdExt, Receipt, Transaction as FuelTransaction,
+};
+use models::contract_balance::ContractBalance;
+
+use crate::block_read::BlockBodies;
+
+type Deltas = HashMap<(ContractId, AssetId), i128>;
+
+// the zero id is the script context, its coins are not held by a contract
+fn add(deltas: &mut Deltas, contract: &ContractId, asset_id: AssetId, amount: i128) {
+    if *contract != ContractId::zeroed() && amount != 0 {
+        *deltas.entry((*contract, asset_id)).or_default() += amount;
+    }
+}
+
+fn receipt_deltas(deltas: &mut Deltas, receipts: &[Receipt]) {
+    for receipt in receipts {
+        match receipt {
+            // coins forwarded with a call
+            Receipt::Call {
+                id,
+                to,
+                amount,
+                asset_id,
+                ..
+            }
+            | Receipt::Transfer {
+                id,
+                to,
+                amount,
+                asset_id,
+                ..
+            } => {
+                add(deltas, id, *asset_id, -(*amount as i128));
+                add(deltas, to, *asset_id, *amount as i128);
+            }
+            Receipt::TransferOut {
+                id,
+                amount,
+                asset_id,
+                ..
+            } => add(deltas, id, *asset_id, -(*amount as i128)),
+            Receipt::Mint {
+                sub_id,
+                contract_id,
+                val,
+                ..
+            } => add(
+                deltas,
+                contract_id,
+                contract_id.asset_id(sub_id),
+                *val as i128,
+            ),
+            Receipt::Burn {
+                sub_id,
+                contract_id,
+                val,
+                ..
+            } => add(
+                deltas,
+                contract_id,
+                contract_id.asset_id(sub_id),
+                -(*val as i128),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// The change of every contract balance in this block, to be added with
+/// `batch_add_contract_balances`.
+pub fn process_contract_balances(header: &Header, bodies: &BlockBodies) -> Vec<ContractBalance> {
+    let mut deltas = Deltas::new();
+
+    for (_, tx, receipts) in bodies {
+        let Some(tx) = tx else { continue };
+        // a reverted transaction leaves the contract state untouched
+        if !matches!(tx.status, TransactionStatus::Success { .. }) {
+            continue;
+        }
+
+        // the block fees are minted into the coinbase contract
+        if let FuelTransaction::Mint(m) = &tx.transaction {
+            add(
+                &mut deltas,
+                &m.input_contract().contract_id,
+                *m.mint_asset_id(),
+                *m.mint_amount() as i128,
+            );
+        }
+        receipt_deltas(&mut deltas, receipts.as_deref().unwrap_or_default());
+    }
+
+    deltas
+        .into_iter()
+        .filter(|(_, amount)| *amount != 0)
+        .map(|((contract, asset_id), amount)| ContractBalance {
+            contract_hash: contract.to_string(),
+            asset_id: asset_id.to_string(),
+            amount: BigDecimal::from(BigInt::from(amount)),
+            block_height: header.height as i64,
+            inserted_at: SystemTime::now(),
+            updated_at: SystemTime::now(),
+        })
+        .collect()
+}
@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+
+use fuel_core_client::client::types::{block::Header, TransactionStatus};
+use fuel_core_types::fuel_tx::{ContractId, Receipt};
+use models::transaction_failure::{FailureKind, TransactionFailure};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use serde_json::json;
+
+use crate::block_read::BlockBodies;
+
+// the revert codes of the Sway std library, see `std::error_signals`
+const ERROR_SIGNALS: [(u64, &str, &str); 7] = [
+    (0xffff_ffff_ffff_0000, "failed_require", "require failed"),
+    (
+        0xffff_ffff_ffff_0001,
+        "failed_transfer_to_address",
+        "transfer to address failed",
+    ),
+    (
+        0xffff_ffff_ffff_0002,
+        "failed_send_message",
+        "send message failed",
+    ),
+    (
+        0xffff_ffff_ffff_0003,
+        "failed_assert_eq",
+        "assert_eq failed",
+    ),
+    (0xffff_ffff_ffff_0004, "failed_assert", "assert failed"),
+    (
+        0xffff_ffff_ffff_0005,
+        "failed_assert_ne",
+        "assert_ne failed",
+    ),
+    (
+        0xffff_ffff_ffff_0006,
+        "revert_with_log",
+        "reverted with a logged error",
+    ),
+];
+
+const LOGGING_SIGNALS: [&str; 4] = [
+    "failed_require",
+    "failed_assert_eq",
+    "failed_assert_ne",
+    "revert_with_log",
+];
+
+fn error_signal(ra: u64) -> Option<(&'static str, &'static str)> {
+    ERROR_SIGNALS
+        .iter()
+        .find(|(code, _, _)| *code == ra)
+        .map(|(_, signal, message)| (*signal, *message))
+}
+
+// `NotEnoughBalance` -> `not enough balance`
+fn split_words(name: &str) -> String {
+    let mut words = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            words.push(' ');
+        }
+        words.extend(c.to_lowercase());
+    }
+    words
+}
+
+fn context_id(id: &ContractId) -> Option<String> {
+    (*id != ContractId::zeroed()).then(|| id.to_string())
+}
+
+fn abi_type<'a>(
+    abi: &'a serde_json::Value,
+    type_id: &serde_json::Value,
+) -> Option<&'a serde_json::Value> {
+    abi["types"]
+        .as_array()?
+        .iter()
+        .find(|t| &t["typeId"] == type_id)
+}
+
+fn read_uint(data: &[u8], size: usize) -> Option<u64> {
+    let bytes = data.get(..size)?;
+    Some(bytes.iter().fold(0u64, |n, b| n << 8 | *b as u64))
+}
+
+// only the first level of a value is decoded, nested data is left as hex.
+fn decode_value(
+    abi: &serde_json::Value,
+    type_id: &serde_json::Value,
+    data: &[u8],
+) -> Option<serde_json::Value> {
+    let decl = abi_type(abi, type_id)?;
+    let name = decl["type"].as_str()?;
+
+    let value = match name {
+        "bool" => json!(*data.first()? != 0),
+        "u8" => json!(read_uint(data, 1)?),
+        "u16" => json!(read_uint(data, 2)?),
+        "u32" => json!(read_uint(data, 4)?),
+        "u64" => json!(read_uint(data, 8)?),
+        "b256" => json!(hex::encode(data.get(..32)?)),
+        "str" => {
+            let len = read_uint(data, 8)? as usize;
+            json!(String::from_utf8_lossy(data.get(8..8 + len)?))
+        }
+        _ if name.starts_with("str[") => json!(String::from_utf8_lossy(data)),
+        _ if name.starts_with("enum ") => {
+            let discriminant = read_uint(data, 8)? as usize;
+            let variant = decl["components"].as_array()?.get(discriminant)?;
+            json!({
+                "type": name.trim_start_matches("enum "),
+                "variant": variant["name"],
+                "data": hex::encode(&data[8..]),
+            })
+        }
+        _ => json!({
+            "type": name.split_whitespace().last().unwrap_or(name),
+            "data": hex::encode(data),
+        }),
+    };
+
+    Some(value)
+}
+
+/// Decodes a value logged with `log_id` using the `loggedTypes` of a json
+/// ABI, only the ABI encoding v1 layout is understood.
+fn decode_logged_value(
+    abi: &serde_json::Value,
+    log_id: u64,
+    data: &[u8],
+) -> Option<serde_json::Value> {
+    let logged = abi["loggedTypes"].as_array()?.iter().find(|l| {
+        // the log id is a string since encoding v1 and a number before
+        l["logId"].as_u64() == Some(log_id)
+            || l["logId"].as_str().and_then(|id| id.parse().ok()) == Some(log_id)
+    })?;
+
+    decode_value(abi, &logged["loggedType"]["type"], data)
+}
+
+// the value logged by the failing contract right before it reverted
+fn logged_error(
+    receipts: &[Receipt],
+    revert_index: usize,
+    id: &ContractId,
+    abis: &HashMap<String, serde_json::Value>,
+) -> Option<serde_json::Value> {
+    let (log_id, data) = receipts[..revert_index]
+        .iter()
+        .rev()
+        .take_while(|r| !matches!(r, Receipt::Call { .. } | Receipt::Return { .. }))
+        .find_map(|r| match r {
+            Receipt::LogData {
+                id: log_contract,
+                rb,
+                data: Some(data),
+                ..
+            } if log_contract == id => Some((*rb, data.clone())),
+            Receipt::Log {
+                id: log_contract,
+                ra,
+                rb,
+                ..
+            } if log_contract == id => Some((*rb, ra.to_be_bytes().to_vec())),
+            _ => None,
+        })?;
+
+    let decoded = context_id(id)
+        .and_then(|id| abis.get(&id))
+        .and_then(|abi| decode_logged_value(abi, log_id, &data));
+
+    Some(json!({
+        "log_id": log_id.to_string(),
+        "data": hex::encode(&data),
+        "decoded": decoded,
+    }))
+}
+
+fn describe_error(error: &serde_json::Value) -> Option<String> {
+    let decoded = &error["decoded"];
+    match (decoded["type"].as_str(), decoded["variant"].as_str()) {
+        (Some(t), Some(v)) => Some(format!("{}::{}", t, v)),
+        (Some(t), None) => Some(t.to_string()),
+        _ if !decoded.is_null() => Some(decoded.to_string()),
+        _ => None,
+    }
+}
+
+fn failure(
+    header: &Header,
+    tx_hash: String,
+    node_reason: String,
+    receipts: &[Receipt],
+    abis: &HashMap<String, serde_json::Value>,
+) -> TransactionFailure {
+    let mut failure = TransactionFailure {
+        transaction_id: tx_hash,
+        kind: FailureKind::Unknown,
+        receipt_index: None,
+        contract_id: None,
+        panic_reason: None,
+        revert_code: None,
+        signal: None,
+        logged_error: None,
+        message: node_reason.clone(),
+        node_reason,
+        height: header.height as i64,
+        timestamp: header.time.to_unix(),
+    };
+
+    let Some(index) = receipts
+        .iter()
+        .rposition(|r| matches!(r, Receipt::Panic { .. } | Receipt::Revert { .. }))
+    else {
+        return failure;
+    };
+    failure.receipt_index = Some(index as i64);
+
+    match &receipts[index] {
+        Receipt::Panic {
+            id,
+            reason,
+            contract_id,
+            ..
+        } => {
+            failure.kind = FailureKind::Panic;
+            failure.contract_id = context_id(id);
+            failure.panic_reason = Some(reason.reason().to_string());
+            failure.message = format!("panicked: {}", split_words(&reason.reason().to_string()));
+            // set when the panic is about a contract missing from the inputs
+            if let Some(missing) = contract_id {
+                failure.message = format!("{} {}", failure.message, missing);
+            }
+        }
+        Receipt::Revert { id, ra, .. } => {
+            failure.kind = FailureKind::Revert;
+            failure.contract_id = context_id(id);
+            failure.revert_code = Some(format!("{:#018x}", ra));
+            let Some((signal, message)) = error_signal(*ra) else {
+                failure.message = format!("reverted with code {}", ra);
+                return failure;
+            };
+            failure.signal = Some(signal.to_string());
+            failure.message = message.to_string();
+
+            // require, the asserts and revert_with_log log a value right
+            // before reverting, a transfer failure logs nothing.
+            if LOGGING_SIGNALS.contains(&signal) {
+                failure.logged_error = logged_error(receipts, index, id, abis);
+                if let Some(error) = failure.logged_error.as_ref().and_then(describe_error) {
+                    failure.message = format!("{}: {}", message, error);
+                }
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    failure
+}
+
+/// The contracts whose ABI may explain a failure in this block.
+pub fn failed_contracts(bodies: &BlockBodies) -> Vec<String> {
+    let mut contracts = bodies
+        .iter()
+        .filter(|(_, tx, _)| {
+            tx.as_ref()
+                .is_some_and(|tx| matches!(tx.status, TransactionStatus::Failure { .. }))
+        })
+        .flat_map(|(_, _, receipts)| receipts.iter().flatten())
+        .filter_map(|r| match r {
+            Receipt::Revert { id, .. } => context_id(id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    contracts.sort();
+    contracts.dedup();
+    contracts
+}
+
+pub fn process_failures(
+    header: &Header,
+    bodies: &BlockBodies,
+    abis: &HashMap<String, serde_json::Value>,
+) -> Vec<TransactionFailure> {
+    bodies
+        .par_iter()
+        .filter_map(|(tx_hash, tx, receipts)| {
+            let TransactionStatus::Failure { reason, .. } = &tx.as_ref()?.status else {
+                return None;
+            };
+            Some(failure(
+                header,
+                tx_hash.to_string(),
+                reason.clone(),
+                receipts.as_deref().unwrap_or_default(),
+                abis,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn abi() -> serde_json::Value {
+        json!({
+            "types": [
+                { "typeId": 0, "type": "bool" },
+                { "typeId": 1, "type": "u8" },
+                { "typeId": 2, "type": "u16" },
+                { "typeId": 3, "type": "u32" },
+                { "typeId": 4, "type": "u64" },
+                { "typeId": 5, "type": "b256" },
+                { "typeId": 6, "type": "str" },
+                { "typeId": 7, "type": "str[3]" },
+                {
+                    "typeId": 8,
+                    "type": "enum errors::AccessError",
+                    "components": [{ "name": "NotOwner" }, { "name": "Paused" }],
+                },
+                { "typeId": 9, "type": "struct lib::Position" },
+            ],
+            "loggedTypes": [
+                { "logId": "1515152261580153489", "loggedType": { "type": 8 } },
+                { "logId": 7, "loggedType": { "type": 4 } },
+            ],
+        })
+    }
+
+    fn decode(type_id: u64, data: &[u8]) -> Option<serde_json::Value> {
+        decode_value(&abi(), &json!(type_id), data)
+    }
+
+    #[test]
+    fn split_words_lowercases_each_word() {
+        assert_eq!(split_words("NotEnoughBalance"), "not enough balance");
+        assert_eq!(split_words("OutOfGas"), "out of gas");
+        assert_eq!(split_words("Revert"), "revert");
+        assert_eq!(split_words(""), "");
+    }
+
+    #[test]
+    fn every_sway_signal_is_known() {
+        let signals = [
+            (0xffff_ffff_ffff_0000, "failed_require", "require failed"),
+            (
+                0xffff_ffff_ffff_0001,
+                "failed_transfer_to_address",
+                "transfer to address failed",
+            ),
+            (
+                0xffff_ffff_ffff_0002,
+                "failed_send_message",
+                "send message failed",
+            ),
+            (
+                0xffff_ffff_ffff_0003,
+                "failed_assert_eq",
+                "assert_eq failed",
+            ),
+            (0xffff_ffff_ffff_0004, "failed_assert", "assert failed"),
+            (
+                0xffff_ffff_ffff_0005,
+                "failed_assert_ne",
+                "assert_ne failed",
+            ),
+            (
+                0xffff_ffff_ffff_0006,
+                "revert_with_log",
+                "reverted with a logged error",
+            ),
+        ];
+        for (code, signal, message) in signals {
+            assert_eq!(error_signal(code), Some((signal, message)));
+        }
+        assert_eq!(error_signal(0xffff_ffff_ffff_0007), None);
+        assert_eq!(error_signal(0), None);
+        assert_eq!(error_signal(42), None);
+    }
+
+    #[test]
+    fn numbers_are_read_big_endian() {
+        assert_eq!(decode(0, &[1]), Some(json!(true)));
+        assert_eq!(decode(0, &[0]), Some(json!(false)));
+        assert_eq!(decode(1, &[0xff]), Some(json!(255)));
+        assert_eq!(decode(2, &[0x01, 0x02]), Some(json!(258)));
+        assert_eq!(decode(3, &[0, 0, 1, 0]), Some(json!(256)));
+        assert_eq!(decode(4, &[0, 0, 0, 0, 0, 0, 0, 7]), Some(json!(7)));
+        assert_eq!(decode(5, &[0xab; 32]), Some(json!("ab".repeat(32))));
+    }
+
+    #[test]
+    fn short_input_is_not_decoded() {
+        assert_eq!(decode(0, &[]), None);
+        assert_eq!(decode(2, &[1]), None);
+        assert_eq!(decode(3, &[0, 0, 1]), None);
+        assert_eq!(decode(4, &[0; 7]), None);
+        assert_eq!(decode(5, &[0; 31]), None);
+        // a string longer than the data left
+        assert_eq!(decode(6, &[0, 0, 0, 0, 0, 0, 0, 5, b'a', b'b', b'c']), None);
+        assert_eq!(decode(8, &[0, 0, 0]), None);
+    }
+
+    #[test]
+    fn strings_are_decoded() {
+        assert_eq!(
+            decode(6, &[0, 0, 0, 0, 0, 0, 0, 3, b'a', b'b', b'c', b'd']),
+            Some(json!("abc"))
+        );
+        assert_eq!(decode(7, b"abc"), Some(json!("abc")));
+        // odd lengths and invalid utf8 are kept lossily
+        assert_eq!(decode(7, &[b'a', 0xff, b'c']), Some(json!("a\u{fffd}c")));
+    }
+
+    #[test]
+    fn enums_name_their_variant() {
+        assert_eq!(
+            decode(8, &[0, 0, 0, 0, 0, 0, 0, 1, 0xaa]),
+            Some(json!({ "type": "errors::AccessError", "variant": "Paused", "data": "aa" }))
+        );
+        // no such variant
+        assert_eq!(decode(8, &[0, 0, 0, 0, 0, 0, 0, 2]), None);
+    }
+
+    #[test]
+    fn other_types_are_left_as_hex() {
+        assert_eq!(
+            decode(9, &[1, 2, 3]),
+            Some(json!({ "type": "lib::Position", "data": "010203" }))
+        );
+        assert_eq!(decode(10, &[1]), None);
+    }
+
+    #[test]
+    fn logged_values_are_found_by_log_id() {
+        assert_eq!(
+            decode_logged_value(&abi(), 1515152261580153489, &[0; 8]),
+            Some(json!({ "type": "errors::AccessError", "variant": "NotOwner", "data": "" }))
+        );
+        assert_eq!(
+            decode_logged_value(&abi(), 7, &[0, 0, 0, 0, 0, 0, 0, 9]),
+            Some(json!(9))
+        );
+        assert_eq!(decode_logged_value(&abi(), 8, &[0; 8]), None);
+        assert_eq!(decode_logged_value(&json!({}), 7, &[0; 8]), None);
+    }
+}
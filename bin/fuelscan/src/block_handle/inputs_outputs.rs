@@ -0,0 +1,173 @@
+use fuel_core_client::client::types::block::Header;
+use fuel_core_types::{
+    fuel_tx::{
+        field::{InputContract, OutputContract},
+        input, AssetId, Input, Output, Transaction as FuelTransaction, UtxoId,
+    },
+    fuel_types::Bytes32,
+};
+use models::{
+    tx_input::{InputType, TxInput},
+    tx_output::{OutputType, TxOutput},
+};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::block_read::BlockBodies;
+
+use super::process::{tx_inputs, tx_outputs};
+
+fn contract_input(
+    header: &Header,
+    tx_hash: &Bytes32,
+    input_index: usize,
+    contract: &input::contract::Contract,
+) -> TxInput {
+    TxInput {
+        transaction_id: tx_hash.to_string(),
+        input_index: input_index as i64,
+        input_type: InputType::Contract,
+        utxo_id: Some(format!("{:x}", contract.utxo_id)),
+        owner: None,
+        sender: None,
+        asset_id: None,
+        amount: None,
+        tx_pointer_height: Some(*contract.tx_pointer.block_height() as i64),
+        tx_pointer_index: Some(contract.tx_pointer.tx_index() as i64),
+        contract_id: Some(contract.contract_id.to_string()),
+        nonce: None,
+        height: header.height as i64,
+        timestamp: header.time.to_unix(),
+    }
+}
+
+fn tx_input(
+    header: &Header,
+    base_asset_id: &AssetId,
+    tx_hash: &Bytes32,
+    input_index: usize,
+    i: &Input,
+) -> TxInput {
+    let input_type = match i {
+        Input::CoinSigned(_) => InputType::CoinSigned,
+        Input::CoinPredicate(_) => InputType::CoinPredicate,
+        Input::Contract(c) => return contract_input(header, tx_hash, input_index, c),
+        Input::MessageCoinSigned(_) => InputType::MessageCoinSigned,
+        Input::MessageCoinPredicate(_) => InputType::MessageCoinPredicate,
+        Input::MessageDataSigned(_) => InputType::MessageDataSigned,
+        Input::MessageDataPredicate(_) => InputType::MessageDataPredicate,
+    };
+    let tx_pointer = i.tx_pointer();
+
+    TxInput {
+        transaction_id: tx_hash.to_string(),
+        input_index: input_index as i64,
+        input_type,
+        utxo_id: i.utxo_id().map(|u| format!("{:x}", u)),
+        owner: i.input_owner().or(i.recipient()).map(|o| o.to_string()),
+        sender: i.sender().map(|s| s.to_string()),
+        asset_id: i.asset_id(base_asset_id).map(|a| a.to_string()),
+        amount: i.amount().map(|a| a as i64),
+        tx_pointer_height: tx_pointer.map(|p| *p.block_height() as i64),
+        tx_pointer_index: tx_pointer.map(|p| p.tx_index() as i64),
+        contract_id: None,
+        nonce: i.nonce().map(|n| n.to_string()),
+        height: header.height as i64,
+        timestamp: header.time.to_unix(),
+    }
+}
+
+fn tx_output(
+    header: &Header,
+    tx_hash: &Bytes32,
+    output_index: usize,
+    o: &Output,
+    inputs: &[Input],
+) -> TxOutput {
+    let output_type = match o {
+        Output::Coin { .. } => OutputType::Coin,
+        Output::Contract(_) => OutputType::Contract,
+        Output::Change { .. } => OutputType::Change,
+        Output::Variable { .. } => OutputType::Variable,
+        Output::ContractCreated { .. } => OutputType::ContractCreated,
+    };
+    let utxo_id = if o.is_coin() || o.is_change() || o.is_variable() {
+        Some(format!("{:x}", UtxoId::new(*tx_hash, output_index as u16)))
+    } else {
+        None
+    };
+    // a contract output refers to the contract input it updates
+    let contract_id = o.contract_id().copied().or_else(|| {
+        o.input_index()
+            .and_then(|i| inputs.get(i as usize))
+            .and_then(|i| i.contract_id().copied())
+    });
+
+    TxOutput {
+        transaction_id: tx_hash.to_string(),
+        output_index: output_index as i64,
+        output_type,
+        utxo_id,
+        to_address: o.to().map(|t| t.to_string()),
+        asset_id: o.asset_id().map(|a| a.to_string()),
+        amount: o.amount().map(|a| a as i64),
+        input_index: o.input_index().map(|i| i as i64),
+        contract_id: contract_id.map(|c| c.to_string()),
+        height: header.height as i64,
+        timestamp: header.time.to_unix(),
+    }
+}
+
+pub fn process_inputs_outputs(
+    header: &Header,
+    base_asset_id: &AssetId,
+    bodies: &BlockBodies,
+) -> (Vec<TxInput>, Vec<TxOutput>) {
+    let inputs_outputs = bodies
+        .par_iter()
+        .filter_map(|(tx_hash, tx, _)| {
+            let tx = &tx.as_ref()?.transaction;
+            let inputs = tx_inputs(tx);
+            let outputs = tx_outputs(tx);
+
+            let mut tx_inputs = inputs
+                .iter()
+                .enumerate()
+                .map(|(index, i)| tx_input(header, base_asset_id, tx_hash, index, i))
+                .collect::<Vec<_>>();
+            let mut tx_outputs = outputs
+                .iter()
+                .enumerate()
+                .map(|(index, o)| tx_output(header, tx_hash, index, o, inputs))
+                .collect::<Vec<_>>();
+
+            // the coinbase contract of a mint is kept outside of the inputs and outputs
+            if let FuelTransaction::Mint(m) = tx {
+                let input_contract = m.input_contract();
+                tx_inputs.push(contract_input(header, tx_hash, 0, input_contract));
+                tx_outputs.push(TxOutput {
+                    transaction_id: tx_hash.to_string(),
+                    output_index: 0,
+                    output_type: OutputType::Contract,
+                    utxo_id: None,
+                    to_address: None,
+                    asset_id: None,
+                    amount: None,
+                    input_index: Some(m.output_contract().input_index as i64),
+                    contract_id: Some(input_contract.contract_id.to_string()),
+                    height: header.height as i64,
+                    timestamp: header.time.to_unix(),
+                });
+            }
+
+            Some((tx_inputs, tx_outputs))
+        })
+        .collect::<Vec<_>>();
+
+    let (mut inputs, mut outputs) = (vec![], vec![]);
+    for (i, o) in inputs_outputs {
+        inputs.extend(i);
+        outputs.extend(o);
+    }
+
+    (inputs, outputs)
+}
@@ -0,0 +1,88 @@
+use fuel_core_client::client::types::{block::Header, TransactionStatus};
+use fuel_core_types::fuel_tx::{Input, Receipt};
+use models::message::{Message, MessageDirection};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::block_read::BlockBodies;
+
+use super::process::tx_inputs;
+
+// the node forgets the da height a deposit was relayed at once it is spent,
+// so both directions keep the da height of the block they were seen in, as
+// `block_da_height`. The relay height of a deposit is filled in later by the
+// `MessageRelayWatcher`, when it saw the message unspent.
+fn deposit(header: &Header, tx_hash: String, input_index: usize, i: &Input) -> Option<Message> {
+    Some(Message {
+        nonce: i.nonce()?.to_string(),
+        message_id: i.message_id()?.to_string(),
+        direction: MessageDirection::Deposit,
+        sender: i.sender()?.to_string(),
+        recipient: i.recipient()?.to_string(),
+        amount: i.amount()? as i64,
+        data: hex::encode(i.input_data().unwrap_or_default()),
+        block_da_height: header.da_height as i64,
+        transaction_id: tx_hash,
+        input_index: Some(input_index as i64),
+        receipt_index: None,
+        height: header.height as i64,
+        timestamp: header.time.to_unix(),
+        da_height: None,
+    })
+}
+
+fn withdrawal(
+    header: &Header,
+    tx_hash: String,
+    receipt_index: usize,
+    r: &Receipt,
+) -> Option<Message> {
+    Some(Message {
+        nonce: r.nonce()?.to_string(),
+        message_id: r.message_id()?.to_string(),
+        direction: MessageDirection::Withdrawal,
+        sender: r.sender()?.to_string(),
+        recipient: r.recipient()?.to_string(),
+        amount: r.amount()? as i64,
+        data: hex::encode(r.data().unwrap_or_default()),
+        block_da_height: header.da_height as i64,
+        transaction_id: tx_hash,
+        input_index: None,
+        receipt_index: Some(receipt_index as i64),
+        height: header.height as i64,
+        timestamp: header.time.to_unix(),
+        da_height: None,
+    })
+}
+
+pub fn process_messages(header: &Header, bodies: &BlockBodies) -> Vec<Message> {
+    bodies
+        .par_iter()
+        .filter_map(|(tx_hash, tx, receipts)| {
+            let tx = tx.as_ref()?;
+            let success = matches!(tx.status, TransactionStatus::Success { .. });
+
+            // a reverted tx only spends the messages without data and emits nothing
+            let mut messages = tx_inputs(&tx.transaction)
+                .iter()
+                .enumerate()
+                .filter(|(_, i)| {
+                    i.is_message_coin_signed() || i.is_message_coin_predicate() || success
+                })
+                .filter_map(|(index, i)| deposit(header, tx_hash.to_string(), index, i))
+                .collect::<Vec<_>>();
+
+            if success {
+                messages.extend(
+                    receipts
+                        .iter()
+                        .flatten()
+                        .enumerate()
+                        .filter_map(|(index, r)| withdrawal(header, tx_hash.to_string(), index, r)),
+                );
+            }
+
+            Some(messages)
+        })
+        .flatten()
+        .collect()
+}
@@ -1,10 +1,33 @@
 use crate::block_read::{BlockBodies, Blocks};
 
-use fuel_core_client::client::types::block::Header;
+use fuel_core_client::client::types::block::{Consensus, Header};
+use fuel_core_types::fuel_tx::AssetId;
 use models::{
-    account::batch_insert_accounts, assets::batch_insert_assets, block::batch_insert_block,
-    call::batch_insert_calls, coinbase::batch_insert_coinbase, contract::batch_insert_contracts,
-    transaction::batch_insert_transactions, PgSqlPool,
+    account::batch_insert_accounts,
+    address_transaction::batch_insert_address_transactions,
+    asset_holder::batch_insert_asset_holder_changes,
+    assets::{batch_delete_assets, batch_insert_assets},
+    balance::batch_insert_address_coin_balances,
+    block::{advance_indexed_height, batch_insert_block},
+    call::batch_insert_calls,
+    chain_stats::insert_block_stats,
+    coinbase::batch_insert_coinbase,
+    consensus_parameters::{
+        batch_insert_consensus_parameters, batch_insert_state_transition_bytecodes,
+    },
+    contract::batch_insert_contracts,
+    contract_abi::get_contract_abis,
+    contract_balance::batch_add_contract_balances,
+    message::batch_insert_messages,
+    notify::{notify_block, BlockNotification},
+    predicate::{batch_insert_predicate_spends, batch_insert_predicates},
+    script::{batch_insert_scripts, get_known_scripts},
+    token_transfer::batch_insert_token_transfers,
+    transaction::batch_insert_transactions,
+    transaction_failure::batch_insert_transaction_failures,
+    tx_input::batch_insert_tx_inputs,
+    tx_output::batch_insert_tx_outputs,
+    PgSqlPool,
 };
 
 use crate::block_handle::process::process;
@@ -13,12 +36,38 @@ use thiserror::Error;
 use tokio::{select, sync::broadcast};
 use tracing::{error, trace};
 
-use self::account::process_account;
+use self::{
+    account::process_account,
+    address_transactions::process_address_transactions,
+    asset_holders::process_asset_holders,
+    balances::process_balances,
+    chain_stats::process_chain_stats,
+    contract_balances::process_contract_balances,
+    failures::{failed_contracts, process_failures},
+    inputs_outputs::process_inputs_outputs,
+    messages::process_messages,
+    predicate::{label_predicate_accounts, process_predicates},
+    scripts::{classify_scripts, process_scripts, script_hashes},
+    token_transfers::process_token_transfers,
+    upgrades::process_upgrades,
+};
 
 pub mod account;
+pub mod address_transactions;
+pub mod asset_holders;
 pub mod assets;
+pub mod balances;
 pub mod blocks;
+pub mod chain_stats;
+pub mod contract_balances;
+pub mod failures;
+pub mod inputs_outputs;
+pub mod messages;
+pub mod predicate;
 pub mod process;
+pub mod scripts;
+pub mod token_transfers;
+pub mod upgrades;
 
 pub const CHAIN_ID: u64 = 0;
 
@@ -36,6 +85,38 @@ pub enum BlockHandlerError {
     InsertAssets(String),
     #[error("insert accounts failed: {0}")]
     InsertAccounts(String),
+    #[error("insert asset holder changes failed: {0}")]
+    InsertAssetHolderChanges(String),
+    #[error("insert address coin balances failed: {0}")]
+    InsertBalances(String),
+    #[error("insert predicates failed: {0}")]
+    InsertPredicates(String),
+    #[error("insert transaction inputs and outputs failed: {0}")]
+    InsertInputsOutputs(String),
+    #[error("insert messages failed: {0}")]
+    InsertMessages(String),
+    #[error("insert upgrades failed: {0}")]
+    InsertUpgrades(String),
+    #[error("insert transaction failures failed: {0}")]
+    InsertFailures(String),
+    #[error("read contract abis failed: {0}")]
+    ReadAbis(String),
+    #[error("insert scripts failed: {0}")]
+    InsertScripts(String),
+    #[error("read known scripts failed: {0}")]
+    ReadKnownScripts(String),
+    #[error("insert token transfers failed: {0}")]
+    InsertTokenTransfers(String),
+    #[error("insert contract balances failed: {0}")]
+    InsertContractBalances(String),
+    #[error("insert address transactions failed: {0}")]
+    InsertAddressTransactions(String),
+    #[error("insert chain statistics failed: {0}")]
+    InsertChainStats(String),
+    #[error("notify block failed: {0}")]
+    NotifyBlock(String),
+    #[error("advance indexed height failed: {0}")]
+    AdvanceIndexedHeight(String),
     #[error("failed to insert into db: {0}")]
     InsertDb(#[from(diesel::result::Error)] String),
     #[error("failed to insert into db: {0}")]
@@ -49,8 +130,10 @@ pub enum BlockHandlerError {
 #[derive(Clone)]
 pub struct BlockHandler {
     db_client: PgSqlPool,
+    base_asset_id: AssetId,
     block_rx: flume::Receiver<Blocks>,
     shutdown: broadcast::Sender<()>,
+    committed: Option<flume::Sender<i64>>,
 }
 
 impl Drop for BlockHandler {
@@ -68,19 +151,24 @@ impl From<diesel::result::Error> for BlockHandlerError {
 impl BlockHandler {
     pub fn new(
         db_client: PgSqlPool,
+        base_asset_id: AssetId,
         block_rx: flume::Receiver<Blocks>,
         shutdown: broadcast::Sender<()>,
+        committed: Option<flume::Sender<i64>>,
     ) -> Self {
         Self {
             db_client,
+            base_asset_id,
             block_rx,
             shutdown,
+            committed,
         }
     }
 
     async fn insert_header_and_txs(
         &mut self,
         header: &Header,
+        consensus: &Consensus,
         bodies: &BlockBodies,
     ) -> Result<(), BlockHandlerError> {
         let mut conn = self
@@ -88,20 +176,53 @@ impl BlockHandler {
             .get()
             .map_err(|e| BlockHandlerError::GetPgSqlPoolFailed(e.to_string()))?;
 
-        let (block, coinbase, transactions, contracts, calls, (assets_delete, assets_insert)) =
-            process(header, bodies)
+        let (block, coinbase, mut transactions, contracts, calls, (assets_delete, assets_insert)) =
+            process(header, consensus, bodies)
                 .await
                 .map_err(|e| BlockHandlerError::DataProcessError(e.to_string()))?;
 
-        let accounts = process_account(&calls);
+        let notification = BlockNotification::from(&block);
+        let (predicates, predicate_spends) = process_predicates(header, bodies);
+        let (tx_inputs, tx_outputs) = process_inputs_outputs(header, &self.base_asset_id, bodies);
+        let messages = process_messages(header, bodies);
+        let token_transfers = process_token_transfers(header, bodies);
+        let contract_balances = process_contract_balances(header, bodies);
+        let mut accounts = process_account(header, bodies, &transactions, &calls, &token_transfers);
+        label_predicate_accounts(&mut accounts, &predicates);
+        let balances = process_balances(header, &assets_delete, &assets_insert);
+        let asset_holders =
+            process_asset_holders(header.height as i64, &assets_delete, &assets_insert);
+        let (consensus_parameters, state_transition_bytecodes) = process_upgrades(header, bodies);
+        let abis = get_contract_abis(&mut conn, &failed_contracts(bodies))
+            .map_err(|e| BlockHandlerError::ReadAbis(e.to_string()))?;
+        let failures = process_failures(header, bodies, &abis);
+        let known_scripts = get_known_scripts(&mut conn, &script_hashes(bodies))
+            .map_err(|e| BlockHandlerError::ReadKnownScripts(e.to_string()))?;
+        classify_scripts(&mut transactions, bodies, &known_scripts);
+        let scripts = process_scripts(header, bodies);
+        let address_transactions = process_address_transactions(
+            &transactions,
+            &tx_inputs,
+            &tx_outputs,
+            &calls,
+            &token_transfers,
+        );
+        let chain_stats =
+            process_chain_stats(header, &transactions, &contracts, &address_transactions);
 
         conn.build_transaction()
             .read_write()
             .serializable()
             .deferrable()
-            .run(|conn| {
-                batch_insert_block(conn, &vec![block])
+            .run::<_, BlockHandlerError, _>(|conn| {
+                let inserted = batch_insert_block(conn, &vec![block])
                     .map_err(|e| BlockHandlerError::InsertHeaderDb(e.to_string()))?;
+                if inserted == 0 {
+                    // already committed before a restart, the account
+                    // statistics must not be added twice.
+                    trace!("block {} already indexed", header.height);
+                    return Ok(());
+                }
                 if let Some(c) = coinbase {
                     batch_insert_coinbase(conn, &vec![c])
                         .map_err(|e| BlockHandlerError::InsertHeaderDb(e.to_string()))?;
@@ -116,16 +237,80 @@ impl BlockHandler {
                 batch_insert_calls(conn, &calls)
                     .map_err(|e| BlockHandlerError::InsertCalls(e.to_string()))?;
 
-                batch_insert_assets(conn, &assets_delete)
+                batch_insert_assets(conn, &assets_insert)
                     .map_err(|e| BlockHandlerError::InsertAssets(e.to_string()))?;
 
-                batch_insert_assets(conn, &assets_insert)
+                batch_delete_assets(conn, &assets_delete)
                     .map_err(|e| BlockHandlerError::InsertAssets(e.to_string()))?;
 
+                batch_insert_address_coin_balances(conn, &balances)
+                    .map_err(|e| BlockHandlerError::InsertBalances(e.to_string()))?;
+
+                batch_insert_asset_holder_changes(conn, &asset_holders)
+                    .map_err(|e| BlockHandlerError::InsertAssetHolderChanges(e.to_string()))?;
+
+                batch_insert_tx_inputs(conn, &tx_inputs)
+                    .map_err(|e| BlockHandlerError::InsertInputsOutputs(e.to_string()))?;
+
+                batch_insert_tx_outputs(conn, &tx_outputs)
+                    .map_err(|e| BlockHandlerError::InsertInputsOutputs(e.to_string()))?;
+
+                batch_insert_messages(conn, &messages)
+                    .map_err(|e| BlockHandlerError::InsertMessages(e.to_string()))?;
+
+                batch_insert_predicates(conn, &predicates)
+                    .map_err(|e| BlockHandlerError::InsertPredicates(e.to_string()))?;
+
+                batch_insert_predicate_spends(conn, &predicate_spends)
+                    .map_err(|e| BlockHandlerError::InsertPredicates(e.to_string()))?;
+
+                batch_add_contract_balances(conn, &contract_balances)
+                    .map_err(|e| BlockHandlerError::InsertContractBalances(e.to_string()))?;
+
+                batch_insert_token_transfers(conn, &token_transfers)
+                    .map_err(|e| BlockHandlerError::InsertTokenTransfers(e.to_string()))?;
+
+                batch_insert_address_transactions(
+                    conn,
+                    header.height as i64,
+                    &address_transactions,
+                )
+                .map_err(|e| BlockHandlerError::InsertAddressTransactions(e.to_string()))?;
+
+                batch_insert_scripts(conn, &scripts)
+                    .map_err(|e| BlockHandlerError::InsertScripts(e.to_string()))?;
+
+                batch_insert_transaction_failures(conn, &failures)
+                    .map_err(|e| BlockHandlerError::InsertFailures(e.to_string()))?;
+
+                batch_insert_consensus_parameters(conn, &consensus_parameters)
+                    .map_err(|e| BlockHandlerError::InsertUpgrades(e.to_string()))?;
+
+                batch_insert_state_transition_bytecodes(conn, &state_transition_bytecodes)
+                    .map_err(|e| BlockHandlerError::InsertUpgrades(e.to_string()))?;
+
                 batch_insert_accounts(conn, &accounts)
                     .map_err(|e| BlockHandlerError::InsertAccounts(e.to_string()))?;
+
+                insert_block_stats(conn, &chain_stats)
+                    .map_err(|e| BlockHandlerError::InsertChainStats(e.to_string()))?;
+
+                // delivered on commit only
+                notify_block(conn, &notification)
+                    .map_err(|e| BlockHandlerError::NotifyBlock(e.to_string()))?;
                 Ok(())
-            })
+            })?;
+
+        // outside the block transaction, handlers only wait on each other
+        // for this row
+        advance_indexed_height(&mut conn)
+            .map_err(|e| BlockHandlerError::AdvanceIndexedHeight(e.to_string()))?;
+
+        // also when it was already indexed, the feed waits for every height
+        if let Some(committed) = &self.committed {
+            let _ = committed.send(header.height as i64);
+        }
+        Ok(())
     }
 
     pub async fn start(&mut self) -> Result<(), BlockHandlerError> {
@@ -134,8 +319,8 @@ impl BlockHandler {
         loop {
             select! {
                 Ok(blocks) = self.block_rx.recv_async() => {
-                    for (header, transactions) in blocks {
-                        while let Err(e) = self.insert_header_and_txs(&header,&transactions).await {
+                    for (header, consensus, transactions) in blocks {
+                        while let Err(e) = self.insert_header_and_txs(&header, &consensus, &transactions).await {
                             error!("insert_header_and_tx failed {}, retrying",e.to_string());
                             tokio::time::sleep(Duration::from_secs(1)).await;
                         }
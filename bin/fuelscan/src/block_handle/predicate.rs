@@ -0,0 +1,96 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use fuel_core_client::client::types::block::Header;
+use fuel_core_types::fuel_tx::{Contract as FuelContract, Input};
+use models::{
+    account::{Account, AccountType},
+    predicate::{Predicate, PredicateSpend},
+};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::block_read::BlockBodies;
+
+use super::process::tx_inputs;
+
+fn predicate_spends<'a>(
+    header: &Header,
+    tx_hash: String,
+    inputs: &'a [Input],
+) -> Vec<(PredicateSpend, &'a [u8])> {
+    inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(input_index, input)| {
+            let (predicate, predicate_data, predicate_gas_used) = input.predicate()?;
+            // the owner of a predicate input is derived from its bytecode root
+            let predicate_hash = Input::predicate_owner(predicate);
+
+            let spend = PredicateSpend {
+                transaction_id: tx_hash.clone(),
+                input_index: input_index as i64,
+                predicate_hash: predicate_hash.to_string(),
+                utxo_id: input.utxo_id().map(|u| format!("{:x}", u)),
+                nonce: input.nonce().map(|n| n.to_string()),
+                amount: input.amount().unwrap_or_default() as i64,
+                predicate_data: hex::encode(predicate_data),
+                predicate_gas_used: *predicate_gas_used as i64,
+                height: header.height as i64,
+                timestamp: header.time.to_unix(),
+            };
+            Some((spend, predicate))
+        })
+        .collect()
+}
+
+pub fn process_predicates(
+    header: &Header,
+    bodies: &BlockBodies,
+) -> (Vec<Predicate>, Vec<PredicateSpend>) {
+    let spends = bodies
+        .par_iter()
+        .filter_map(|(tx_hash, tx, _)| {
+            tx.as_ref()
+                .map(|tx| predicate_spends(header, tx_hash.to_string(), tx_inputs(&tx.transaction)))
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let mut predicates: HashMap<String, Predicate> = HashMap::new();
+    for (spend, bytecode) in &spends {
+        match predicates.entry(spend.predicate_hash.clone()) {
+            Entry::Occupied(mut e) => e.get_mut().usage_count += 1,
+            Entry::Vacant(e) => {
+                e.insert(Predicate {
+                    predicate_hash: spend.predicate_hash.clone(),
+                    bytecode_root: FuelContract::root_from_code(bytecode).to_string(),
+                    bytecode: hex::encode(bytecode),
+                    bytecode_length: bytecode.len() as i64,
+                    first_transaction_id: spend.transaction_id.clone(),
+                    first_height: header.height as i64,
+                    usage_count: 1,
+                    timestamp: header.time.to_unix(),
+                });
+            }
+        }
+    }
+
+    (
+        predicates.into_values().collect(),
+        spends.into_iter().map(|(spend, _)| spend).collect(),
+    )
+}
+
+// predicate owned addresses keep their bytecode as the account code.
+pub fn label_predicate_accounts(accounts: &mut [Account], predicates: &[Predicate]) {
+    let bytecodes = predicates
+        .iter()
+        .map(|p| (p.predicate_hash.as_str(), p.bytecode.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    for account in accounts.iter_mut() {
+        if let Some(bytecode) = bytecodes.get(account.account_hash.as_str()) {
+            account.account_type = AccountType::Predicate;
+            account.account_code = Some(bytecode.to_string());
+        }
+    }
+}
@@ -1,12 +1,15 @@
 use anyhow::Result;
-use fuel_core_client::client::types::{block::Header, TransactionStatus};
+use fuel_core_client::client::types::{
+    block::{Consensus, Header},
+    TransactionStatus,
+};
 use fuel_core_types::fuel_tx::{
     field::{
-        BytecodeWitnessIndex, Inputs, MaxFeeLimit, MintAmount, MintGasPrice, Outputs, Script,
+        BytecodeWitnessIndex, InputContract, Inputs, MaxFeeLimit, MintAmount, Outputs, Script,
         ScriptData, StorageSlots, Witnesses,
     },
-    input::coin::Coin,
-    Input, Receipt,
+    input::{coin::Coin, message::Message},
+    Input, Output, Receipt, Transaction as FuelTransaction,
 };
 
 use models::{
@@ -17,14 +20,15 @@ use models::{
     contract::Contract,
     transaction::{Transaction, TxStatus, TxType},
 };
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use crate::block_read::{BlockBodies, BlockBody};
 
-use super::{assets::assets_process, blocks::init_block_by_with_header};
+use super::{assets::assets_process, blocks::init_block_by_with_header, scripts::script_hash};
 
 pub async fn process(
     header: &Header,
+    consensus: &Consensus,
     bodies: &BlockBodies,
 ) -> Result<(
     Block,
@@ -34,19 +38,20 @@ pub async fn process(
     Vec<Call>,
     (Vec<Assets>, Vec<Assets>),
 )> {
-    let mut block = init_block_by_with_header(header);
+    let mut block = init_block_by_with_header(header, consensus);
     let mut coinbase: Option<Coinbase> = None;
 
-    if let Some((tx, coinbase_tx, _)) = coinbase_pick(bodies) {
+    if let Some((tx_index, (tx, coinbase_tx, _))) = coinbase_pick(bodies) {
         block.coinbase_hash = Some(tx.to_string());
         if let Some(c) = coinbase_tx.clone().unwrap().transaction.as_mint() {
             block.coinbase_amount = Some(*c.mint_amount() as i64);
-            //block.coinbase = Some(c.outputs()[0].to().unwrap().to_string());
-            block.coinbase = None;
+            // the block reward is minted into the coinbase contract
+            block.coinbase = Some(c.input_contract().contract_id.to_string());
 
             coinbase = Some(Coinbase {
                 id: tx.to_string(),
                 height: header.height as i64,
+                tx_index: tx_index as i64,
                 da_height: header.da_height as i64,
                 block_hash: header.id.to_string(),
                 amount: block.coinbase_amount,
@@ -71,6 +76,8 @@ pub async fn process(
         calls.push(call);
     }
 
+    transactions.extend(upgrade_upload_transactions(header, bodies));
+
     let assrts = assets_process(header, bodies);
 
     Ok((block, coinbase, transactions, contracts, calls, assrts))
@@ -87,17 +94,18 @@ pub fn deploy_contract_transactions(
 
     let contract_txs = bodies
         .par_iter()
-        .filter(|tx| tx.1.as_ref().is_some())
-        .filter(|tx| tx.1.as_ref().unwrap().transaction.is_create())
+        .enumerate()
+        .filter(|(_, tx)| tx.1.as_ref().is_some())
+        .filter(|(_, tx)| tx.1.as_ref().unwrap().transaction.is_create())
         .collect::<Vec<_>>();
 
     contract_txs
         .par_iter()
-        .map(|(tx_hash, tx, receipts)| {
+        .map(|(tx_index, (tx_hash, tx, receipts))| {
             //this is safe we already check
             let create = tx.as_ref().unwrap().transaction.as_create().unwrap();
-            let sender = find_sender(create);
-            let (status, reason) = match tx.clone().unwrap().status {
+            let sender = find_sender(create.inputs()).expect("can't find coin sign");
+            let (status, reason, fee) = match tx.clone().unwrap().status {
                 TransactionStatus::Submitted { submitted_at: _ } => unreachable!(),
                 TransactionStatus::Success {
                     time: _,
@@ -105,8 +113,8 @@ pub fn deploy_contract_transactions(
                     receipts: _,
                     block_height: _,
                     total_gas: _,
-                    total_fee: _,
-                } => (TxStatus::Success, "".to_string()),
+                    total_fee,
+                } => (TxStatus::Success, "".to_string(), total_fee),
                 TransactionStatus::SqueezedOut { reason: _ } => unimplemented!(),
                 TransactionStatus::Failure {
                     block_height: _,
@@ -115,8 +123,8 @@ pub fn deploy_contract_transactions(
                     program_state: _,
                     receipts: _,
                     total_gas: _,
-                    total_fee: _,
-                } => (TxStatus::Failed, reason),
+                    total_fee,
+                } => (TxStatus::Failed, reason, total_fee),
             };
 
             let input = serde_json::to_value(create.inputs()).ok();
@@ -131,19 +139,23 @@ pub fn deploy_contract_transactions(
                 Transaction {
                     id: tx_hash.to_string(),
                     height: header.height as i64,
+                    tx_index: *tx_index as i64,
                     da_height: header.da_height as i64,
                     block_hash: header.id.to_string(),
                     tx_type: Some(TxType::Deploy),
                     gas_limit: create.max_fee_limit() as i64,
                     gas_price: 0,
                     gas_used,
-                    timestamp: header.time.clone().to_unix(),
+                    fee: fee as i64,
+                    timestamp: header.time.to_unix(),
                     sender: Some(sender.clone()),
                     status,
                     reason,
                     input,
                     output,
                     receipts: serde_json::to_value(receipts).ok(),
+                    script_hash: None,
+                    script_category: None,
                 },
                 Contract {
                     contract_hash: tx_hash.to_string(),
@@ -157,40 +169,92 @@ pub fn deploy_contract_transactions(
                     ),
                     bytecoin_length: *create.bytecode_witness_index() as i64,
                     storage_slots: serde_json::to_value(create.storage_slots()).ok(),
-                    timestamp: header.time.clone().to_unix(),
+                    timestamp: header.time.to_unix(),
                 },
             )
         })
         .collect::<Vec<_>>()
 }
 
-fn find_sender(create: &fuel_core_types::fuel_tx::Create) -> String {
-    //TODO add more select contation in here
-    let sender = create
-        .inputs()
+/// Upgrade and upload transactions, they run no script so they have no
+/// gas limit, only the gas their status charged.
+pub fn upgrade_upload_transactions(header: &Header, bodies: &BlockBodies) -> Vec<Transaction> {
+    bodies
         .par_iter()
-        .find_first(|t| t.is_coin_signed())
-        .and_then(|t| match t {
-            Input::CoinSigned(Coin { owner, .. }) => Some(owner.to_string()),
-            /*                     Input::CoinPredicate(_) => todo!(),
-                    Input::Contract(_) => todo!(),
-                    Input::MessageCoinSigned(_) => todo!(),
-                    Input::MessageCoinPredicate(_) => todo!(),
-                    Input::MessageDataSigned(_) => todo!(),
-                    Input::MessageDataPredicate(_) => todo!(), */
-            /* Input::Signed({
-                        utxo_id: _,
-                        owner,
-                        amount: _,
-                        asset_id: _,
-                        tx_pointer: _,
-                        witness_index: _,
-                        maturity: _,
-                    }) => Some(owner.to_string()), */
-            _ => None,
+        .enumerate()
+        .filter_map(|(tx_index, (tx_hash, tx, receipts))| {
+            let tx = tx.as_ref()?;
+            let tx_type = match &tx.transaction {
+                FuelTransaction::Upgrade(_) => TxType::Upgrade,
+                FuelTransaction::Upload(_) => TxType::Upload,
+                _ => return None,
+            };
+            let inputs = tx_inputs(&tx.transaction);
+            let (status, reason, gas_used, fee) = match tx.status.clone() {
+                TransactionStatus::Success {
+                    total_gas,
+                    total_fee,
+                    ..
+                } => (TxStatus::Success, "".to_string(), total_gas, total_fee),
+                TransactionStatus::Failure {
+                    reason,
+                    total_gas,
+                    total_fee,
+                    ..
+                } => (TxStatus::Failed, reason, total_gas, total_fee),
+                // never in a block
+                TransactionStatus::Submitted { .. } | TransactionStatus::SqueezedOut { .. } => {
+                    return None
+                }
+            };
+
+            Some(Transaction {
+                id: tx_hash.to_string(),
+                height: header.height as i64,
+                tx_index: tx_index as i64,
+                da_height: header.da_height as i64,
+                block_hash: header.id.to_string(),
+                tx_type: Some(tx_type),
+                gas_limit: 0,
+                gas_price: 0,
+                gas_used: gas_used as i64,
+                fee: fee as i64,
+                timestamp: header.time.to_unix(),
+                sender: find_sender(inputs),
+                status,
+                reason,
+                input: serde_json::to_value(inputs).ok(),
+                output: serde_json::to_value(tx_outputs(&tx.transaction)).ok(),
+                receipts: receipts.as_ref().and_then(|r| serde_json::to_value(r).ok()),
+                script_hash: None,
+                script_category: None,
+            })
+        })
+        .collect()
+}
+
+/// The address paying for a transaction: a signed coin or message input
+/// first, the owner of a predicate input only when nobody signed it.
+pub fn find_sender(inputs: &[Input]) -> Option<String> {
+    let signed = inputs.par_iter().find_first(|t| {
+        t.is_coin_signed() || t.is_message_coin_signed() || t.is_message_data_signed()
+    });
+    let predicate = || {
+        inputs.par_iter().find_first(|t| {
+            t.is_coin_predicate() || t.is_message_coin_predicate() || t.is_message_data_predicate()
         })
-        .expect("can't find coin sign");
-    sender
+    };
+
+    signed.or_else(predicate).and_then(|t| match t {
+        Input::CoinSigned(Coin { owner, .. }) | Input::CoinPredicate(Coin { owner, .. }) => {
+            Some(owner.to_string())
+        }
+        Input::MessageCoinSigned(Message { recipient, .. })
+        | Input::MessageCoinPredicate(Message { recipient, .. })
+        | Input::MessageDataSigned(Message { recipient, .. })
+        | Input::MessageDataPredicate(Message { recipient, .. }) => Some(recipient.to_string()),
+        Input::Contract(_) => None,
+    })
 }
 
 pub fn calls_transactions(header: &Header, bodies: &BlockBodies) -> Vec<(Transaction, Call)> {
@@ -201,54 +265,40 @@ pub fn calls_transactions(header: &Header, bodies: &BlockBodies) -> Vec<(Transac
 
     let contract_txs = bodies
         .par_iter()
-        .filter(|tx| tx.1.as_ref().is_some())
-        .filter(|tx| tx.1.as_ref().unwrap().transaction.is_script())
+        .enumerate()
+        .filter(|(_, tx)| tx.1.as_ref().is_some())
+        .filter(|(_, tx)| tx.1.as_ref().unwrap().transaction.is_script())
         .collect::<Vec<_>>();
 
     contract_txs
         .par_iter()
-        .map(|(tx_hash, tx, receipts)| {
+        .map(|(tx_index, (tx_hash, tx, receipts))| {
             //this is safe we already check
             let call = tx.as_ref().unwrap().transaction.as_script().unwrap();
 
-            let (sender, _signed_asset_id) = call
-                .inputs()
-                .par_iter()
-                .find_first(|t| t.is_coin_signed() || t.is_coin_predicate())
-                .and_then(|t| match t {
-                    Input::CoinSigned(Coin {
-                        owner, asset_id, ..
-                    }) => Some((owner.to_string(), asset_id)),
-                    Input::CoinPredicate(Coin {
-                        owner, asset_id, ..
-                    }) => Some((owner.to_string(), asset_id)),
-                    /*                     Input::MessageCoinSigned(Coin { recipient, .. }) => {
-                        Some((recipient.to_string(), &AssetId::BASE))
-                    } */
-                    _ => None,
-                })
-                .expect("can't find coin signer"); //TODO maybe there have more when one? We need find all of them and fingout what's gas,
-
-            let (status, reason) = match tx.clone().unwrap().status {
+            //TODO maybe there have more when one? We need find all of them and fingout what's gas,
+            let sender = find_sender(call.inputs()).expect("can't find coin signer");
+
+            let (status, reason, fee) = match tx.clone().unwrap().status {
                 TransactionStatus::Submitted { submitted_at: _ } => unreachable!(),
                 TransactionStatus::Success {
                     time: _,
                     program_state: _,
                     receipts: _,
-                    block_height,
-                    total_gas,
+                    block_height: _,
+                    total_gas: _,
                     total_fee,
-                } => (TxStatus::Success, "".to_string()),
+                } => (TxStatus::Success, "".to_string(), total_fee),
                 TransactionStatus::SqueezedOut { reason: _ } => unimplemented!(),
                 TransactionStatus::Failure {
                     time: _,
                     reason,
                     program_state: _,
                     receipts: _,
-                    block_height,
-                    total_gas,
+                    block_height: _,
+                    total_gas: _,
                     total_fee,
-                } => (TxStatus::Failed, reason),
+                } => (TxStatus::Failed, reason, total_fee),
             };
 
             let receipts = receipts.as_ref().expect("TODO: There is no receipt");
@@ -261,58 +311,68 @@ pub fn calls_transactions(header: &Header, bodies: &BlockBodies) -> Vec<(Transac
             let input = serde_json::to_value(call.inputs()).ok();
             let output = serde_json::to_value(call.outputs()).ok();
 
-            let (call_type, amount, asset_id, to, payload, payload_data) = if call
+            let (call_type, amount, asset_id, to, payload, payload_data, receipt_index) = if call
                 .outputs()
                 .par_iter()
                 .any(|t| t.is_contract() || t.is_contract_created())
             {
                 let payload = call.script();
                 let payload_data = call.script_data();
-                match receipts
-                    .par_iter()
-                    .find_first(|receipt| match receipt {
-                        Receipt::Call { .. } => true,
-                        Receipt::Transfer { .. } => true,
-
-                        //there is also contract created but maybe failed.
-                        //we shuld check this and put in the tarnscation table or what??
-                        _ => true,
-                    })
-                    .expect("can't find coin output")
-                {
-                    Receipt::Call {
-                        amount,
-                        asset_id,
-                        to,
-                        id: _,
-                        gas: _,
-                        param1: _,
-                        param2: _,
-                        pc: _,
-                        is: _,
-                    } => (
+                // the first call or transfer out of the script, none when the
+                // script failed before reaching one
+                let called = receipts.par_iter().enumerate().find_first(|(_, receipt)| {
+                    matches!(receipt, Receipt::Call { .. } | Receipt::Transfer { .. })
+                });
+                match called {
+                    Some((
+                        receipt_index,
+                        Receipt::Call {
+                            amount,
+                            asset_id,
+                            to,
+                            id: _,
+                            gas: _,
+                            param1: _,
+                            param2: _,
+                            pc: _,
+                            is: _,
+                        },
+                    )) => (
                         CallType::Contract,
                         Some(*amount as i64),
                         Some(asset_id.to_string()),
                         to.to_string(),
                         Some(hex::encode(payload)),
                         Some(hex::encode(payload_data)),
+                        Some(receipt_index as i64),
                     ),
-                    Receipt::Transfer {
-                        id,
-                        //to,
-                        amount,
-                        asset_id,
-                        ..
-                    } => (
+                    Some((
+                        receipt_index,
+                        Receipt::Transfer {
+                            id,
+                            //to,
+                            amount,
+                            asset_id,
+                            ..
+                        },
+                    )) => (
                         CallType::Contract,
                         Some(*amount as i64),
                         Some(asset_id.to_string()),
                         id.to_string(),
                         Some(hex::encode(payload)),
                         Some(hex::encode(payload_data)),
+                        Some(receipt_index as i64),
+                    ),
+                    _ => (
+                        CallType::Contract,
+                        None,
+                        None,
+                        "".to_string(),
+                        None,
+                        None,
+                        None,
                     ),
-                    _ => (CallType::Contract, None, None, "".to_string(), None, None),
                 }
             } else {
                 let (amount, id, to) = match call
@@ -364,6 +424,7 @@ pub fn calls_transactions(header: &Header, bodies: &BlockBodies) -> Vec<(Transac
                     to.to_string(),
                     payload,
                     payload_data,
+                    None,
                 )
             };
 
@@ -371,23 +432,29 @@ pub fn calls_transactions(header: &Header, bodies: &BlockBodies) -> Vec<(Transac
                 Transaction {
                     id: tx_hash.to_string(),
                     height: header.height as i64,
+                    tx_index: *tx_index as i64,
                     da_height: header.da_height as i64,
                     block_hash: header.id.to_string(),
                     tx_type: Some(TxType::Call),
                     gas_limit: call.max_fee_limit() as i64,
                     gas_price: 0,
                     gas_used,
-                    timestamp: header.time.clone().to_unix(),
+                    fee: fee as i64,
+                    timestamp: header.time.to_unix(),
                     sender: Some(sender.to_string()),
                     status,
                     reason,
                     input,
                     output,
                     receipts: serde_json::to_value(receipts).ok(),
+                    script_hash: Some(script_hash(call.script())),
+                    // filled in by `classify_scripts`
+                    script_category: None,
                 },
                 Call {
                     transaction_id: tx_hash.to_string(),
                     height: header.height as i64,
+                    tx_index: *tx_index as i64,
                     da_height: header.da_height as i64,
                     block_hash: header.id.to_string(),
                     call_type,
@@ -400,18 +467,40 @@ pub fn calls_transactions(header: &Header, bodies: &BlockBodies) -> Vec<(Transac
                     asset_id,
                     payload,
                     payload_data,
-                    timestamp: header.time.clone().to_unix(),
+                    receipt_index,
+                    timestamp: header.time.to_unix(),
                 },
             )
         })
         .collect::<Vec<_>>()
 }
 
-pub fn coinbase_pick(bodies: &BlockBodies) -> Option<&BlockBody> {
+/// The inputs of any transaction type, a mint has no coin inputs.
+pub fn tx_inputs(tx: &FuelTransaction) -> &[Input] {
+    match tx {
+        FuelTransaction::Script(s) => s.inputs(),
+        FuelTransaction::Create(c) => c.inputs(),
+        FuelTransaction::Upgrade(u) => u.inputs(),
+        FuelTransaction::Upload(u) => u.inputs(),
+        FuelTransaction::Mint(_) => &[],
+    }
+}
+
+/// The outputs of any transaction type, a mint has no coin outputs.
+pub fn tx_outputs(tx: &FuelTransaction) -> &[Output] {
+    match tx {
+        FuelTransaction::Script(s) => s.outputs(),
+        FuelTransaction::Create(c) => c.outputs(),
+        FuelTransaction::Upgrade(u) => u.outputs(),
+        FuelTransaction::Upload(u) => u.outputs(),
+        FuelTransaction::Mint(_) => &[],
+    }
+}
+
+/// The mint transaction of a block along with its position in the block.
+pub fn coinbase_pick(bodies: &BlockBodies) -> Option<(usize, &BlockBody)> {
     bodies
         .par_iter()
-        .find_first(|tx| tx.1.is_some())
-        .par_iter()
-        .find_first(|tx| tx.1.clone().unwrap().transaction.is_mint())
-        .cloned()
+        .enumerate()
+        .find_first(|(_, tx)| tx.1.as_ref().is_some_and(|t| t.transaction.is_mint()))
 }
@@ -0,0 +1,170 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use fuel_core_client::client::types::block::Header;
+use fuel_core_types::{
+    fuel_asm::{from_bytes, Opcode},
+    fuel_crypto::Hasher,
+    fuel_tx::{
+        field::{Inputs, Script as ScriptField},
+        Script as FuelScript,
+    },
+};
+use models::{
+    script::{Script, ScriptCategory},
+    transaction::Transaction,
+};
+
+use crate::block_read::BlockBodies;
+
+pub fn script_hash(script: &[u8]) -> String {
+    Hasher::hash(script).to_string()
+}
+
+fn scripts(bodies: &BlockBodies) -> impl Iterator<Item = (String, &FuelScript)> {
+    bodies.iter().filter_map(|(tx_hash, tx, _)| {
+        tx.as_ref()?
+            .transaction
+            .as_script()
+            .map(|script| (tx_hash.to_string(), script))
+    })
+}
+
+// a guess from the instructions for scripts nobody has labeled, the SDKs
+// unroll one `CALL` per contract call.
+fn guess_category(script: &FuelScript) -> ScriptCategory {
+    let instructions = from_bytes(script.script().iter().copied())
+        .filter_map(Result::ok)
+        .map(|i| i.opcode())
+        .collect::<Vec<_>>();
+
+    match instructions
+        .iter()
+        .filter(|op| **op == Opcode::CALL)
+        .count()
+    {
+        0 if instructions
+            .iter()
+            .all(|op| matches!(op, Opcode::RET | Opcode::NOOP)) =>
+        {
+            ScriptCategory::Transfer
+        }
+        0 => ScriptCategory::Custom,
+        1 => ScriptCategory::ContractCall,
+        _ => ScriptCategory::Multicall,
+    }
+}
+
+fn predicate_only(script: &FuelScript) -> bool {
+    !script.inputs().is_empty()
+        && script
+            .inputs()
+            .iter()
+            .filter(|i| !i.is_contract())
+            .all(|i| i.predicate().is_some())
+}
+
+/// The hashes of the scripts run in this block.
+pub fn script_hashes(bodies: &BlockBodies) -> Vec<String> {
+    let mut hashes = scripts(bodies)
+        .map(|(_, script)| script_hash(script.script()))
+        .collect::<Vec<_>>();
+    hashes.sort();
+    hashes.dedup();
+    hashes
+}
+
+pub fn process_scripts(header: &Header, bodies: &BlockBodies) -> Vec<Script> {
+    let mut scripts_used: HashMap<String, Script> = HashMap::new();
+    for (tx_hash, script) in scripts(bodies) {
+        match scripts_used.entry(script_hash(script.script())) {
+            Entry::Occupied(mut e) => e.get_mut().usage_count += 1,
+            Entry::Vacant(e) => {
+                let bytecode = script.script();
+                let script_hash = e.key().clone();
+                e.insert(Script {
+                    script_hash,
+                    bytecode: hex::encode(bytecode),
+                    bytecode_length: bytecode.len() as i64,
+                    first_transaction_id: tx_hash,
+                    first_height: header.height as i64,
+                    usage_count: 1,
+                    timestamp: header.time.to_unix(),
+                });
+            }
+        }
+    }
+
+    scripts_used.into_values().collect()
+}
+
+// labeled scripts keep their label, the others get a guessed category. A
+// transfer whose coins are all owned by predicates is a predicate spend,
+// whichever transfer script it runs.
+pub fn classify_scripts(
+    transactions: &mut [Transaction],
+    bodies: &BlockBodies,
+    known: &HashMap<String, ScriptCategory>,
+) {
+    let categories = scripts(bodies)
+        .map(|(tx_hash, script)| {
+            let category = known
+                .get(&script_hash(script.script()))
+                .copied()
+                .unwrap_or_else(|| guess_category(script));
+            match category {
+                ScriptCategory::Transfer if predicate_only(script) => {
+                    (tx_hash, ScriptCategory::PredicateSpend)
+                }
+                category => (tx_hash, category),
+            }
+        })
+        .collect::<HashMap<_, _>>();
+
+    for tx in transactions.iter_mut() {
+        if let Some(category) = categories.get(&tx.id) {
+            tx.script_category = Some(*category);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fuel_core_types::fuel_asm::{op, Instruction, RegId};
+
+    use super::*;
+
+    fn script(instructions: &[Instruction]) -> FuelScript {
+        let mut script = FuelScript::default();
+        *script.script_mut() = instructions.iter().copied().collect();
+        script
+    }
+
+    #[test]
+    fn the_transfer_script_hash_is_seeded() {
+        assert_eq!(
+            script_hash(script(&[op::ret(RegId::ONE)]).script()),
+            "e04d10aa974f5ae14299e2083124f7b42e13ef7146863415cf27b42c3680c111"
+        );
+    }
+
+    #[test]
+    fn scripts_are_guessed_by_their_calls() {
+        let call = op::call(0x10, 0x11, 0x12, 0x13);
+        assert_eq!(
+            guess_category(&script(&[op::noop(), op::ret(RegId::ONE)])),
+            ScriptCategory::Transfer
+        );
+        assert_eq!(
+            guess_category(&script(&[op::movi(0x10, 1), op::ret(RegId::ONE)])),
+            ScriptCategory::Custom
+        );
+        assert_eq!(
+            guess_category(&script(&[op::movi(0x10, 1), call, op::ret(RegId::ONE)])),
+            ScriptCategory::ContractCall
+        );
+        assert_eq!(
+            guess_category(&script(&[call, call, op::ret(RegId::ONE)])),
+            ScriptCategory::Multicall
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use fuel_core_client::client::types::{block::Header, TransactionStatus};
+use fuel_core_types::fuel_tx::{
+    field::{InputContract, MintAmount, MintAssetId},
+    ContractId, ContractIdExt, Output, Receipt, Transaction as FuelTransaction,
+};
+use models::token_transfer::{TokenTransfer, TransferType};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::block_read::BlockBodies;
+
+use super::process::{find_sender, tx_inputs, tx_outputs};
+
+struct Transfer {
+    transfer_type: TransferType,
+    output_index: Option<usize>,
+    receipt_index: Option<usize>,
+    asset_id: String,
+    from_address: Option<String>,
+    to_address: Option<String>,
+    amount: u64,
+}
+
+// coins sent back to the sender are change, not a transfer
+fn coin_transfers(outputs: &[Output], sender: &Option<String>) -> Vec<Transfer> {
+    outputs
+        .iter()
+        .enumerate()
+        .filter_map(|(output_index, output)| match output {
+            Output::Coin {
+                to,
+                amount,
+                asset_id,
+            } if Some(to.to_string()) != *sender => Some(Transfer {
+                transfer_type: TransferType::Coin,
+                output_index: Some(output_index),
+                receipt_index: None,
+                asset_id: asset_id.to_string(),
+                from_address: sender.clone(),
+                to_address: Some(to.to_string()),
+                amount: *amount,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn receipt_transfers(receipts: &[Receipt], sender: &Option<String>) -> Vec<Transfer> {
+    // the script itself spends the free coins of the sender
+    let context = |id: &ContractId| {
+        if *id == ContractId::zeroed() {
+            sender.clone()
+        } else {
+            Some(id.to_string())
+        }
+    };
+
+    receipts
+        .iter()
+        .enumerate()
+        .filter_map(|(receipt_index, receipt)| {
+            let (transfer_type, asset_id, from_address, to_address, amount) = match receipt {
+                Receipt::Transfer {
+                    id,
+                    to,
+                    amount,
+                    asset_id,
+                    ..
+                } => (
+                    TransferType::Transfer,
+                    *asset_id,
+                    context(id),
+                    Some(to.to_string()),
+                    *amount,
+                ),
+                Receipt::TransferOut {
+                    id,
+                    to,
+                    amount,
+                    asset_id,
+                    ..
+                } => (
+                    TransferType::TransferOut,
+                    *asset_id,
+                    context(id),
+                    Some(to.to_string()),
+                    *amount,
+                ),
+                Receipt::Mint {
+                    sub_id,
+                    contract_id,
+                    val,
+                    ..
+                } => (
+                    TransferType::Mint,
+                    contract_id.asset_id(sub_id),
+                    None,
+                    Some(contract_id.to_string()),
+                    *val,
+                ),
+                Receipt::Burn {
+                    sub_id,
+                    contract_id,
+                    val,
+                    ..
+                } => (
+                    TransferType::Burn,
+                    contract_id.asset_id(sub_id),
+                    Some(contract_id.to_string()),
+                    None,
+                    *val,
+                ),
+                _ => return None,
+            };
+
+            Some(Transfer {
+                transfer_type,
+                output_index: None,
+                receipt_index: Some(receipt_index),
+                asset_id: asset_id.to_string(),
+                from_address,
+                to_address,
+                amount,
+            })
+        })
+        .collect()
+}
+
+pub fn process_token_transfers(header: &Header, bodies: &BlockBodies) -> Vec<TokenTransfer> {
+    bodies
+        .par_iter()
+        .enumerate()
+        .filter_map(|(tx_index, (tx_hash, tx, receipts))| {
+            let tx = tx.as_ref()?;
+            // a reverted transaction moves nothing but its fee
+            if !matches!(tx.status, TransactionStatus::Success { .. }) {
+                return None;
+            }
+            let sender = find_sender(tx_inputs(&tx.transaction));
+
+            let mut transfers = coin_transfers(tx_outputs(&tx.transaction), &sender);
+            // the block fees minted into the coinbase contract, a block
+            // without fees mints nothing
+            match &tx.transaction {
+                FuelTransaction::Mint(m) if *m.mint_amount() > 0 => transfers.push(Transfer {
+                    transfer_type: TransferType::Mint,
+                    output_index: None,
+                    receipt_index: None,
+                    asset_id: m.mint_asset_id().to_string(),
+                    from_address: None,
+                    to_address: Some(m.input_contract().contract_id.to_string()),
+                    amount: *m.mint_amount(),
+                }),
+                _ => {}
+            }
+            transfers.extend(receipt_transfers(
+                receipts.as_deref().unwrap_or_default(),
+                &sender,
+            ));
+
+            Some(
+                transfers
+                    .into_iter()
+                    .enumerate()
+                    .map(|(transfer_index, t)| TokenTransfer {
+                        transaction_id: tx_hash.to_string(),
+                        transfer_index: transfer_index as i64,
+                        transfer_type: t.transfer_type,
+                        output_index: t.output_index.map(|i| i as i64),
+                        receipt_index: t.receipt_index.map(|i| i as i64),
+                        asset_id: t.asset_id,
+                        from_address: t.from_address,
+                        to_address: t.to_address,
+                        amount: t.amount as i64,
+                        height: header.height as i64,
+                        tx_index: tx_index as i64,
+                        timestamp: header.time.to_unix(),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect()
+}
@@ -0,0 +1,80 @@
+use std::time::SystemTime;
+
+use fuel_core_client::client::types::{block::Header, TransactionStatus};
+use fuel_core_types::fuel_tx::{
+    field::UpgradePurpose as UpgradePurposeField, Transaction as FuelTransaction, UpgradeMetadata,
+    UpgradePurpose,
+};
+use models::consensus_parameters::{ConsensusParameters, StateTransitionBytecode};
+use tracing::warn;
+
+use crate::block_read::BlockBodies;
+
+// an upgrade takes effect from the next block on, every successful upgrade
+// of a kind bumps the version found in the header of its block.
+pub fn process_upgrades(
+    header: &Header,
+    bodies: &BlockBodies,
+) -> (Vec<ConsensusParameters>, Vec<StateTransitionBytecode>) {
+    let mut consensus_parameters = vec![];
+    let mut consensus_parameters_version = header.consensus_parameters_version as i64;
+    let mut state_transition_bytecodes = vec![];
+
+    for (tx_hash, tx, _) in bodies {
+        let Some(tx) = tx else { continue };
+        let FuelTransaction::Upgrade(upgrade) = &tx.transaction else {
+            continue;
+        };
+        if !matches!(tx.status, TransactionStatus::Success { .. }) {
+            continue;
+        }
+
+        match upgrade.upgrade_purpose() {
+            UpgradePurpose::ConsensusParameters { checksum, .. } => {
+                // a skipped upgrade still takes a version
+                consensus_parameters_version += 1;
+                let parameters = match UpgradeMetadata::compute(upgrade) {
+                    Ok(UpgradeMetadata::ConsensusParameters {
+                        consensus_parameters,
+                        ..
+                    }) => match serde_json::to_value(consensus_parameters) {
+                        Ok(parameters) => parameters,
+                        Err(e) => {
+                            warn!(
+                                "can't serialize consensus parameters of upgrade {}: {}",
+                                tx_hash, e
+                            );
+                            continue;
+                        }
+                    },
+                    _ => {
+                        warn!("can't decode consensus parameters of upgrade {}", tx_hash);
+                        continue;
+                    }
+                };
+                consensus_parameters.push(ConsensusParameters {
+                    version: consensus_parameters_version,
+                    parameters,
+                    checksum: Some(checksum.to_string()),
+                    transaction_id: Some(tx_hash.to_string()),
+                    height: Some(header.height as i64),
+                    timestamp: Some(header.time.to_unix()),
+                    inserted_at: SystemTime::now(),
+                });
+            }
+            UpgradePurpose::StateTransition { root } => {
+                state_transition_bytecodes.push(StateTransitionBytecode {
+                    version: (header.state_transition_bytecode_version as usize
+                        + state_transition_bytecodes.len()
+                        + 1) as i64,
+                    bytecode_root: root.to_string(),
+                    transaction_id: tx_hash.to_string(),
+                    height: header.height as i64,
+                    timestamp: header.time.to_unix(),
+                });
+            }
+        }
+    }
+
+    (consensus_parameters, state_transition_bytecodes)
+}
@@ -1,16 +1,18 @@
-use fuel_core_client::client::types::{block::Header, TransactionResponse};
+use fuel_core_client::client::types::{
+    block::{Consensus, Header},
+    TransactionResponse,
+};
 use fuel_core_client::client::FuelClient;
 
-use fuel_core_types::fuel_types::BlockHeight;
 use fuel_core_types::{fuel_tx::Receipt, fuel_types::Bytes32};
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use thiserror::Error;
-use tracing::{error, info, trace};
+use tracing::{info, trace};
 
 pub type BlockBody = (Bytes32, Option<TransactionResponse>, Option<Vec<Receipt>>);
 pub type BlockBodies = Vec<BlockBody>;
-pub type Blocks = Vec<(Header, BlockBodies)>;
-pub type FetchBlockResult = Result<(Header, BlockBodies), BlockReaderError>;
+pub type Blocks = Vec<(Header, Consensus, BlockBodies)>;
+pub type FetchBlockResult = Result<(Header, Consensus, BlockBodies), BlockReaderError>;
 
 pub struct BlockReader {
     batch_fetch_size: u64,
@@ -56,13 +58,10 @@ impl BlockReader {
             let maybe_blocks = futures::future::join_all(fetch_feat).await;
             let blocks = maybe_blocks
                 .into_par_iter()
-                .filter_map(|block| match block {
-                    Ok(block) => Some(block),
-                    Err(_) => None,
-                })
+                .filter_map(|block| block.ok())
                 .collect::<Vec<_>>();
 
-            if blocks.len() == 0 {
+            if blocks.is_empty() {
                 info!("No blocks fetched, maybe the rpc is down");
                 tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                 continue;
@@ -107,11 +106,11 @@ impl BlockReader {
             .iter()
             .map(|tx_hash| async move {
                 let feat = client
-                    .transaction(&tx_hash)
+                    .transaction(tx_hash)
                     .await
                     .map_err(|e| BlockReaderError::ReadFromRpc(e.to_string()));
                 let reseipts = client
-                    .receipts(&tx_hash)
+                    .receipts(tx_hash)
                     .await
                     .map_err(|e| BlockReaderError::ReadFromRpc(e.to_string()));
                 (feat, reseipts, tx_hash)
@@ -121,9 +120,9 @@ impl BlockReader {
 
         let maybe_empty_txs = futures::future::join_all(txs).await;
         for (tx, reseipts, hash) in maybe_empty_txs {
-            transactions.push((hash.clone(), tx?, reseipts?));
+            transactions.push((*hash, tx?, reseipts?));
         }
 
-        Ok((header, transactions))
+        Ok((header, block.consensus, transactions))
     }
 }
@@ -0,0 +1,217 @@
+use std::{
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use bigdecimal::BigDecimal;
+use fuel_core_client::client::FuelClient;
+use fuel_core_types::fuel_tx::{AssetId, ContractId};
+use models::{
+    block::get_indexed_height,
+    contract_abi::{insert_contract_abi, ContractAbi},
+    contract_balance::{get_contract_balance, sample_contract_balances},
+    script::{get_unknown_scripts, insert_known_script, KnownScript, ScriptCategory},
+    PgSqlPool,
+};
+
+use crate::genesis;
+
+const USAGE: &str = "usage:
+  fuelscan                                              index the chain
+  fuelscan import-genesis <snapshot>                    seed the genesis state from a json
+                                                        chain config or snapshot, parquet
+                                                        snapshots are not supported
+  fuelscan register-abi <contract id> <abi json>        decode the errors a contract logs
+  fuelscan label-script <script hash> <category> <name> label a script bytecode
+  fuelscan unknown-scripts [limit]                      list the most used unlabeled scripts
+  fuelscan reconcile-contract-balances [sample]         compare contract balances with the node";
+
+fn arg(args: &[String], i: usize) -> &str {
+    args.get(i)
+        .map(String::as_str)
+        .unwrap_or_else(|| panic!("{}", USAGE))
+}
+
+async fn node_height(client: &FuelClient) -> i64 {
+    client
+        .chain_info()
+        .await
+        .expect("failed to fetch chain_info")
+        .latest_block
+        .header
+        .height as i64
+}
+
+/// Waits up to a minute for every block up to `height` to be indexed,
+/// returns the indexed height reached.
+async fn wait_for_indexed_height(pool: &PgSqlPool, height: i64) -> i64 {
+    let mut indexed = 0;
+    for _ in 0..60 {
+        indexed = get_indexed_height(&mut pool.get().unwrap())
+            .expect("failed to read indexed height")
+            .unwrap_or_default();
+        if indexed >= height {
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    indexed
+}
+
+// the node only answers with the balance at its latest block, which is
+// above the indexed height on a live chain. A balance is compared as of
+// the indexed height, and a difference only counts once the blocks up to
+// the height the node answered at are indexed and none of them changed it.
+async fn reconcile_contract_balances(pool: &PgSqlPool, sample: i64) {
+    let client =
+        FuelClient::from_str(&std::env::var("RPC_URL").unwrap()).expect("failed to create client");
+    let Some(indexed_height) =
+        get_indexed_height(&mut pool.get().unwrap()).expect("failed to read indexed height")
+    else {
+        println!("no block is indexed yet");
+        return;
+    };
+    println!("comparing the balances as of height {}", indexed_height);
+
+    let balances = sample_contract_balances(&mut pool.get().unwrap(), sample)
+        .expect("failed to read contract balances");
+    let mut changed = 0;
+    let mut differing = vec![];
+    for balance in &balances {
+        if balance.block_height > indexed_height {
+            changed += 1;
+            continue;
+        }
+        let contract = ContractId::from_str(&balance.contract_hash).expect("invalid contract id");
+        let asset_id = AssetId::from_str(&balance.asset_id).expect("invalid asset id");
+        let node = client
+            .contract_balance(&contract, Some(&asset_id))
+            .await
+            .expect("failed to fetch contract balance");
+        // the node may have moved on while the balance was fetched
+        let node_height = node_height(&client).await;
+
+        if BigDecimal::from(node) != balance.amount {
+            differing.push((balance, node, node_height));
+        }
+    }
+
+    let highest = differing.iter().map(|(_, _, height)| *height).max();
+    let reached = match highest {
+        Some(highest) => wait_for_indexed_height(pool, highest).await,
+        None => indexed_height,
+    };
+    let mut mismatches = 0;
+    let mut unconfirmed = 0;
+    for (balance, node, node_height) in differing {
+        let current = get_contract_balance(
+            &mut pool.get().unwrap(),
+            &balance.contract_hash,
+            &balance.asset_id,
+        )
+        .expect("failed to read contract balance");
+        if current
+            .filter(|c| c.block_height > indexed_height)
+            .is_some()
+        {
+            changed += 1;
+            continue;
+        }
+
+        let verdict = if node_height > reached {
+            unconfirmed += 1;
+            "not indexed yet"
+        } else {
+            mismatches += 1;
+            "differs"
+        };
+        println!(
+            "{} {}: indexed {} at height {}, node {} at height {}, {}",
+            balance.contract_hash,
+            balance.asset_id,
+            balance.amount,
+            balance.block_height,
+            node,
+            node_height,
+            verdict
+        );
+    }
+
+    println!(
+        "{} of {} balances differ, {} changed above height {}, {} could not be confirmed",
+        mismatches,
+        balances.len(),
+        changed,
+        indexed_height,
+        unconfirmed
+    );
+}
+
+/// Runs the maintenance command named by the first argument, returns false
+/// when there is none and the indexer should start.
+pub async fn run(pool: &PgSqlPool, args: &[String]) -> bool {
+    let Some(command) = args.get(1) else {
+        return false;
+    };
+
+    match command.as_str() {
+        "import-genesis" => {
+            genesis::import_genesis(pool, Path::new(arg(args, 2)))
+                .expect("failed to import genesis state");
+        }
+        "register-abi" => {
+            let abi = std::fs::read_to_string(arg(args, 3)).expect("failed to read abi");
+            insert_contract_abi(
+                &mut pool.get().unwrap(),
+                &ContractAbi {
+                    contract_hash: arg(args, 2).trim_start_matches("0x").to_string(),
+                    abi: serde_json::from_str(&abi).expect("failed to parse abi"),
+                    inserted_at: SystemTime::now(),
+                    updated_at: SystemTime::now(),
+                },
+            )
+            .expect("failed to register abi");
+        }
+        "label-script" => {
+            let category: ScriptCategory =
+                serde_json::from_value(serde_json::Value::String(arg(args, 3).to_string()))
+                    .expect("unknown script category");
+            insert_known_script(
+                &mut pool.get().unwrap(),
+                &KnownScript {
+                    script_hash: arg(args, 2).trim_start_matches("0x").to_string(),
+                    category,
+                    name: arg(args, 4).to_string(),
+                    inserted_at: SystemTime::now(),
+                },
+            )
+            .expect("failed to label script");
+        }
+        "unknown-scripts" => {
+            let limit = args
+                .get(2)
+                .map_or(20, |l| l.parse().expect("limit is a number"));
+            for script in get_unknown_scripts(&mut pool.get().unwrap(), limit)
+                .expect("failed to read scripts")
+            {
+                println!(
+                    "{} used {} times, {} bytes, first in {}",
+                    script.script_hash,
+                    script.usage_count,
+                    script.bytecode_length,
+                    script.first_transaction_id
+                );
+            }
+        }
+        "reconcile-contract-balances" => {
+            let sample = args
+                .get(2)
+                .map_or(100, |s| s.parse().expect("sample is a number"));
+            reconcile_contract_balances(pool, sample).await;
+        }
+        _ => panic!("{}", USAGE),
+    }
+
+    true
+}
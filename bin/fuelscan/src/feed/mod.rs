@@ -0,0 +1,139 @@
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use models::{
+    feed::{get_block_event, BlockEvent},
+    PgSqlPool,
+};
+use thiserror::Error;
+use tokio::{select, sync::broadcast};
+use tracing::{error, trace};
+
+pub mod server;
+
+/// Events kept for subscribers that fall behind, a lagging subscriber
+/// catches up from the database.
+const EVENT_BUFFER: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum FeedError {
+    #[error("failed to get connection from pool: {0}")]
+    GetPgSqlPoolFailed(String),
+    #[error("failed to read block event: {0}")]
+    ReadEvent(String),
+    #[error("block {0} is not indexed")]
+    MissingBlock(i64),
+}
+
+/// What the feed has published so far, shared with the feed server.
+#[derive(Clone)]
+pub struct FeedState {
+    pub db_client: PgSqlPool,
+    pub events: broadcast::Sender<Arc<BlockEvent>>,
+    released: Arc<AtomicI64>,
+}
+
+impl FeedState {
+    pub fn new(db_client: PgSqlPool, start_height: i64) -> Self {
+        Self {
+            db_client,
+            events: broadcast::channel(EVENT_BUFFER).0,
+            released: Arc::new(AtomicI64::new(start_height - 1)),
+        }
+    }
+
+    /// Highest height published, every height below it was published too.
+    pub fn released(&self) -> i64 {
+        self.released.load(Ordering::SeqCst)
+    }
+
+    /// Reads the event of the block at `height`, diesel is blocking so the
+    /// query runs off the reactor.
+    pub async fn load_event(&self, height: i64) -> Result<Option<BlockEvent>, FeedError> {
+        let db_client = self.db_client.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = db_client
+                .get()
+                .map_err(|e| FeedError::GetPgSqlPoolFailed(e.to_string()))?;
+            get_block_event(&mut conn, height).map_err(|e| FeedError::ReadEvent(e.to_string()))
+        })
+        .await
+        .map_err(|e| FeedError::ReadEvent(e.to_string()))?
+    }
+}
+
+/// Publishes the blocks committed by the block handlers. Handlers commit
+/// out of order, heights are held back until every height below them is
+/// committed so subscribers see the chain in order.
+pub struct FeedPublisher {
+    state: FeedState,
+    committed_rx: flume::Receiver<i64>,
+    pending: BTreeSet<i64>,
+    shutdown: broadcast::Sender<()>,
+}
+
+impl Drop for FeedPublisher {
+    fn drop(&mut self) {
+        trace!("FeedPublisher drop");
+    }
+}
+
+impl FeedPublisher {
+    pub fn new(
+        state: FeedState,
+        committed_rx: flume::Receiver<i64>,
+        shutdown: broadcast::Sender<()>,
+    ) -> Self {
+        Self {
+            state,
+            committed_rx,
+            pending: BTreeSet::new(),
+            shutdown,
+        }
+    }
+
+    async fn publish(&self, height: i64) -> Result<(), FeedError> {
+        let event = self
+            .state
+            .load_event(height)
+            .await?
+            .ok_or(FeedError::MissingBlock(height))?;
+
+        // no subscriber is not an error
+        let _ = self.state.events.send(Arc::new(event));
+        self.state.released.store(height, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub async fn start(&mut self) -> Result<(), FeedError> {
+        let mut shutdown = self.shutdown.subscribe();
+
+        loop {
+            select! {
+                Ok(height) = self.committed_rx.recv_async() => {
+                    if height > self.state.released() {
+                        self.pending.insert(height);
+                    }
+                    while self.pending.first() == Some(&(self.state.released() + 1)) {
+                        let next = self.state.released() + 1;
+                        while let Err(e) = self.publish(next).await {
+                            error!("publish block {} failed {}, retrying", next, e.to_string());
+                            tokio::time::sleep(Duration::from_secs(1)).await;
+                        }
+                        self.pending.remove(&next);
+                    }
+                }
+                _ = shutdown.recv() => {
+                    trace!("FeedPublisher shutdown");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::Infallible,
+    net::SocketAddr,
+    time::Duration,
+};
+
+use hyper::{
+    body::{Bytes, Sender},
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use models::{feed::BlockEvent, id::normalize_id};
+use serde_json::json;
+use tokio::{select, sync::broadcast::error::RecvError};
+use tracing::{error, info};
+
+use super::FeedState;
+
+/// How far behind the published height a subscriber may resume.
+const MAX_REPLAY: i64 = 10_000;
+
+/// Comment sent on idle streams so proxies keep the connection open.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Transactions a subscriber wants, every given filter must match.
+struct Filter {
+    address: Option<String>,
+    contract: Option<String>,
+    asset: Option<String>,
+}
+
+fn wanted(filter: &Option<String>, touched: &BTreeSet<String>) -> bool {
+    match filter {
+        Some(id) => touched.contains(id),
+        None => true,
+    }
+}
+
+impl Filter {
+    fn is_empty(&self) -> bool {
+        self.address.is_none() && self.contract.is_none() && self.asset.is_none()
+    }
+
+    /// The server sent event of a block, `None` when no transaction of a
+    /// filtered stream matches.
+    fn render(&self, event: &BlockEvent) -> Option<String> {
+        let data = if self.is_empty() {
+            serde_json::to_string(event)
+        } else {
+            let transactions = event
+                .transactions
+                .iter()
+                .filter(|t| {
+                    wanted(&self.address, &t.addresses)
+                        && wanted(&self.contract, &t.contracts)
+                        && wanted(&self.asset, &t.assets)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if transactions.is_empty() {
+                return None;
+            }
+            serde_json::to_string(&BlockEvent {
+                transactions,
+                ..event.clone()
+            })
+        }
+        .expect("block event serializes");
+
+        Some(format!(
+            "id: {}\nevent: block\ndata: {}\n\n",
+            event.height, data
+        ))
+    }
+}
+
+// the same shape as the errors of the api
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    let code = match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        _ => "internal",
+    };
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Body::from(
+            json!({ "error": { "code": code, "message": message } }).to_string(),
+        ))
+        .unwrap()
+}
+
+async fn send(
+    sender: &mut Sender,
+    filter: &Filter,
+    event: &BlockEvent,
+) -> Result<(), hyper::Error> {
+    match filter.render(event) {
+        Some(frame) => sender.send_data(Bytes::from(frame)).await,
+        None => Ok(()),
+    }
+}
+
+/// Streams every block from `next` on. The subscription is taken before the
+/// replay so no block published meanwhile is missed, blocks the replay
+/// already sent are skipped.
+async fn stream(mut sender: Sender, state: FeedState, filter: Filter, mut next: i64) {
+    let mut events = state.events.subscribe();
+    let mut keep_alive = tokio::time::interval(KEEP_ALIVE);
+
+    loop {
+        // catch up from the database, also after lagging behind the channel
+        while next <= state.released() {
+            match state.load_event(next).await {
+                Ok(Some(event)) => {
+                    if send(&mut sender, &filter, &event).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("feed replay of block {} failed {}", next, e.to_string());
+                    sender.abort();
+                    return;
+                }
+            }
+            next += 1;
+        }
+
+        select! {
+            event = events.recv() => match event {
+                Ok(event) if event.height < next => {}
+                Ok(event) => {
+                    if send(&mut sender, &filter, &event).await.is_err() {
+                        return;
+                    }
+                    next = event.height + 1;
+                }
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return,
+            },
+            _ = keep_alive.tick() => {
+                if sender.send_data(Bytes::from_static(b":\n\n")).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn subscribe(state: &FeedState, req: &Request<Body>) -> Result<Response<Body>, String> {
+    let query = serde_urlencoded::from_str::<HashMap<String, String>>(
+        req.uri().query().unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())?;
+    let hex = |name: &str| {
+        query
+            .get(name)
+            .map(|v| normalize_id(v).map_err(|e| e.to_string()))
+            .transpose()
+    };
+    let filter = Filter {
+        address: hex("address")?,
+        contract: hex("contract")?,
+        asset: hex("asset")?,
+    };
+
+    // a reconnecting EventSource resumes after the last id it received
+    let last_event_id = req
+        .headers()
+        .get("last-event-id")
+        .map(|v| {
+            v.to_str()
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .ok_or_else(|| "Last-Event-ID must be a block height".to_string())
+        })
+        .transpose()?;
+    let from_height = query
+        .get("from_height")
+        .map(|v| {
+            v.parse::<i64>()
+                .map_err(|_| "from_height must be a block height".to_string())
+        })
+        .transpose()?;
+
+    let released = state.released();
+    let next = match (last_event_id, from_height) {
+        (Some(id), _) => id + 1,
+        (None, Some(height)) => height,
+        (None, None) => released + 1,
+    };
+    if next < 0 || released - next >= MAX_REPLAY {
+        return Err(format!(
+            "resuming is limited to the last {} blocks, the feed is at {}",
+            MAX_REPLAY, released
+        ));
+    }
+
+    let (sender, body) = Body::channel();
+    tokio::spawn(stream(sender, state.clone(), filter, next));
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .unwrap())
+}
+
+async fn handle(state: FeedState, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if req.uri().path() != "/feed" {
+        return Ok(error_response(
+            StatusCode::NOT_FOUND,
+            &format!("route {} not found", req.uri().path()),
+        ));
+    }
+    if req.method() != Method::GET {
+        return Ok(error_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "method not allowed",
+        ));
+    }
+    Ok(subscribe(&state, &req).unwrap_or_else(|e| error_response(StatusCode::BAD_REQUEST, &e)))
+}
+
+/// Serves the live feed as server sent events on `GET /feed`.
+pub async fn serve(addr: SocketAddr, state: FeedState) -> Result<(), hyper::Error> {
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+    });
+
+    info!("feed listening on {}", addr);
+    Server::bind(&addr).serve(make_service).await
+}
+
+#[cfg(test)]
+mod tests {
+    use models::{feed::TransactionEvent, transaction::TxStatus};
+
+    use super::*;
+
+    fn transaction(id: &str, address: &str, contract: Option<&str>) -> TransactionEvent {
+        TransactionEvent {
+            id: id.to_string(),
+            tx_index: 0,
+            status: TxStatus::Success,
+            sender: Some(address.to_string()),
+            fee: 1,
+            addresses: BTreeSet::from([address.to_string()]),
+            contracts: contract.into_iter().map(str::to_string).collect(),
+            assets: BTreeSet::new(),
+        }
+    }
+
+    fn event() -> BlockEvent {
+        BlockEvent {
+            height: 7,
+            id: "b".to_string(),
+            timestamp: 0,
+            transaction_count: 2,
+            transactions: vec![
+                transaction("t1", "a1", Some("c1")),
+                transaction("t2", "a2", None),
+            ],
+        }
+    }
+
+    fn filter(address: Option<&str>, contract: Option<&str>) -> Filter {
+        Filter {
+            address: address.map(str::to_string),
+            contract: contract.map(str::to_string),
+            asset: None,
+        }
+    }
+
+    fn rendered_ids(frame: &str) -> Vec<String> {
+        let data = frame
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .unwrap();
+        serde_json::from_str::<BlockEvent>(data)
+            .unwrap()
+            .transactions
+            .into_iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    #[test]
+    fn an_unfiltered_stream_gets_every_transaction() {
+        let frame = filter(None, None).render(&event()).unwrap();
+        assert!(frame.starts_with("id: 7\nevent: block\n"));
+        assert!(frame.ends_with("\n\n"));
+        assert_eq!(rendered_ids(&frame), ["t1", "t2"]);
+    }
+
+    #[test]
+    fn every_filter_must_match() {
+        let frame = filter(Some("a2"), None).render(&event()).unwrap();
+        assert_eq!(rendered_ids(&frame), ["t2"]);
+        let frame = filter(Some("a1"), Some("c1")).render(&event()).unwrap();
+        assert_eq!(rendered_ids(&frame), ["t1"]);
+        assert!(filter(Some("a2"), Some("c1")).render(&event()).is_none());
+    }
+
+    #[tokio::test]
+    async fn errors_carry_the_api_codes() {
+        let response = error_response(StatusCode::BAD_REQUEST, "bad");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({ "error": { "code": "bad_request", "message": "bad" } })
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bigdecimal::BigDecimal;
+use diesel::Connection;
+use fuel_core_types::{
+    fuel_tx::{Address, AssetId, ContractId, UtxoId},
+    fuel_types::Bytes32,
+};
+use models::{
+    account::{batch_insert_accounts, Account, AccountType},
+    asset_holder::{batch_insert_asset_holder_changes, AssetHolderChange},
+    assets::{batch_insert_assets, AssetStatus, Assets},
+    balance::{batch_insert_address_coin_balances, AddressCoinBalance},
+    contract::{batch_insert_contracts, Contract},
+    contract_balance::{batch_insert_contract_balances, ContractBalance},
+    PgSqlPool,
+};
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use tracing::info;
+
+use crate::block_handle::asset_holders::process_asset_holders;
+
+// keeps every batch far below the bind parameter limit of postgres
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Debug, Error)]
+pub enum GenesisImportError {
+    #[error("failed to read snapshot: {0}")]
+    ReadSnapshot(String),
+    #[error("failed to parse snapshot: {0}")]
+    ParseSnapshot(String),
+    #[error("unsupported snapshot encoding: {0}, only Json snapshots can be imported")]
+    UnsupportedEncoding(String),
+    #[error("failed to get connection from pool: {0}")]
+    GetPgSqlPoolFailed(String),
+    #[error("failed to insert genesis state: {0}")]
+    InsertDb(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinConfig {
+    tx_id: Bytes32,
+    output_index: u16,
+    owner: Address,
+    amount: u64,
+    asset_id: AssetId,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractStateConfig {
+    key: Bytes32,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractBalanceConfig {
+    asset_id: AssetId,
+    amount: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractConfig {
+    contract_id: ContractId,
+    code: String,
+    #[serde(default)]
+    tx_id: Option<Bytes32>,
+    #[serde(default)]
+    states: Vec<ContractStateConfig>,
+    #[serde(default)]
+    balances: Vec<ContractBalanceConfig>,
+}
+
+/// The part of a fuel-core `StateConfig` that ends up in the explorer.
+#[derive(Debug, Default, Deserialize)]
+struct StateConfig {
+    #[serde(default)]
+    coins: Vec<CoinConfig>,
+    #[serde(default)]
+    contracts: Vec<ContractConfig>,
+    #[serde(default)]
+    block_height: u32,
+}
+
+fn read_json(path: &Path) -> Result<serde_json::Value, GenesisImportError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| GenesisImportError::ReadSnapshot(format!("{}: {}", path.display(), e)))?;
+    serde_json::from_str(&content)
+        .map_err(|e| GenesisImportError::ParseSnapshot(format!("{}: {}", path.display(), e)))
+}
+
+// a snapshot directory names its state file in `metadata.json`, older
+// directories and single files hold the state themselves. Only the Json
+// table encoding is read, a Parquet snapshot is refused.
+fn state_config_path(path: &Path) -> Result<PathBuf, GenesisImportError> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+
+    let metadata = path.join("metadata.json");
+    if !metadata.exists() {
+        return Ok(path.join("state_config.json"));
+    }
+
+    let metadata = read_json(&metadata)?;
+    let encoding = &metadata["table_encoding"];
+    if let Some(filepath) = encoding["Json"]["filepath"].as_str() {
+        return Ok(path.join(filepath));
+    }
+
+    Err(GenesisImportError::UnsupportedEncoding(
+        encoding.to_string(),
+    ))
+}
+
+fn load_state_config(path: &Path) -> Result<StateConfig, GenesisImportError> {
+    let mut state = read_json(&state_config_path(path)?)?;
+    // the combined chain config of older releases keeps the state inline
+    if let Some(initial_state) = state.get_mut("initial_state") {
+        state = initial_state.take();
+    }
+    if state.is_null() {
+        return Ok(StateConfig::default());
+    }
+
+    serde_json::from_value(state).map_err(|e| GenesisImportError::ParseSnapshot(e.to_string()))
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, GenesisImportError> {
+    hex::decode(s.trim_start_matches("0x"))
+        .map_err(|e| GenesisImportError::ParseSnapshot(e.to_string()))
+}
+
+fn genesis_account(account_hash: String, account_type: AccountType, height: i64) -> Account {
+    Account {
+        account_hash,
+        account_type,
+        first_seen_height: Some(height),
+        last_seen_height: Some(height),
+        ..Default::default()
+    }
+}
+
+struct GenesisState {
+    assets: Vec<Assets>,
+    asset_holders: Vec<AssetHolderChange>,
+    balances: Vec<AddressCoinBalance>,
+    contracts: Vec<Contract>,
+    contract_balances: Vec<ContractBalance>,
+    accounts: Vec<Account>,
+}
+
+fn process_state(state: StateConfig) -> Result<GenesisState, GenesisImportError> {
+    let height = state.block_height as i64;
+    let mut accounts = HashMap::new();
+
+    let assets = state
+        .coins
+        .iter()
+        .map(|coin| {
+            accounts.insert(
+                coin.owner.to_string(),
+                genesis_account(coin.owner.to_string(), AccountType::Account, height),
+            );
+            Assets {
+                assets_id: coin.asset_id.to_string(),
+                assets_utxo_id: format!("{:x}", UtxoId::new(coin.tx_id, coin.output_index)),
+                assets_owner: coin.owner.to_string(),
+                amount: coin.amount as i64,
+                block_height: height,
+                create_height: height,
+                create_tx_hash: coin.tx_id.to_string(),
+                asset_status: AssetStatus::Alive,
+                ..Default::default()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let asset_holders = process_asset_holders(height, &[], &assets);
+
+    let mut balances = assets
+        .iter()
+        .map(|a| (a.assets_owner.clone(), a.assets_id.clone()))
+        .collect::<Vec<_>>();
+    balances.sort();
+    balances.dedup();
+    let balances = balances
+        .into_iter()
+        .map(|(address_hash, asset_hash)| AddressCoinBalance {
+            address_hash,
+            asset_hash,
+            block_number: height,
+            inserted_at: SystemTime::now(),
+            updated_at: SystemTime::now(),
+        })
+        .collect();
+
+    let mut contracts = vec![];
+    let mut contract_balances = vec![];
+    for contract in state.contracts {
+        let contract_hash = contract.contract_id.to_string();
+        let bytecode = decode_hex(&contract.code)?;
+        let storage_slots = contract
+            .states
+            .iter()
+            .map(|s| json!({ "key": s.key.to_string(), "value": s.value.trim_start_matches("0x") }))
+            .collect::<Vec<_>>();
+
+        // nobody sent a genesis contract and it has no block time
+        contracts.push(Contract {
+            contract_hash: contract_hash.clone(),
+            transaction_id: contract.tx_id.map(|t| t.to_string()).unwrap_or_default(),
+            sender: "".to_string(),
+            bytecode: hex::encode(&bytecode),
+            bytecoin_length: bytecode.len() as i64,
+            storage_slots: Some(serde_json::Value::Array(storage_slots)),
+            timestamp: 0,
+        });
+        contract_balances.extend(contract.balances.iter().map(|b| ContractBalance {
+            contract_hash: contract_hash.clone(),
+            asset_id: b.asset_id.to_string(),
+            amount: BigDecimal::from(b.amount),
+            block_height: height,
+            inserted_at: SystemTime::now(),
+            updated_at: SystemTime::now(),
+        }));
+
+        let mut account = genesis_account(contract_hash.clone(), AccountType::Contract, height);
+        account.account_code = Some(hex::encode(&bytecode));
+        accounts.insert(contract_hash, account);
+    }
+
+    Ok(GenesisState {
+        assets,
+        asset_holders,
+        balances,
+        contracts,
+        contract_balances,
+        accounts: accounts.into_values().collect(),
+    })
+}
+
+/// Seeds `assets`, `asset_holder_changes`, `address_coin_balances`,
+/// `smart_contracts`, `contract_balances` and `accounts` with the state a
+/// chain starts from, so the first spend of a genesis coin or call of a
+/// genesis contract refers to a known row. Meant to be run once before the
+/// first block is indexed.
+pub fn import_genesis(db_client: &PgSqlPool, path: &Path) -> Result<(), GenesisImportError> {
+    let state = process_state(load_state_config(path)?)?;
+
+    let mut conn = db_client
+        .get()
+        .map_err(|e| GenesisImportError::GetPgSqlPoolFailed(e.to_string()))?;
+
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        for chunk in state.assets.chunks(INSERT_CHUNK_SIZE) {
+            batch_insert_assets(conn, &chunk.to_vec())?;
+        }
+        for chunk in state.asset_holders.chunks(INSERT_CHUNK_SIZE) {
+            batch_insert_asset_holder_changes(conn, &chunk.to_vec())?;
+        }
+        for chunk in state.balances.chunks(INSERT_CHUNK_SIZE) {
+            batch_insert_address_coin_balances(conn, &chunk.to_vec())?;
+        }
+        for chunk in state.contracts.chunks(INSERT_CHUNK_SIZE) {
+            batch_insert_contracts(conn, &chunk.to_vec())?;
+        }
+        for chunk in state.contract_balances.chunks(INSERT_CHUNK_SIZE) {
+            batch_insert_contract_balances(conn, &chunk.to_vec())?;
+        }
+        for chunk in state.accounts.chunks(INSERT_CHUNK_SIZE) {
+            batch_insert_accounts(conn, &chunk.to_vec())?;
+        }
+        Ok(())
+    })
+    .map_err(|e| GenesisImportError::InsertDb(e.to_string()))?;
+
+    info!(
+        "imported genesis state: {} coins, {} contracts, {} contract balances, {} accounts",
+        state.assets.len(),
+        state.contracts.len(),
+        state.contract_balances.len(),
+        state.accounts.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use models::{
+    asset_holder::{get_changed_assets, refresh_asset_holders},
+    block::get_indexed_height,
+    PgSqlPool,
+};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::{select, sync::broadcast};
+use tracing::{error, info, trace};
+
+#[derive(Debug, Error)]
+pub enum HolderRefresherError {
+    #[error("failed to get connection from pool: {0}")]
+    GetPgSqlPoolFailed(String),
+    #[error("failed to read indexed height: {0}")]
+    IndexedHeight(String),
+    #[error("failed to read changed assets: {0}")]
+    ChangedAssets(String),
+    #[error("failed to refresh holders of {0}: {1}")]
+    RefreshHolders(String, String),
+}
+
+/// Applies the balance changes the block handlers queued, up to the height
+/// below which every block is indexed, then ranks the holders of the assets
+/// that changed and records their distribution at that height.
+pub struct HolderRefresher {
+    db_client: PgSqlPool,
+    batch_refresh_size: i64,
+    interval: Duration,
+    shutdown: broadcast::Sender<()>,
+}
+
+impl Drop for HolderRefresher {
+    fn drop(&mut self) {
+        trace!("HolderRefresher drop");
+    }
+}
+
+impl HolderRefresher {
+    pub fn new(
+        db_client: PgSqlPool,
+        batch_refresh_size: i64,
+        interval: Duration,
+        shutdown: broadcast::Sender<()>,
+    ) -> Self {
+        Self {
+            db_client,
+            batch_refresh_size,
+            interval,
+            shutdown,
+        }
+    }
+
+    fn refresh_holders(&self) -> Result<usize, HolderRefresherError> {
+        let mut conn = self
+            .db_client
+            .get()
+            .map_err(|e| HolderRefresherError::GetPgSqlPoolFailed(e.to_string()))?;
+
+        // the balances are only exact once every earlier block is in
+        let Some(height) = get_indexed_height(&mut conn)
+            .map_err(|e| HolderRefresherError::IndexedHeight(e.to_string()))?
+        else {
+            return Ok(0);
+        };
+
+        let assets = get_changed_assets(&mut conn, height, self.batch_refresh_size)
+            .map_err(|e| HolderRefresherError::ChangedAssets(e.to_string()))?;
+        for asset_id in &assets {
+            refresh_asset_holders(&mut conn, asset_id, height).map_err(|e| {
+                HolderRefresherError::RefreshHolders(asset_id.clone(), e.to_string())
+            })?;
+        }
+        Ok(assets.len())
+    }
+
+    pub async fn start(&mut self) -> Result<(), HolderRefresherError> {
+        let mut shutdown = self.shutdown.subscribe();
+
+        loop {
+            let wait = match self.refresh_holders() {
+                Ok(n) if n as i64 == self.batch_refresh_size => {
+                    info!("Refreshed holders of {} assets", n);
+                    Duration::ZERO
+                }
+                Ok(n) => {
+                    if n > 0 {
+                        info!("Refreshed holders of {} assets", n);
+                    }
+                    self.interval
+                }
+                Err(e) => {
+                    error!("refresh_holders failed {}, retrying", e.to_string());
+                    Duration::from_secs(1)
+                }
+            };
+
+            select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.recv() => {
+                    trace!("HolderRefresher shutdown");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,34 @@
+use balance_fetch::BalanceFetcher;
 use block_read::{BlockReader, Blocks};
+use feed::{FeedPublisher, FeedState};
+use holder_refresh::HolderRefresher;
+use message_proof::{chain_state::ChainState, MessageProofFetcher};
+use message_relay::MessageRelayWatcher;
+use stats_fold::StatsFolder;
 
 use diesel::{r2d2::ConnectionManager, PgConnection};
 use flume::unbounded;
 use fuel_core_client::client::FuelClient;
-use models::block::get_last_block_height;
-use std::str::FromStr;
+use models::{
+    block::get_indexed_height,
+    consensus_parameters::{batch_insert_consensus_parameters, ConsensusParameters},
+};
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 use tracing_subscriber::FmtSubscriber;
 
+mod balance_fetch;
 mod block_handle;
 mod block_read;
+mod commands;
+mod feed;
+mod genesis;
+mod holder_refresh;
+mod message_proof;
+mod message_relay;
+mod stats_fold;
 
 #[tokio::main]
 async fn main() {
@@ -33,35 +53,96 @@ async fn main() {
         .build(manager)
         .expect("Failed to create pool");
 
+    if commands::run(&pool, &std::env::args().collect::<Vec<_>>()).await {
+        return;
+    }
+
     let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
 
     let (block_handler_tx, block_handler_rx) = unbounded::<Blocks>();
     let rpc = std::env::var("RPC_URL").unwrap();
     let client = FuelClient::from_str(&rpc).expect("failed to create client");
 
-    tracing::info!(
-        "{}",
-        client
-            .chain_info()
-            .await
-            .expect("failed to fetch chain_info")
-            .latest_block
-            .header
-            .height
-    );
+    let chain_info = client
+        .chain_info()
+        .await
+        .expect("failed to fetch chain_info");
+    tracing::info!("{}", chain_info.latest_block.header.height);
+    tracing::info!("chain_id: {}", chain_info.consensus_parameters.chain_id());
+    let base_asset_id = *chain_info.consensus_parameters.base_asset_id();
 
-    tracing::info!(
-        "chain_id: {}",
-        client
-            .chain_info()
-            .await
-            .expect("failed to fetch chain_info")
-            .consensus_parameters
-            .chain_id()
+    // the version in force now may predate every upgrade we will index
+    match serde_json::to_value(&chain_info.consensus_parameters) {
+        Ok(parameters) => {
+            batch_insert_consensus_parameters(
+                &mut pool.get().unwrap(),
+                &vec![ConsensusParameters {
+                    version: chain_info.latest_block.header.consensus_parameters_version as i64,
+                    parameters,
+                    checksum: None,
+                    transaction_id: None,
+                    height: None,
+                    timestamp: None,
+                    inserted_at: SystemTime::now(),
+                }],
+            )
+            .expect("failed to insert consensus parameters");
+        }
+        Err(e) => tracing::warn!(
+            "can't serialize the consensus parameters of the node: {}",
+            e
+        ),
+    }
+
+    // proofs can only be relayed against a commit block finalized on L1,
+    // without one they are fetched against the latest block of the node
+    let chain_state = match (
+        std::env::var("L1_RPC_URL"),
+        std::env::var("FUEL_CHAIN_STATE_ADDRESS"),
+    ) {
+        (Ok(l1_rpc), Ok(chain_state)) => Some(
+            ChainState::connect(&l1_rpc, &chain_state)
+                .await
+                .expect("failed to read FuelChainState"),
+        ),
+        _ => {
+            tracing::info!("no L1 configured, withdrawal proofs are not finalized");
+            None
+        }
+    };
+    let mut message_proof = MessageProofFetcher::new(
+        pool.clone(),
+        client.clone(),
+        chain_state,
+        100,
+        shutdown_tx.clone(),
     );
+    tokio::spawn(async move {
+        match message_proof.start().await {
+            Ok(_) => {}
+            Err(e) => {
+                panic!("{}", e);
+            }
+        }
+    });
+
+    let mut message_relay =
+        MessageRelayWatcher::new(pool.clone(), client.clone(), 100, shutdown_tx.clone());
+    tokio::spawn(async move {
+        match message_relay.start().await {
+            Ok(_) => {}
+            Err(e) => {
+                panic!("{}", e);
+            }
+        }
+    });
 
     let mut block_read = BlockReader::new(50, client, block_handler_tx);
-    let height = get_last_block_height(&mut pool.get().unwrap()) as u64;
+    // blocks are committed out of order, resume after the last gap free height
+    let height = get_indexed_height(&mut pool.get().unwrap())
+        .expect("failed to read indexed height")
+        .map(|h| h + 1)
+        .unwrap_or_default() as u64;
 
     tokio::spawn(async move {
         match block_read.start(height).await {
@@ -72,7 +153,71 @@ async fn main() {
         }
     });
 
-    let block_handle = block_handle::BlockHandler::new(pool, block_handler_rx, shutdown_tx.clone());
+    let mut balance_fetch = BalanceFetcher::new(pool.clone(), 1000, shutdown_tx.clone());
+    tokio::spawn(async move {
+        match balance_fetch.start().await {
+            Ok(_) => {}
+            Err(e) => {
+                panic!("{}", e);
+            }
+        }
+    });
+
+    let mut holder_refresh = HolderRefresher::new(
+        pool.clone(),
+        100,
+        Duration::from_secs(60),
+        shutdown_tx.clone(),
+    );
+    tokio::spawn(async move {
+        match holder_refresh.start().await {
+            Ok(_) => {}
+            Err(e) => {
+                panic!("{}", e);
+            }
+        }
+    });
+
+    let mut stats_fold = StatsFolder::new(pool.clone(), 1000, shutdown_tx.clone());
+    tokio::spawn(async move {
+        match stats_fold.start().await {
+            Ok(_) => {}
+            Err(e) => {
+                panic!("{}", e);
+            }
+        }
+    });
+
+    // the live feed is served only when an address is configured
+    let committed_tx = std::env::var("FEED_ADDR").ok().map(|addr| {
+        let addr = addr.parse().expect("invalid FEED_ADDR");
+        let (committed_tx, committed_rx) = unbounded::<i64>();
+        let state = FeedState::new(pool.clone(), height as i64);
+
+        let mut feed_publish = FeedPublisher::new(state.clone(), committed_rx, shutdown_tx.clone());
+        tokio::spawn(async move {
+            match feed_publish.start().await {
+                Ok(_) => {}
+                Err(e) => {
+                    panic!("{}", e);
+                }
+            }
+        });
+        tokio::spawn(async move {
+            if let Err(e) = feed::server::serve(addr, state).await {
+                panic!("{}", e);
+            }
+        });
+        committed_tx
+    });
+
+    let block_handle = block_handle::BlockHandler::new(
+        pool,
+        base_asset_id,
+        block_handler_rx,
+        shutdown_tx.clone(),
+        committed_tx,
+    );
     for _ in 0..num_cpus::get() {
         let mut block_handle = block_handle.clone();
         tokio::spawn(async move {
@@ -0,0 +1,124 @@
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+
+use super::MessageProofFetcherError;
+
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn uint256(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// The `FuelChainState` contract on L1, where the block committer posts the
+/// last block of every commit interval. A withdrawal can only be relayed
+/// with a proof against a commit block the contract holds as finalized.
+pub struct ChainState {
+    http: reqwest::Client,
+    rpc_url: String,
+    contract: String,
+    blocks_per_commit: u64,
+    commit_slots: u64,
+}
+
+impl ChainState {
+    /// Reads the commit interval and the number of commit slots of the
+    /// contract at `contract` through the L1 node at `rpc_url`.
+    pub async fn connect(rpc_url: &str, contract: &str) -> Result<Self, MessageProofFetcherError> {
+        let mut chain_state = Self {
+            http: reqwest::Client::new(),
+            rpc_url: rpc_url.to_string(),
+            contract: contract.to_string(),
+            blocks_per_commit: 0,
+            commit_slots: 0,
+        };
+        chain_state.blocks_per_commit =
+            chain_state.constant("BLOCKS_PER_COMMIT_INTERVAL()").await?;
+        chain_state.commit_slots = chain_state.constant("NUM_COMMIT_SLOTS()").await?;
+        if chain_state.blocks_per_commit == 0 {
+            return Err(MessageProofFetcherError::ReadFromL1(
+                "commit interval of 0 blocks".to_string(),
+            ));
+        }
+        Ok(chain_state)
+    }
+
+    /// How many commits the contract keeps before reusing their slot.
+    pub fn commit_slots(&self) -> u64 {
+        self.commit_slots
+    }
+
+    /// The L2 height committed by commit `commit`.
+    pub fn commit_block_height(&self, commit: u64) -> u64 {
+        (commit + 1) * self.blocks_per_commit - 1
+    }
+
+    /// The newest commit at or below the L2 block at `height`.
+    pub fn commit_at(&self, height: u64) -> Option<u64> {
+        ((height + 1) / self.blocks_per_commit).checked_sub(1)
+    }
+
+    // the returned data, none when the call reverted
+    async fn call(&self, data: &[u8]) -> Result<Option<Vec<u8>>, MessageProofFetcherError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [{ "to": self.contract, "data": format!("0x{}", hex::encode(data)) }, "latest"],
+        });
+        let response = self
+            .http
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| MessageProofFetcherError::ReadFromL1(e.to_string()))?
+            .json::<Value>()
+            .await
+            .map_err(|e| MessageProofFetcherError::ReadFromL1(e.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            let message = error["message"].as_str().unwrap_or_default();
+            if message.contains("revert") {
+                return Ok(None);
+            }
+            return Err(MessageProofFetcherError::ReadFromL1(error.to_string()));
+        }
+        let result = response["result"].as_str().unwrap_or_default();
+        hex::decode(result.trim_start_matches("0x"))
+            .map(Some)
+            .map_err(|e| MessageProofFetcherError::ReadFromL1(e.to_string()))
+    }
+
+    async fn constant(&self, signature: &str) -> Result<u64, MessageProofFetcherError> {
+        let word = self
+            .call(&selector(signature))
+            .await?
+            .filter(|word| word.len() == 32 && word[..24].iter().all(|b| *b == 0))
+            .ok_or_else(|| {
+                MessageProofFetcherError::ReadFromL1(format!("invalid result of {}", signature))
+            })?;
+        Ok(u64::from_be_bytes(word[24..].try_into().expect("8 bytes")))
+    }
+
+    /// Whether the block `block_id` at `height` is committed and past its
+    /// finalization period. The contract reverts for a block it does not hold.
+    pub async fn finalized(
+        &self,
+        block_id: &impl AsRef<[u8]>,
+        height: u64,
+    ) -> Result<bool, MessageProofFetcherError> {
+        let mut data = selector("finalized(bytes32,uint256)").to_vec();
+        data.extend_from_slice(block_id.as_ref());
+        data.extend_from_slice(&uint256(height));
+        Ok(self
+            .call(&data)
+            .await?
+            .is_some_and(|word| word.last() == Some(&1)))
+    }
+}
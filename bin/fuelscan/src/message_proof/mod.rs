@@ -0,0 +1,278 @@
+use std::{str::FromStr, time::Duration};
+
+use chain_state::ChainState;
+
+use fuel_core_client::client::{
+    types::{block::Header, MerkleProof, MessageProof},
+    FuelClient,
+};
+use fuel_core_types::fuel_types::{Bytes32, Nonce};
+use models::{
+    message::{get_unproven_withdrawals, update_message_proof},
+    PgSqlPool,
+};
+use serde_json::json;
+use thiserror::Error;
+use tokio::{select, sync::broadcast};
+use tracing::{error, info, trace};
+
+pub mod chain_state;
+
+#[derive(Debug, Error)]
+pub enum MessageProofFetcherError {
+    #[error("failed to get connection from pool: {0}")]
+    GetPgSqlPoolFailed(String),
+    #[error("Read message proof from rpc failed: {0}")]
+    ReadFromRpc(String),
+    #[error("failed to read the chain state from L1: {0}")]
+    ReadFromL1(String),
+    #[error("failed to read unproven withdrawals: {0}")]
+    ReadWithdrawals(String),
+    #[error("failed to store message proof: {0}")]
+    UpdateProof(String),
+}
+
+fn merkle_proof_json(proof: &MerkleProof) -> serde_json::Value {
+    json!({
+        "proof_set": proof.proof_set.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+        "proof_index": proof.proof_index,
+    })
+}
+
+fn header_json(header: &Header) -> serde_json::Value {
+    json!({
+        "id": header.id.to_string(),
+        "da_height": header.da_height,
+        "consensus_parameters_version": header.consensus_parameters_version,
+        "state_transition_bytecode_version": header.state_transition_bytecode_version,
+        "transactions_count": header.transactions_count,
+        "message_receipt_count": header.message_receipt_count,
+        "transactions_root": header.transactions_root.to_string(),
+        "message_outbox_root": header.message_outbox_root.to_string(),
+        "event_inbox_root": header.event_inbox_root.to_string(),
+        "height": header.height,
+        "prev_root": header.prev_root.to_string(),
+        "time": header.time.0,
+        "application_hash": header.application_hash.to_string(),
+    })
+}
+
+// same shape as the `messageProof` query of fuel-core, so a bridge client can
+// use it as is.
+fn message_proof_json(proof: &MessageProof) -> serde_json::Value {
+    json!({
+        "message_proof": merkle_proof_json(&proof.message_proof),
+        "block_proof": merkle_proof_json(&proof.block_proof),
+        "message_block_header": header_json(&proof.message_block_header),
+        "commit_block_header": header_json(&proof.commit_block_header),
+        "sender": proof.sender.to_string(),
+        "recipient": proof.recipient.to_string(),
+        "nonce": proof.nonce.to_string(),
+        "amount": proof.amount,
+        "data": hex::encode(&proof.data),
+    })
+}
+
+/// Fetches the proofs of indexed withdrawal messages from the node against
+/// the newest commit block finalized on L1, and caches them next to the
+/// message rows. A proof is fetched again once the contract reuses the slot
+/// of its commit block, it could no longer be relayed.
+///
+/// Without a `chain_state` the proofs are fetched against the latest block
+/// of the node and kept as unfinalized, they are fetched again once an L1
+/// is configured.
+pub struct MessageProofFetcher {
+    db_client: PgSqlPool,
+    client: FuelClient,
+    chain_state: Option<ChainState>,
+    finalized_commit: Option<u64>,
+    batch_fetch_size: i64,
+    shutdown: broadcast::Sender<()>,
+}
+
+impl Drop for MessageProofFetcher {
+    fn drop(&mut self) {
+        trace!("MessageProofFetcher drop");
+    }
+}
+
+impl MessageProofFetcher {
+    pub fn new(
+        db_client: PgSqlPool,
+        client: FuelClient,
+        chain_state: Option<ChainState>,
+        batch_fetch_size: i64,
+        shutdown: broadcast::Sender<()>,
+    ) -> Self {
+        Self {
+            db_client,
+            client,
+            chain_state,
+            finalized_commit: None,
+            batch_fetch_size,
+            shutdown,
+        }
+    }
+
+    async fn latest_height(&self) -> Result<u32, MessageProofFetcherError> {
+        Ok(self
+            .client
+            .chain_info()
+            .await
+            .map_err(|e| MessageProofFetcherError::ReadFromRpc(e.to_string()))?
+            .latest_block
+            .header
+            .height)
+    }
+
+    async fn is_finalized(
+        &self,
+        chain_state: &ChainState,
+        commit: u64,
+    ) -> Result<bool, MessageProofFetcherError> {
+        let height = chain_state.commit_block_height(commit);
+        let Some(block) = self
+            .client
+            .block_by_height((height as u32).into())
+            .await
+            .map_err(|e| MessageProofFetcherError::ReadFromRpc(e.to_string()))?
+        else {
+            return Ok(false);
+        };
+        chain_state.finalized(&block.id, height).await
+    }
+
+    // walks up from the last finalized commit, or down from the newest one
+    // the first time, the contract only holds the last slots.
+    async fn finalized_commit(
+        &mut self,
+        chain_state: &ChainState,
+    ) -> Result<Option<u64>, MessageProofFetcherError> {
+        let latest = self.latest_height().await?;
+        let Some(newest) = chain_state.commit_at(latest as u64) else {
+            return Ok(None);
+        };
+
+        match self.finalized_commit {
+            Some(mut commit) => {
+                while commit < newest && self.is_finalized(chain_state, commit + 1).await? {
+                    commit += 1;
+                }
+                self.finalized_commit = Some(commit);
+            }
+            None => {
+                let oldest = newest.saturating_sub(chain_state.commit_slots());
+                for commit in (oldest..=newest).rev() {
+                    if self.is_finalized(chain_state, commit).await? {
+                        self.finalized_commit = Some(commit);
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(self.finalized_commit)
+    }
+
+    /// The height of the block to prove against, with the commit height
+    /// below which a cached proof is stale when it is finalized on L1.
+    async fn commit_block(
+        &mut self,
+    ) -> Result<Option<(u32, Option<i64>)>, MessageProofFetcherError> {
+        // taken for the walk, which updates `finalized_commit`
+        let Some(chain_state) = self.chain_state.take() else {
+            return Ok(Some((self.latest_height().await?, None)));
+        };
+        let commit_block = self.finalized_commit(&chain_state).await.map(|commit| {
+            commit.map(|commit| {
+                // the slot of an older commit block may have been reused already
+                let stale_below = chain_state.commit_block_height(
+                    commit.saturating_sub(chain_state.commit_slots().saturating_sub(1)),
+                );
+                (
+                    chain_state.commit_block_height(commit) as u32,
+                    Some(stale_below as i64),
+                )
+            })
+        });
+        self.chain_state = Some(chain_state);
+        commit_block
+    }
+
+    async fn fetch_proofs(&mut self) -> Result<usize, MessageProofFetcherError> {
+        let Some((commit_height, stale_below)) = self.commit_block().await? else {
+            trace!("no finalized commit block yet");
+            return Ok(0);
+        };
+        let finalized = stale_below.is_some();
+
+        let mut conn = self
+            .db_client
+            .get()
+            .map_err(|e| MessageProofFetcherError::GetPgSqlPoolFailed(e.to_string()))?;
+
+        // the commit block must be above the block holding the message
+        let withdrawals = get_unproven_withdrawals(
+            &mut conn,
+            commit_height as i64,
+            stale_below,
+            self.batch_fetch_size,
+        )
+        .map_err(|e| MessageProofFetcherError::ReadWithdrawals(e.to_string()))?;
+
+        let mut fetched = 0;
+        for (transaction_id, nonce) in withdrawals {
+            let tx_id = Bytes32::from_str(&transaction_id)
+                .map_err(|e| MessageProofFetcherError::ReadWithdrawals(e.to_string()))?;
+            let message_nonce = Nonce::from_str(&nonce)
+                .map_err(|e| MessageProofFetcherError::ReadWithdrawals(e.to_string()))?;
+
+            let Some(proof) = self
+                .client
+                .message_proof(&tx_id, &message_nonce, None, Some(commit_height.into()))
+                .await
+                .map_err(|e| MessageProofFetcherError::ReadFromRpc(e.to_string()))?
+            else {
+                trace!("no proof yet for message {}", nonce);
+                continue;
+            };
+
+            update_message_proof(
+                &mut conn,
+                &nonce,
+                &message_proof_json(&proof),
+                commit_height as i64,
+                finalized,
+            )
+            .map_err(|e| MessageProofFetcherError::UpdateProof(e.to_string()))?;
+            fetched += 1;
+        }
+
+        Ok(fetched)
+    }
+
+    pub async fn start(&mut self) -> Result<(), MessageProofFetcherError> {
+        let mut shutdown = self.shutdown.subscribe();
+
+        loop {
+            let wait = match self.fetch_proofs().await {
+                Ok(0) => Duration::from_secs(10),
+                Ok(n) => {
+                    info!("Fetched {} message proofs", n);
+                    Duration::ZERO
+                }
+                Err(e) => {
+                    error!("fetch_proofs failed {}, retrying", e.to_string());
+                    Duration::from_secs(10)
+                }
+            };
+
+            select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.recv() => {
+                    trace!("MessageProofFetcher shutdown");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use fuel_core_client::client::{
+    pagination::{PageDirection, PaginationRequest},
+    FuelClient,
+};
+use models::{
+    message::{apply_message_relays, batch_insert_message_relays},
+    PgSqlPool,
+};
+use thiserror::Error;
+use tokio::{select, sync::broadcast};
+use tracing::{error, info, trace};
+
+#[derive(Debug, Error)]
+pub enum MessageRelayWatcherError {
+    #[error("failed to get connection from pool: {0}")]
+    GetPgSqlPoolFailed(String),
+    #[error("Read messages from rpc failed: {0}")]
+    ReadFromRpc(String),
+    #[error("failed to store message relays: {0}")]
+    InsertRelays(String),
+    #[error("failed to apply message relays: {0}")]
+    ApplyRelays(String),
+}
+
+/// Records the da height the node relayed each unspent message at. The node
+/// drops a message once it is spent, so the relay height of a deposit is
+/// only known when it was seen here before the spending block; the others
+/// keep no relay height.
+pub struct MessageRelayWatcher {
+    db_client: PgSqlPool,
+    client: FuelClient,
+    batch_fetch_size: i32,
+    shutdown: broadcast::Sender<()>,
+}
+
+impl Drop for MessageRelayWatcher {
+    fn drop(&mut self) {
+        trace!("MessageRelayWatcher drop");
+    }
+}
+
+impl MessageRelayWatcher {
+    pub fn new(
+        db_client: PgSqlPool,
+        client: FuelClient,
+        batch_fetch_size: i32,
+        shutdown: broadcast::Sender<()>,
+    ) -> Self {
+        Self {
+            db_client,
+            client,
+            batch_fetch_size,
+            shutdown,
+        }
+    }
+
+    /// Walks every unspent message of the node, then sets the relay height
+    /// of the deposits indexed since. Returns the deposits updated.
+    async fn watch_relays(&self) -> Result<usize, MessageRelayWatcherError> {
+        let mut conn = self
+            .db_client
+            .get()
+            .map_err(|e| MessageRelayWatcherError::GetPgSqlPoolFailed(e.to_string()))?;
+
+        let mut cursor = None;
+        loop {
+            let page = self
+                .client
+                .messages(
+                    None,
+                    PaginationRequest {
+                        cursor,
+                        results: self.batch_fetch_size,
+                        direction: PageDirection::Forward,
+                    },
+                )
+                .await
+                .map_err(|e| MessageRelayWatcherError::ReadFromRpc(e.to_string()))?;
+
+            let relays = page
+                .results
+                .iter()
+                .map(|m| (m.nonce.to_string(), m.da_height as i64))
+                .collect::<Vec<_>>();
+            batch_insert_message_relays(&mut conn, &relays)
+                .map_err(|e| MessageRelayWatcherError::InsertRelays(e.to_string()))?;
+
+            if !page.has_next_page {
+                break;
+            }
+            cursor = page.cursor;
+        }
+
+        apply_message_relays(&mut conn)
+            .map_err(|e| MessageRelayWatcherError::ApplyRelays(e.to_string()))
+    }
+
+    pub async fn start(&mut self) -> Result<(), MessageRelayWatcherError> {
+        let mut shutdown = self.shutdown.subscribe();
+
+        loop {
+            match self.watch_relays().await {
+                Ok(0) => {}
+                Ok(n) => info!("Set the relay height of {} deposits", n),
+                Err(e) => error!("watch_relays failed {}, retrying", e.to_string()),
+            }
+
+            select! {
+                _ = tokio::time::sleep(Duration::from_secs(10)) => {}
+                _ = shutdown.recv() => {
+                    trace!("MessageRelayWatcher shutdown");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
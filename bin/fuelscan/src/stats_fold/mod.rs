@@ -0,0 +1,87 @@
+use models::{
+    address_transaction::fold_address_transaction_counts, chain_stats::fold_chain_stats, PgSqlPool,
+};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::{select, sync::broadcast};
+use tracing::{error, info, trace};
+
+#[derive(Debug, Error)]
+pub enum StatsFolderError {
+    #[error("failed to get connection from pool: {0}")]
+    GetPgSqlPoolFailed(String),
+    #[error("failed to fold chain statistics: {0}")]
+    FoldStats(String),
+    #[error("failed to fold address transaction counts: {0}")]
+    FoldCounts(String),
+}
+
+/// Adds the statistics queued by the block handlers to the minute, hour and
+/// day buckets of `chain_stats`, and the transaction counts they queued to
+/// `address_transaction_counts`. A single folder updates the rows many
+/// blocks share, the block handlers never wait on each other for them.
+pub struct StatsFolder {
+    db_client: PgSqlPool,
+    batch_fold_size: i64,
+    shutdown: broadcast::Sender<()>,
+}
+
+impl Drop for StatsFolder {
+    fn drop(&mut self) {
+        trace!("StatsFolder drop");
+    }
+}
+
+impl StatsFolder {
+    pub fn new(
+        db_client: PgSqlPool,
+        batch_fold_size: i64,
+        shutdown: broadcast::Sender<()>,
+    ) -> Self {
+        Self {
+            db_client,
+            batch_fold_size,
+            shutdown,
+        }
+    }
+
+    /// Folds a batch of each queue, returns the most rows folded from one.
+    fn fold_stats(&self) -> Result<i64, StatsFolderError> {
+        let mut conn = self
+            .db_client
+            .get()
+            .map_err(|e| StatsFolderError::GetPgSqlPoolFailed(e.to_string()))?;
+
+        let blocks = fold_chain_stats(&mut conn, self.batch_fold_size)
+            .map_err(|e| StatsFolderError::FoldStats(e.to_string()))?;
+        let counts = fold_address_transaction_counts(&mut conn, self.batch_fold_size)
+            .map_err(|e| StatsFolderError::FoldCounts(e.to_string()))?;
+        Ok(blocks.max(counts))
+    }
+
+    pub async fn start(&mut self) -> Result<(), StatsFolderError> {
+        let mut shutdown = self.shutdown.subscribe();
+
+        loop {
+            let wait = match self.fold_stats() {
+                Ok(n) if n == self.batch_fold_size => {
+                    info!("Folded a batch of {} statistics", n);
+                    Duration::ZERO
+                }
+                Ok(_) => Duration::from_secs(1),
+                Err(e) => {
+                    error!("fold_stats failed {}, retrying", e.to_string());
+                    Duration::from_secs(1)
+                }
+            };
+
+            select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.recv() => {
+                    trace!("StatsFolder shutdown");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
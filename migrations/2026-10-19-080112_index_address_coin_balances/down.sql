@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS address_coin_balances_unfetched_index;
+DROP INDEX IF EXISTS assets_owner_asset_id_index;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+create index assets_owner_asset_id_index on assets (assets_owner, assets_id);
+
+create index address_coin_balances_unfetched_index on address_coin_balances (block_number)
+where value_fetched_at is null;
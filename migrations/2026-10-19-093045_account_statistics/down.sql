@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+alter table accounts
+  drop column if exists last_seen_height,
+  drop column if exists first_seen_height,
+  drop column if exists fee_paid;
+
+alter table transactions drop column if exists fee;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+alter table transactions add column fee BIGINT not null default 0;
+
+alter table accounts
+  add column fee_paid BIGINT not null default 0,
+  add column first_seen_height BIGINT null,
+  add column last_seen_height BIGINT null;
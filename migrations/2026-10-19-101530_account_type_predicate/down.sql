@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- postgres can't drop an enum value, only the rows using it
+UPDATE accounts SET account_type = 'account' WHERE account_type = 'predicate';
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TYPE account_type ADD VALUE IF NOT EXISTS 'predicate';
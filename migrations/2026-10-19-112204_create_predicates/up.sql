@@ -0,0 +1,32 @@
+-- Your SQL goes here
+create table
+  predicates (
+    predicate_hash varchar not null,
+    bytecode_root varchar not null,
+    bytecode text not null,
+    bytecode_length BIGINT not null,
+    first_transaction_id varchar not null,
+    first_height BIGINT not null,
+    usage_count BIGINT not null,
+    timestamp BIGINT not null,
+    constraint predicates_pkey primary key (predicate_hash)
+  ) tablespace pg_default;
+
+create index predicates_usage_count_index on predicates (usage_count);
+
+create table
+  predicate_spends (
+    transaction_id varchar not null,
+    input_index BIGINT not null,
+    predicate_hash varchar not null,
+    utxo_id varchar null,
+    nonce varchar null,
+    amount BIGINT not null,
+    predicate_data text not null,
+    predicate_gas_used BIGINT not null,
+    height BIGINT not null,
+    timestamp BIGINT not null,
+    constraint predicate_spends_pkey primary key (transaction_id, input_index)
+  ) tablespace pg_default;
+
+create index predicate_spends_predicate_hash_index on predicate_spends (predicate_hash, height);
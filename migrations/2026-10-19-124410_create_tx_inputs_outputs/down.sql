@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS tx_outputs;
+DROP TABLE IF EXISTS tx_inputs;
+DROP TYPE IF EXISTS output_type;
+DROP TYPE IF EXISTS input_type;
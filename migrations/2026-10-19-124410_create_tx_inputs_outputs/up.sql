@@ -0,0 +1,63 @@
+-- Your SQL goes here
+DO
+$$
+BEGIN
+CREATE TYPE input_type AS ENUM (
+  'coin_signed',
+  'coin_predicate',
+  'contract',
+  'message_coin_signed',
+  'message_coin_predicate',
+  'message_data_signed',
+  'message_data_predicate'
+);
+CREATE TYPE output_type AS ENUM ('coin', 'contract', 'change', 'variable', 'contract_created');
+END
+$$;
+
+create table
+  tx_inputs (
+    transaction_id varchar not null,
+    input_index BIGINT not null,
+    input_type input_type not null,
+    utxo_id varchar null,
+    owner varchar null,
+    sender varchar null,
+    asset_id varchar null,
+    amount BIGINT null,
+    tx_pointer_height BIGINT null,
+    tx_pointer_index BIGINT null,
+    contract_id varchar null,
+    nonce varchar null,
+    height BIGINT not null,
+    timestamp BIGINT not null,
+    constraint tx_inputs_pkey primary key (transaction_id, input_index)
+  ) tablespace pg_default;
+
+create index tx_inputs_utxo_id_index on tx_inputs (utxo_id);
+create index tx_inputs_owner_index on tx_inputs (owner, height);
+create index tx_inputs_asset_id_index on tx_inputs (asset_id, height);
+create index tx_inputs_contract_id_index on tx_inputs (contract_id, height);
+create index tx_inputs_nonce_index on tx_inputs (nonce);
+create index tx_inputs_tx_pointer_index on tx_inputs (tx_pointer_height, tx_pointer_index);
+
+create table
+  tx_outputs (
+    transaction_id varchar not null,
+    output_index BIGINT not null,
+    output_type output_type not null,
+    utxo_id varchar null,
+    to_address varchar null,
+    asset_id varchar null,
+    amount BIGINT null,
+    input_index BIGINT null,
+    contract_id varchar null,
+    height BIGINT not null,
+    timestamp BIGINT not null,
+    constraint tx_outputs_pkey primary key (transaction_id, output_index)
+  ) tablespace pg_default;
+
+create index tx_outputs_utxo_id_index on tx_outputs (utxo_id);
+create index tx_outputs_to_address_index on tx_outputs (to_address, height);
+create index tx_outputs_asset_id_index on tx_outputs (asset_id, height);
+create index tx_outputs_contract_id_index on tx_outputs (contract_id, height);
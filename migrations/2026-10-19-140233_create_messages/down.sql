@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS messages;
+DROP TYPE IF EXISTS message_direction;
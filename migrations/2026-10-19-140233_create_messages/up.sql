@@ -0,0 +1,28 @@
+-- Your SQL goes here
+DO
+$$
+BEGIN
+CREATE TYPE message_direction AS ENUM ('deposit', 'withdrawal');
+END
+$$;
+
+create table
+  messages (
+    nonce varchar not null,
+    message_id varchar not null,
+    direction message_direction not null,
+    sender varchar not null,
+    recipient varchar not null,
+    amount BIGINT not null,
+    data text not null,
+    da_height BIGINT not null,
+    transaction_id varchar not null,
+    height BIGINT not null,
+    timestamp BIGINT not null,
+    constraint messages_pkey primary key (nonce)
+  ) tablespace pg_default;
+
+create index messages_message_id_index on messages (message_id);
+create index messages_sender_index on messages (sender, height);
+create index messages_recipient_index on messages (recipient, height);
+create index messages_transaction_id_index on messages (transaction_id);
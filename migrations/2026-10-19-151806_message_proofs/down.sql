@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS messages_unproven_index;
+
+alter table messages
+  drop column if exists proof_fetched_at,
+  drop column if exists proof_commit_height,
+  drop column if exists proof;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+alter table messages
+  add column proof json null,
+  add column proof_commit_height BIGINT null,
+  add column proof_fetched_at timestamp null;
+
+create index messages_unproven_index on messages (height)
+where direction = 'withdrawal' and proof is null;
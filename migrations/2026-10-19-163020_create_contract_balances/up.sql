@@ -0,0 +1,13 @@
+-- Your SQL goes here
+create table
+  contract_balances (
+    contract_hash varchar not null,
+    asset_id varchar not null,
+    amount numeric not null,
+    block_height BIGINT not null,
+    inserted_at timestamp not null default now(),
+    updated_at timestamp not null default now(),
+    constraint contract_balances_pkey primary key (contract_hash, asset_id)
+  ) tablespace pg_default;
+
+create index contract_balances_asset_id_index on contract_balances (asset_id);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS blocks_producer_index;
+alter table blocks
+  drop column if exists event_inbox_root,
+  drop column if exists consensus_parameters_version,
+  drop column if exists state_transition_bytecode_version,
+  drop column if exists consensus,
+  drop column if exists producer;
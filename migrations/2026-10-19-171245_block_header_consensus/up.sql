@@ -0,0 +1,9 @@
+-- Your SQL goes here
+alter table blocks
+  add column event_inbox_root varchar not null default '',
+  add column consensus_parameters_version BIGINT not null default 0,
+  add column state_transition_bytecode_version BIGINT not null default 0,
+  add column consensus json null,
+  add column producer varchar null;
+
+create index blocks_producer_index on blocks (producer, height);
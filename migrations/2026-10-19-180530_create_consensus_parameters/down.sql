@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS blocks_consensus_parameters_version_index;
+DROP INDEX IF EXISTS blocks_state_transition_bytecode_version_index;
+DROP TABLE IF EXISTS state_transition_bytecodes;
+DROP TABLE IF EXISTS consensus_parameters;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+create table
+  consensus_parameters (
+    version BIGINT not null,
+    parameters json not null,
+    checksum varchar null,
+    transaction_id varchar null,
+    height BIGINT null,
+    timestamp BIGINT null,
+    inserted_at timestamp not null default now(),
+    constraint consensus_parameters_pkey primary key (version)
+  ) tablespace pg_default;
+
+create table
+  state_transition_bytecodes (
+    version BIGINT not null,
+    bytecode_root varchar not null,
+    transaction_id varchar not null,
+    height BIGINT not null,
+    timestamp BIGINT not null,
+    constraint state_transition_bytecodes_pkey primary key (version)
+  ) tablespace pg_default;
+
+create index blocks_consensus_parameters_version_index on blocks (consensus_parameters_version, height);
+create index blocks_state_transition_bytecode_version_index on blocks (state_transition_bytecode_version, height);
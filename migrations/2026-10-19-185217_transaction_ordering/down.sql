@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS transactions_height_tx_index_index;
+DROP INDEX IF EXISTS coinbases_height_tx_index_index;
+DROP INDEX IF EXISTS calls_height_tx_index_index;
+alter table transactions drop column if exists tx_index;
+alter table coinbases drop column if exists tx_index;
+alter table calls
+  drop column if exists tx_index,
+  drop column if exists receipt_index;
+alter table messages
+  drop column if exists input_index,
+  drop column if exists receipt_index;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+alter table transactions add column tx_index BIGINT not null default 0;
+alter table coinbases add column tx_index BIGINT not null default 0;
+alter table calls
+  add column tx_index BIGINT not null default 0,
+  add column receipt_index BIGINT null;
+alter table messages
+  add column input_index BIGINT null,
+  add column receipt_index BIGINT null;
+
+create index transactions_height_tx_index_index on transactions (height, tx_index);
+create index coinbases_height_tx_index_index on coinbases (height, tx_index);
+create index calls_height_tx_index_index on calls (height, tx_index);
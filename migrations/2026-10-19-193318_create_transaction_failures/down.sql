@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS transaction_failures;
+DROP TABLE IF EXISTS contract_abis;
+DROP TYPE IF EXISTS failure_kind;
@@ -0,0 +1,37 @@
+-- Your SQL goes here
+DO
+$$
+BEGIN
+CREATE TYPE failure_kind AS ENUM ('panic', 'revert', 'unknown');
+END
+$$;
+
+create table
+  contract_abis (
+    contract_hash varchar not null,
+    abi json not null,
+    inserted_at timestamp not null default now(),
+    updated_at timestamp not null default now(),
+    constraint contract_abis_pkey primary key (contract_hash)
+  ) tablespace pg_default;
+
+create table
+  transaction_failures (
+    transaction_id varchar not null,
+    kind failure_kind not null,
+    receipt_index BIGINT null,
+    contract_id varchar null,
+    panic_reason varchar null,
+    revert_code varchar null,
+    signal varchar null,
+    logged_error json null,
+    message text not null,
+    node_reason text not null,
+    height BIGINT not null,
+    timestamp BIGINT not null,
+    constraint transaction_failures_pkey primary key (transaction_id)
+  ) tablespace pg_default;
+
+create index transaction_failures_contract_id_index on transaction_failures (contract_id, height);
+create index transaction_failures_panic_reason_index on transaction_failures (panic_reason);
+create index transaction_failures_signal_index on transaction_failures (signal);
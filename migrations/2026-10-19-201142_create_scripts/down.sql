@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS transactions_script_hash_index;
+DROP INDEX IF EXISTS transactions_script_category_index;
+alter table transactions
+  drop column if exists script_hash,
+  drop column if exists script_category;
+DROP TABLE IF EXISTS scripts;
+DROP TABLE IF EXISTS known_scripts;
+DROP TYPE IF EXISTS script_category;
@@ -0,0 +1,37 @@
+-- Your SQL goes here
+DO
+$$
+BEGIN
+CREATE TYPE script_category AS ENUM ('transfer', 'contract_call', 'multicall', 'predicate_spend', 'custom');
+END
+$$;
+
+create table
+  known_scripts (
+    script_hash varchar not null,
+    category script_category not null,
+    name varchar not null,
+    inserted_at timestamp not null default now(),
+    constraint known_scripts_pkey primary key (script_hash)
+  ) tablespace pg_default;
+
+create table
+  scripts (
+    script_hash varchar not null,
+    bytecode text not null,
+    bytecode_length BIGINT not null,
+    first_transaction_id varchar not null,
+    first_height BIGINT not null,
+    usage_count BIGINT not null,
+    timestamp BIGINT not null,
+    constraint scripts_pkey primary key (script_hash)
+  ) tablespace pg_default;
+
+create index scripts_usage_count_index on scripts (usage_count);
+
+alter table transactions
+  add column script_hash varchar null,
+  add column script_category script_category null;
+
+create index transactions_script_hash_index on transactions (script_hash);
+create index transactions_script_category_index on transactions (script_category, height);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS token_transfers;
+DROP TYPE IF EXISTS transfer_type;
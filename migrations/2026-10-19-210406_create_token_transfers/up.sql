@@ -0,0 +1,28 @@
+-- Your SQL goes here
+DO
+$$
+BEGIN
+CREATE TYPE transfer_type AS ENUM ('coin', 'transfer', 'transfer_out', 'mint', 'burn');
+END
+$$;
+
+create table
+  token_transfers (
+    transaction_id varchar not null,
+    transfer_index BIGINT not null,
+    transfer_type transfer_type not null,
+    output_index BIGINT null,
+    receipt_index BIGINT null,
+    asset_id varchar not null,
+    from_address varchar null,
+    to_address varchar null,
+    amount BIGINT not null,
+    height BIGINT not null,
+    tx_index BIGINT not null,
+    timestamp BIGINT not null,
+    constraint token_transfers_pkey primary key (transaction_id, transfer_index)
+  ) tablespace pg_default;
+
+create index token_transfers_from_address_index on token_transfers (from_address, height);
+create index token_transfers_to_address_index on token_transfers (to_address, height);
+create index token_transfers_asset_id_index on token_transfers (asset_id, height);
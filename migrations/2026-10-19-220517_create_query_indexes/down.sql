@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+drop index if exists transactions_sender_height_index;
+drop index if exists calls_receiver_height_index;
+drop index if exists assets_owner_alive_index;
+
+alter table calls alter column timestamp drop not null;
+alter table coinbases alter column height drop not null;
+alter table coinbases alter column da_height drop not null;
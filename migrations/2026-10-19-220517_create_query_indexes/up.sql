@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- the indexer always writes these, make the schema say so for the read api
+alter table calls alter column timestamp set not null;
+alter table coinbases alter column height set not null;
+alter table coinbases alter column da_height set not null;
+
+-- keyset pagination walks these newest first
+create index transactions_sender_height_index on transactions (sender, height, tx_index);
+create index calls_receiver_height_index on calls (receiver, height, tx_index);
+create index assets_owner_alive_index on assets (assets_owner, create_height, assets_utxo_id)
+    where asset_status = 'alive';
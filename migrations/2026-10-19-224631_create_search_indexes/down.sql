@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+drop index if exists blocks_id_prefix_index;
+drop index if exists transactions_id_prefix_index;
+drop index if exists coinbases_id_prefix_index;
+drop index if exists contracts_hash_prefix_index;
+drop index if exists accounts_hash_prefix_index;
+drop index if exists assets_utxo_id_prefix_index;
+drop index if exists token_transfers_asset_id_prefix_index;
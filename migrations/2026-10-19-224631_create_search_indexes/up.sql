@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- `like 'prefix%'` can only use an index built with the pattern operators
+-- unless the database collation is C
+create index blocks_id_prefix_index on blocks (id varchar_pattern_ops);
+create index transactions_id_prefix_index on transactions (id varchar_pattern_ops);
+create index coinbases_id_prefix_index on coinbases (id varchar_pattern_ops);
+create index contracts_hash_prefix_index on smart_contracts (contract_hash varchar_pattern_ops);
+create index accounts_hash_prefix_index on accounts (account_hash varchar_pattern_ops);
+create index assets_utxo_id_prefix_index on assets (assets_utxo_id varchar_pattern_ops);
+create index token_transfers_asset_id_prefix_index on token_transfers (asset_id varchar_pattern_ops);
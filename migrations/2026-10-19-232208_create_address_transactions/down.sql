@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+drop table if exists address_transaction_counts;
+drop table if exists address_transactions;
+drop type if exists address_role;
@@ -0,0 +1,55 @@
+-- Your SQL goes here
+DO
+$$
+BEGIN
+CREATE TYPE address_role AS ENUM ('sender', 'recipient', 'contract_called', 'fee_payer');
+END
+$$;
+
+create table
+  address_transactions (
+    address varchar not null,
+    transaction_id varchar not null,
+    roles address_role[] not null,
+    height BIGINT not null,
+    tx_index BIGINT not null,
+    timestamp BIGINT not null,
+    constraint address_transactions_pkey primary key (address, transaction_id)
+  ) tablespace pg_default;
+
+-- history pages walk an address newest first
+create index address_transactions_address_height_index on address_transactions (address, height desc, tx_index desc);
+
+create table
+  address_transaction_counts (
+    address varchar not null,
+    transaction_count BIGINT not null,
+    constraint address_transaction_counts_pkey primary key (address)
+  ) tablespace pg_default;
+
+-- the history of the blocks indexed so far, with the roles the indexer assigns
+insert into address_transactions (address, transaction_id, roles, height, tx_index, timestamp)
+select r.address, r.transaction_id, array_agg(distinct r.role order by r.role), t.height, t.tx_index, t.timestamp
+from (
+    select sender as address, id as transaction_id, 'fee_payer'::address_role as role
+    from transactions where sender is not null
+    union all
+    select owner, transaction_id, 'sender' from tx_inputs where owner is not null
+    union all
+    select contract_id, transaction_id, 'contract_called' from tx_inputs where contract_id is not null
+    union all
+    select to_address, transaction_id, 'recipient' from tx_outputs
+    where to_address is not null and output_type in ('coin', 'variable')
+    union all
+    select from_address, transaction_id, 'sender' from token_transfers where from_address is not null
+    union all
+    select to_address, transaction_id, 'recipient' from token_transfers where to_address is not null
+    union all
+    select receiver, transaction_id, 'contract_called' from calls where call_type = 'contract'
+) r
+join transactions t on t.id = r.transaction_id
+where r.address not in ('', '0000000000000000000000000000000000000000000000000000000000000000')
+group by r.address, r.transaction_id, t.height, t.tx_index, t.timestamp;
+
+insert into address_transaction_counts (address, transaction_count)
+select address, count(*) from address_transactions group by address;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+drop index if exists assets_owner_asset_alive_history_index;
+drop index if exists assets_owner_asset_spent_history_index;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- a coin counts at height h when it was created at or below h and is still
+-- alive or was spent above h, one index per side so both are range scans
+create index assets_owner_asset_alive_history_index on assets (assets_owner, assets_id, create_height)
+    include (amount) where asset_status = 'alive';
+create index assets_owner_asset_spent_history_index on assets (assets_owner, assets_id, block_height)
+    include (amount, create_height) where asset_status = 'delete';
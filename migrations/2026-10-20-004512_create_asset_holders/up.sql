@@ -0,0 +1,35 @@
+-- Your SQL goes here
+create table
+  asset_holders (
+    asset_id varchar not null,
+    owner varchar not null,
+    balance numeric not null,
+    rank BIGINT null,
+    share numeric null,
+    dirty boolean not null default true,
+    updated_height BIGINT not null,
+    constraint asset_holders_pkey primary key (asset_id, owner)
+  ) tablespace pg_default;
+
+create index asset_holders_rank_index on asset_holders (asset_id, rank) where rank is not null;
+create index asset_holders_dirty_index on asset_holders (asset_id) where dirty;
+
+create table
+  asset_holder_stats (
+    asset_id varchar not null,
+    height BIGINT not null,
+    holder_count BIGINT not null,
+    supply numeric not null,
+    top_10_share numeric not null,
+    top_100_share numeric not null,
+    buckets jsonb not null,
+    inserted_at timestamp not null default now(),
+    constraint asset_holder_stats_pkey primary key (asset_id, height)
+  ) tablespace pg_default;
+
+-- the holders of the coins indexed so far, ranked by the first refresh
+insert into asset_holders (asset_id, owner, balance, updated_height)
+select assets_id, assets_owner, sum(amount), max(create_height)
+from assets
+where asset_status = 'alive' and assets_owner <> ''
+group by assets_id, assets_owner;
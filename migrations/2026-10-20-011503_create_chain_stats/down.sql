@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+drop table if exists chain_stats_pending;
+drop table if exists chain_stats_addresses;
+drop table if exists chain_stats;
+drop type if exists stats_granularity;
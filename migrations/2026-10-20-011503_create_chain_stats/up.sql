@@ -0,0 +1,76 @@
+-- Your SQL goes here
+create type stats_granularity as enum('minute', 'hour', 'day');
+
+create table
+  chain_stats (
+    granularity stats_granularity not null,
+    bucket BIGINT not null,
+    block_count BIGINT not null,
+    transaction_count BIGINT not null,
+    active_addresses BIGINT not null,
+    new_contracts BIGINT not null,
+    gas_used numeric not null,
+    fees numeric not null,
+    first_timestamp BIGINT not null,
+    last_timestamp BIGINT not null,
+    constraint chain_stats_pkey primary key (granularity, bucket)
+  ) tablespace pg_default;
+
+-- the addresses already counted as active in a bucket
+create table
+  chain_stats_addresses (
+    granularity stats_granularity not null,
+    bucket BIGINT not null,
+    address varchar not null,
+    constraint chain_stats_addresses_pkey primary key (granularity, bucket, address)
+  ) tablespace pg_default;
+
+-- the blocks committed since the last fold into chain_stats
+create table
+  chain_stats_pending (
+    height BIGINT not null,
+    timestamp BIGINT not null,
+    transaction_count BIGINT not null,
+    new_contracts BIGINT not null,
+    gas_used numeric not null,
+    fees numeric not null,
+    addresses text[] not null,
+    constraint chain_stats_pending_pkey primary key (height)
+  ) tablespace pg_default;
+
+-- the statistics of the blocks indexed so far
+with granularities (granularity, seconds) as (
+    values ('minute'::stats_granularity, 60), ('hour', 3600), ('day', 86400)
+)
+insert into chain_stats_addresses (granularity, bucket, address)
+select distinct g.granularity, a.timestamp - a.timestamp % g.seconds, a.address
+from address_transactions a cross join granularities g;
+
+with granularities (granularity, seconds) as (
+    values ('minute'::stats_granularity, 60), ('hour', 3600), ('day', 86400)
+)
+insert into chain_stats (granularity, bucket, block_count, transaction_count, active_addresses,
+                         new_contracts, gas_used, fees, first_timestamp, last_timestamp)
+select g.granularity, b.timestamp - b.timestamp % g.seconds, count(*),
+       coalesce(sum(t.transaction_count), 0), 0, coalesce(sum(c.new_contracts), 0),
+       coalesce(sum(t.gas_used), 0), coalesce(sum(t.fees), 0), min(b.timestamp), max(b.timestamp)
+from blocks b
+cross join granularities g
+left join (
+    select height, count(*) as transaction_count, sum(gas_used) as gas_used, sum(fee) as fees
+    from transactions group by height
+) t on t.height = b.height
+left join (
+    select t.height, count(*) as new_contracts
+    from smart_contracts s join transactions t on t.id = s.transaction_id
+    group by t.height
+) c on c.height = b.height
+group by g.granularity, b.timestamp - b.timestamp % g.seconds;
+
+update chain_stats s
+set active_addresses = a.addresses
+from (
+    select granularity, bucket, count(*) as addresses
+    from chain_stats_addresses group by granularity, bucket
+) a
+where s.granularity = a.granularity and s.bucket = a.bucket;
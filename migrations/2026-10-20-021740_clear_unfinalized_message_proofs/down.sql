@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- the cleared proofs are fetched again, nothing to restore
+select 1;
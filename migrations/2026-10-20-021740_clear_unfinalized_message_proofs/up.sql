@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- proofs were fetched against the latest L2 block, which L1 had not
+-- committed yet, fetch them again against a finalized commit block
+update messages
+set proof = null, proof_commit_height = null, proof_fetched_at = null
+where proof is not null;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- postgres can't drop an enum value, only the rows using it
+delete from transactions where tx_type in ('upgrade', 'upload');
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+alter type tx_type add value if not exists 'upgrade';
+alter type tx_type add value if not exists 'upload';
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+alter table messages rename column block_da_height to da_height;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+alter table messages rename column da_height to block_da_height;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+delete from known_scripts
+where script_hash = 'e04d10aa974f5ae14299e2083124f7b42e13ef7146863415cf27b42c3680c111';
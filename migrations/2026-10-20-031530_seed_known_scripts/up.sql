@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- the scripts the SDKs build transactions with, by the sha256 of their
+-- bytecode. `RET(1)` is the script of a plain coin transfer. The contract
+-- call and multicall scripts are not seeded, the SDKs assemble them per
+-- transaction: one `CALL` per call, with the offsets of the script data the
+-- call arguments sit at. Their hash changes with the number of calls, the
+-- gas forwarded, the chain's transaction offsets and the SDK version, they
+-- are guessed from their instructions instead.
+insert into known_scripts (script_hash, category, name)
+values
+  ('e04d10aa974f5ae14299e2083124f7b42e13ef7146863415cf27b42c3680c111', 'transfer', 'SDK transfer script RET(1)')
+on conflict (script_hash) do nothing;
+
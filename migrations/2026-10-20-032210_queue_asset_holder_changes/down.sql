@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+alter table asset_holders add column dirty boolean not null default false;
+create index asset_holders_dirty_index on asset_holders (asset_id) where dirty;
+drop table if exists asset_holder_changes;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- the balance changes of the committed blocks, applied to `asset_holders`
+-- once every block up to theirs is indexed
+create table
+  asset_holder_changes (
+    height BIGINT not null,
+    asset_id varchar not null,
+    owner varchar not null,
+    balance numeric not null,
+    constraint asset_holder_changes_pkey primary key (height, asset_id, owner)
+  ) tablespace pg_default;
+
+create index asset_holder_changes_asset_index on asset_holder_changes (asset_id, height);
+
+drop index if exists asset_holders_dirty_index;
+alter table asset_holders drop column dirty;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- the dropped transfers moved nothing, nothing to restore
+select 1;
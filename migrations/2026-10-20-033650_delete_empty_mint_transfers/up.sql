@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- blocks without fees mint nothing, drop the transfers they recorded and
+-- the count they added to the coinbase contract. A `Mint` receipt of zero
+-- is still a transfer, only the coinbase mints have no receipt.
+with removed as (
+  delete from token_transfers
+  where transfer_type = 'mint' and amount = 0 and receipt_index is null
+  returning to_address
+)
+update accounts a
+set token_transfers_count = a.token_transfers_count - r.transfers
+from (
+  select to_address, count(*) as transfers
+  from removed
+  group by to_address
+) r
+where a.account_hash = r.to_address;
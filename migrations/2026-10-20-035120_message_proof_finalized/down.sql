@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+drop index messages_unproven_index;
+create index messages_unproven_index on messages (height)
+where direction = 'withdrawal' and proof is null;
+
+alter table messages drop column proof_finalized;
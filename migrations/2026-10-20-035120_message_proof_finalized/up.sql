@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- without an L1 proofs are fetched against the latest block of the node,
+-- they are fetched again once a finalized commit block is known
+alter table messages add column proof_finalized boolean not null default false;
+
+update messages set proof_finalized = true where proof is not null;
+
+drop index messages_unproven_index;
+create index messages_unproven_index on messages (height)
+where direction = 'withdrawal' and not proof_finalized;
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+delete from asset_holder_changes where height = 0 and balance = 0;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- the seeded holders were ranked through the dropped `dirty` flag, queue an
+-- empty change for every asset left unranked so its first refresh ranks it
+insert into asset_holder_changes (height, asset_id, owner, balance)
+select distinct on (asset_id) 0, asset_id, owner, 0
+from asset_holders
+where rank is null
+order by asset_id, owner
+on conflict do nothing;
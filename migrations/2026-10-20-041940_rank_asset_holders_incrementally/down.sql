@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+alter table asset_holders add column share numeric null;
+
+update asset_holders h
+set share = round(h.balance / s.supply, 18)
+from (
+  select asset_id, sum(balance) as supply
+  from asset_holders
+  group by asset_id
+) s
+where h.asset_id = s.asset_id and s.supply > 0;
+
+drop index asset_holders_balance_index;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- a refresh only re-ranks the range of balances its changes moved through
+create index asset_holders_balance_index on asset_holders (asset_id, balance desc, owner);
+
+-- the share of supply moves with every mint or burn, it is computed when
+-- the holders are read
+alter table asset_holders drop column share;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- the wrong roles are not restored
+select 1;
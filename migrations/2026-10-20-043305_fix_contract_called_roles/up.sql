@@ -0,0 +1,28 @@
+-- Your SQL goes here
+-- the receiver of every call was tagged as a contract called, including the
+-- recipient of a transfer script, or '' when it had no coin output. Only a
+-- contract input or a contract call makes an address a contract called.
+update address_transactions a
+set roles = array_remove(a.roles, 'contract_called')
+where 'contract_called' = any(a.roles)
+  and not exists (
+    select 1 from tx_inputs i
+    where i.transaction_id = a.transaction_id and i.contract_id = a.address
+  )
+  and not exists (
+    select 1 from calls c
+    where c.transaction_id = a.transaction_id and c.call_type = 'contract'
+      and c.receiver = a.address
+  );
+
+with removed as (
+  delete from address_transactions
+  where roles = '{}' or address = ''
+  returning address
+)
+update address_transaction_counts c
+set transaction_count = c.transaction_count - r.removed
+from (select address, count(*) as removed from removed group by address) r
+where c.address = r.address;
+
+delete from address_transaction_counts where transaction_count <= 0;
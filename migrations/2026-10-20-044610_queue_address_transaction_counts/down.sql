@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+insert into address_transaction_counts (address, transaction_count)
+select address, sum(transaction_count) from address_transaction_counts_pending group by address
+on conflict (address) do update
+set transaction_count = address_transaction_counts.transaction_count + excluded.transaction_count;
+
+drop table if exists address_transaction_counts_pending;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- what every block adds to the counts, folded by a single task so block
+-- transactions touching the same address don't conflict on its count
+create table
+  address_transaction_counts_pending (
+    height BIGINT not null,
+    address varchar not null,
+    transaction_count BIGINT not null,
+    constraint address_transaction_counts_pending_pkey primary key (height, address)
+  ) tablespace pg_default;
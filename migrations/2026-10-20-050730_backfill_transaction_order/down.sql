@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- the backfilled order is as good as the one it replaced, nothing to restore
+select 1;
@@ -0,0 +1,36 @@
+-- Your SQL goes here
+-- blocks indexed before `tx_index` existed have every transaction at 0,
+-- which keyset cursors on (height, tx_index) skip over. Their order is not
+-- stored, give them a stable one instead: transactions by id, the mint last.
+-- Re-index those heights to get the order the block committed.
+create temporary table backfilled_tx_order as
+select t.id, t.height,
+  row_number() over (partition by t.height order by t.mint, t.id) - 1 as tx_index
+from (
+  select id, height, false as mint from transactions
+  union all
+  select id, height, true as mint from coinbases
+) t
+where t.height in (
+  select height
+  from (
+    select height from transactions where tx_index = 0
+    union all
+    select height from coinbases where tx_index = 0
+  ) z
+  group by height
+  having count(*) > 1
+);
+
+update transactions t set tx_index = o.tx_index
+from backfilled_tx_order o where t.id = o.id;
+update coinbases c set tx_index = o.tx_index
+from backfilled_tx_order o where c.id = o.id;
+update calls c set tx_index = o.tx_index
+from backfilled_tx_order o where c.transaction_id = o.id;
+update address_transactions a set tx_index = o.tx_index
+from backfilled_tx_order o where a.transaction_id = o.id;
+update token_transfers t set tx_index = o.tx_index
+from backfilled_tx_order o where t.transaction_id = o.id;
+
+drop table backfilled_tx_order;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+drop table if exists message_relays;
+alter table messages drop column if exists da_height;
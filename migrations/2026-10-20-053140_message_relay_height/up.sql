@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- the da height a deposit was relayed at, read from the node while the
+-- message is unspent and applied to the deposit once it is indexed
+alter table messages add column da_height BIGINT null;
+
+create table
+  message_relays (
+    nonce varchar not null,
+    da_height BIGINT not null,
+    constraint message_relays_pkey primary key (nonce)
+  ) tablespace pg_default;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- a height every block up to is committed at, advanced by the block
+-- handlers so the gap free height is looked for above it only
+create table
+  indexed_height (
+    id integer not null default 0,
+    height BIGINT not null,
+    constraint indexed_height_pkey primary key (id),
+    constraint indexed_height_single_row check (id = 0)
+  ) tablespace pg_default;
+
+insert into indexed_height (id, height)
+select 0, coalesce(min(b.height), 0)
+from blocks b
+where not exists (select 1 from blocks n where n.height = b.height + 1);
@@ -1,27 +1,31 @@
-#![allow(implied_bounds_entailment)]
-
 use anyhow::Result;
 use std::time::SystemTime;
 
 use diesel::{
-    insert_into, upsert::excluded, ExpressionMethods, Insertable, PgConnection, RunQueryDsl,
+    dsl::sql,
+    insert_into, sql_function,
+    sql_types::{BigInt, Nullable, Text},
+    upsert::excluded,
+    ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl, Queryable,
+    RunQueryDsl, Selectable, SelectableHelper,
 };
 use diesel_derive_enum::DbEnum;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::accounts;
+use crate::schema::{accounts, sql_types::AccountType as AccountTypeSql};
 
 #[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 #[ExistingTypePath = "crate::schema::sql_types::AccountType"]
 #[serde(rename_all = "snake_case")]
-
 pub enum AccountType {
     Account,
     Contract,
+    Predicate,
 }
 
-#[derive(Insertable, Debug, Clone, Serialize, Deserialize, Builder)]
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize, Builder)]
 #[diesel(table_name = accounts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 #[builder(setter(into))]
 pub struct Account {
     pub account_hash: String,
@@ -35,6 +39,9 @@ pub struct Account {
     pub sender_count: i64,
     pub recever_count: i64,
     pub decompiled: bool,
+    pub fee_paid: i64,
+    pub first_seen_height: Option<i64>,
+    pub last_seen_height: Option<i64>,
     pub inserted_at: SystemTime,
     pub updated_at: SystemTime,
 }
@@ -53,12 +60,27 @@ impl Default for Account {
             sender_count: Default::default(),
             recever_count: Default::default(),
             decompiled: Default::default(),
+            fee_paid: Default::default(),
+            first_seen_height: Default::default(),
+            last_seen_height: Default::default(),
             inserted_at: SystemTime::now(),
             updated_at: SystemTime::now(),
         }
     }
 }
 
+sql_function! {
+    fn least(a: Nullable<BigInt>, b: Nullable<BigInt>) -> Nullable<BigInt>;
+}
+
+sql_function! {
+    fn greatest(a: Nullable<BigInt>, b: Nullable<BigInt>) -> Nullable<BigInt>;
+}
+
+/// Upsert the per block statistics of `records` into the running totals.
+///
+/// The counters are added to the stored ones, so every block must only be
+/// applied once, see `batch_insert_block`.
 pub fn batch_insert_accounts(
     connection: &mut PgConnection,
     records: &Vec<Account>,
@@ -68,14 +90,51 @@ pub fn batch_insert_accounts(
         .on_conflict(accounts::account_hash)
         .do_update()
         .set((
-            accounts::account_name.eq(excluded(accounts::account_name)),
-            accounts::verified.eq(excluded(accounts::verified)),
-            accounts::gas_used.eq(excluded(accounts::gas_used)),
-            accounts::transactions_count.eq(excluded(accounts::transactions_count)),
-            accounts::sender_count.eq(excluded(accounts::sender_count)),
-            accounts::recever_count.eq(excluded(accounts::recever_count)),
+            // a wallet can later turn out to be a predicate or a contract
+            accounts::account_type.eq(sql::<AccountTypeSql>(
+                "CASE WHEN excluded.account_type = 'account' \
+                 THEN accounts.account_type ELSE excluded.account_type END",
+            )),
+            accounts::account_code.eq(sql::<Nullable<Text>>(
+                "COALESCE(excluded.account_code, accounts.account_code)",
+            )),
+            accounts::gas_used.eq(accounts::gas_used + excluded(accounts::gas_used)),
+            accounts::fee_paid.eq(accounts::fee_paid + excluded(accounts::fee_paid)),
+            accounts::transactions_count
+                .eq(accounts::transactions_count + excluded(accounts::transactions_count)),
+            accounts::token_transfers_count
+                .eq(accounts::token_transfers_count + excluded(accounts::token_transfers_count)),
+            accounts::sender_count.eq(accounts::sender_count + excluded(accounts::sender_count)),
+            accounts::recever_count.eq(accounts::recever_count + excluded(accounts::recever_count)),
+            accounts::first_seen_height.eq(least(
+                accounts::first_seen_height,
+                excluded(accounts::first_seen_height),
+            )),
+            accounts::last_seen_height.eq(greatest(
+                accounts::last_seen_height,
+                excluded(accounts::last_seen_height),
+            )),
             accounts::updated_at.eq(excluded(accounts::updated_at)),
         ))
         .execute(connection)
         .map_err(|e| anyhow::anyhow!(e.to_string()))
 }
+
+pub fn get_account(connection: &mut PgConnection, account: &str) -> Result<Option<Account>> {
+    accounts::table
+        .find(account)
+        .select(Account::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The accounts with the ids in `keys`, for batched loading.
+pub fn get_accounts_by_ids(connection: &mut PgConnection, keys: &[String]) -> Result<Vec<Account>> {
+    accounts::table
+        .filter(accounts::account_hash.eq_any(keys))
+        .order(accounts::account_hash.asc())
+        .select(Account::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
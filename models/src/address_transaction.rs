@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+
+use diesel::{
+    deserialize::{self, FromSql},
+    insert_into,
+    pg::{Pg, PgValue},
+    sql_query,
+    sql_types::{Array, BigInt, Nullable, Text},
+    upsert::excluded,
+    BoolExpressionMethods, ExpressionMethods, FromSqlRow, Insertable, JoinOnDsl, OptionalExtension,
+    PgConnection, QueryDsl, Queryable, QueryableByName, RunQueryDsl, Selectable, SelectableHelper,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pagination::{pages_by_parent, Cursor, Page, ParentRow},
+    schema::{
+        address_transaction_counts, address_transaction_counts_pending, address_transactions,
+        sql_types, transactions,
+    },
+    transaction::{get_transactions_by_ids, Transaction},
+};
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[ExistingTypePath = "crate::schema::sql_types::AddressRole"]
+#[serde(rename_all = "snake_case")]
+pub enum AddressRole {
+    /// Spent a coin or message input, or sent a token transfer.
+    Sender,
+    /// Received a coin output or a token transfer.
+    Recipient,
+    /// A contract called or used as an input.
+    ContractCalled,
+    /// Paid the fee, the `sender` of the transaction.
+    FeePayer,
+}
+
+/// The roles column is an array of nullable enums to diesel, the indexer
+/// never writes a null.
+#[derive(FromSqlRow)]
+pub struct Roles(Vec<AddressRole>);
+
+impl FromSql<Array<Nullable<sql_types::AddressRole>>, Pg> for Roles {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let roles = <Vec<Option<AddressRole>> as FromSql<
+            Array<Nullable<sql_types::AddressRole>>,
+            Pg,
+        >>::from_sql(bytes)?;
+        Ok(Roles(roles.into_iter().flatten().collect()))
+    }
+}
+
+impl From<Roles> for Vec<AddressRole> {
+    fn from(roles: Roles) -> Self {
+        roles.0
+    }
+}
+
+/// A transaction in the history of an address, with every role the address
+/// plays in it.
+#[derive(
+    Insertable, Queryable, QueryableByName, Selectable, Debug, Clone, Serialize, Deserialize,
+)]
+#[diesel(table_name = address_transactions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AddressTransaction {
+    pub address: String,
+    pub transaction_id: String,
+    #[diesel(deserialize_as = Roles)]
+    pub roles: Vec<AddressRole>,
+    pub height: i64,
+    pub tx_index: i64,
+    pub timestamp: i64,
+}
+
+/// Inserts the history rows and queues the count of the ones not indexed
+/// before, so the counts stay exact when a block is written again. Must run
+/// in the transaction writing the block at `height`.
+pub fn batch_insert_address_transactions(
+    connection: &mut PgConnection,
+    height: i64,
+    records: &Vec<AddressTransaction>,
+) -> Result<usize> {
+    let inserted = insert_into(address_transactions::table)
+        .values(records)
+        .on_conflict((
+            address_transactions::address,
+            address_transactions::transaction_id,
+        ))
+        .do_nothing()
+        .returning(address_transactions::address)
+        .get_results::<String>(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+    let mut counts = HashMap::<String, i64>::new();
+    for address in &inserted {
+        *counts.entry(address.clone()).or_default() += 1;
+    }
+    let counts = counts
+        .into_iter()
+        .map(|(address, count)| {
+            (
+                address_transaction_counts_pending::height.eq(height),
+                address_transaction_counts_pending::address.eq(address),
+                address_transaction_counts_pending::transaction_count.eq(count),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    insert_into(address_transaction_counts_pending::table)
+        .values(&counts)
+        .on_conflict((
+            address_transaction_counts_pending::height,
+            address_transaction_counts_pending::address,
+        ))
+        .do_update()
+        .set(
+            address_transaction_counts_pending::transaction_count
+                .eq(address_transaction_counts_pending::transaction_count
+                    + excluded(address_transaction_counts_pending::transaction_count)),
+        )
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+    Ok(inserted.len())
+}
+
+#[derive(QueryableByName)]
+struct Folded {
+    #[diesel(sql_type = BigInt)]
+    counts: i64,
+}
+
+/// Adds up to `limit` queued counts to `address_transaction_counts` and
+/// returns how many were folded. A busy address is in many concurrent
+/// blocks, so only one folder should update its count.
+pub fn fold_address_transaction_counts(connection: &mut PgConnection, limit: i64) -> Result<i64> {
+    sql_query(
+        r#"
+        WITH pending AS (
+            DELETE FROM address_transaction_counts_pending
+            WHERE (height, address) IN (
+                SELECT height, address
+                FROM address_transaction_counts_pending
+                ORDER BY height
+                LIMIT $1
+            )
+            RETURNING address, transaction_count
+        ),
+        upserted AS (
+            INSERT INTO address_transaction_counts (address, transaction_count)
+            SELECT address, SUM(transaction_count)
+            FROM pending
+            GROUP BY address
+            ON CONFLICT (address) DO UPDATE
+            SET transaction_count =
+                address_transaction_counts.transaction_count + excluded.transaction_count
+        )
+        SELECT COUNT(*) AS counts FROM pending
+        "#,
+    )
+    .bind::<BigInt, _>(limit)
+    .get_result::<Folded>(connection)
+    .map(|f| f.counts)
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// How many transactions are in the history of an address.
+pub fn get_address_transaction_count(connection: &mut PgConnection, address: &str) -> Result<i64> {
+    address_transaction_counts::table
+        .find(address)
+        .select(address_transaction_counts::transaction_count)
+        .first(connection)
+        .optional()
+        .map(Option::unwrap_or_default)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The history of an address below `before`, newest first, each transaction
+/// with the roles the address plays in it.
+pub fn get_address_transactions(
+    connection: &mut PgConnection,
+    address: &str,
+    before: Option<Cursor>,
+    limit: i64,
+) -> Result<Page<(AddressTransaction, Transaction)>> {
+    let mut query = address_transactions::table
+        .inner_join(
+            transactions::table.on(transactions::id.eq(address_transactions::transaction_id)),
+        )
+        .filter(address_transactions::address.eq(address))
+        .select((AddressTransaction::as_select(), Transaction::as_select()))
+        .into_boxed();
+    if let Some(before) = before {
+        query = query.filter(
+            address_transactions::height
+                .lt(before.height)
+                .or(address_transactions::height
+                    .eq(before.height)
+                    .and(address_transactions::tx_index.lt(before.index))),
+        );
+    }
+
+    query
+        .order((
+            address_transactions::height.desc(),
+            address_transactions::tx_index.desc(),
+        ))
+        .limit(limit + 1)
+        .load(connection)
+        .map(|rows| {
+            Page::from_rows(rows, limit, |(a, _): &(AddressTransaction, Transaction)| {
+                Cursor::new(a.height, a.tx_index)
+            })
+        })
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// `get_address_transactions` for every address in `addresses`, keyed by
+/// address. The history rows of all of them are loaded with one query and
+/// their transactions with another.
+pub fn get_address_transactions_by_addresses(
+    connection: &mut PgConnection,
+    addresses: &[String],
+    before: Option<Cursor>,
+    limit: i64,
+) -> Result<BTreeMap<String, Page<(AddressTransaction, Transaction)>>> {
+    let rows = sql_query(
+        r#"
+        SELECT * FROM (
+            SELECT a.*, a.address AS parent, row_number() OVER (
+                PARTITION BY a.address ORDER BY a.height DESC, a.tx_index DESC
+            ) AS rn
+            FROM address_transactions a
+            WHERE a.address = ANY($1) AND ($2::bigint IS NULL OR (a.height, a.tx_index) < ($2, $3))
+        ) a
+        WHERE rn <= $4
+        ORDER BY parent, rn
+        "#,
+    )
+    .bind::<Array<Text>, _>(addresses)
+    .bind::<Nullable<BigInt>, _>(before.map(|c| c.height))
+    .bind::<Nullable<BigInt>, _>(before.map(|c| c.index))
+    .bind::<BigInt, _>(limit + 1)
+    .load::<ParentRow<AddressTransaction>>(connection)
+    .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+    let mut ids = rows
+        .iter()
+        .map(|r| r.row.transaction_id.clone())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    let transactions = get_transactions_by_ids(connection, &ids)?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect::<HashMap<_, _>>();
+
+    Ok(pages_by_parent(rows, limit, |a: &AddressTransaction| {
+        Cursor::new(a.height, a.tx_index)
+    })
+    .into_iter()
+    .map(|(address, page)| {
+        let items = page
+            .items
+            .into_iter()
+            .filter_map(|a| {
+                // the same transaction may be in the page of several addresses
+                let transaction = transactions.get(&a.transaction_id).cloned();
+                transaction.map(|t| (a, t))
+            })
+            .collect();
+        (
+            address,
+            Page {
+                items,
+                next: page.next,
+            },
+        )
+    })
+    .collect())
+}
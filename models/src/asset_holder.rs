@@ -0,0 +1,367 @@
+use std::time::SystemTime;
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use diesel::{
+    dsl::exists,
+    insert_into, select, sql_query,
+    sql_types::{BigInt, Nullable, Numeric, Varchar},
+    Connection, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, QueryableByName,
+    RunQueryDsl, Selectable, SelectableHelper,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pagination::Page,
+    schema::{asset_holder_changes, asset_holder_stats, asset_holders},
+};
+
+/// What the block at `height` changed in the balance of an owner, `balance`
+/// is the signed difference.
+#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = asset_holder_changes)]
+pub struct AssetHolderChange {
+    pub height: i64,
+    pub asset_id: String,
+    pub owner: String,
+    pub balance: BigDecimal,
+}
+
+/// An owner of unspent coins of an asset at the last refresh, with its
+/// `rank` and `share` of supply.
+#[derive(QueryableByName, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = asset_holders)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AssetHolder {
+    pub asset_id: String,
+    pub owner: String,
+    pub balance: BigDecimal,
+    pub rank: Option<i64>,
+    #[diesel(sql_type = Nullable<Numeric>)]
+    pub share: Option<BigDecimal>,
+    pub updated_height: i64,
+}
+
+/// The holder distribution of an asset when refreshed at `height`. Buckets
+/// count the holders by order of magnitude of their balance, `"3"` holds
+/// the balances from 1000 to 9999.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = asset_holder_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AssetHolderStats {
+    pub asset_id: String,
+    pub height: i64,
+    pub holder_count: i64,
+    pub supply: BigDecimal,
+    pub top_10_share: BigDecimal,
+    pub top_100_share: BigDecimal,
+    pub buckets: serde_json::Value,
+    pub inserted_at: SystemTime,
+}
+
+/// Queues the balance changes of a block. Must run in the transaction
+/// writing the block, a rolled back block is then never applied.
+pub fn batch_insert_asset_holder_changes(
+    connection: &mut PgConnection,
+    records: &Vec<AssetHolderChange>,
+) -> Result<usize> {
+    insert_into(asset_holder_changes::table)
+        .values(records)
+        .on_conflict_do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The assets with changes queued at or below `height`.
+pub fn get_changed_assets(
+    connection: &mut PgConnection,
+    height: i64,
+    limit: i64,
+) -> Result<Vec<String>> {
+    asset_holder_changes::table
+        .filter(asset_holder_changes::height.le(height))
+        .select(asset_holder_changes::asset_id)
+        .distinct()
+        .limit(limit)
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+// the first refresh of an asset ranks all its holders and counts its
+// distribution, later ones only touch what the applied changes moved
+const RANK_ALL: &str = r#"
+    UPDATE asset_holders h
+    SET rank = r.rank
+    FROM (
+        SELECT owner, row_number() OVER (ORDER BY balance DESC, owner) AS rank
+        FROM asset_holders
+        WHERE asset_id = $1
+    ) r
+    WHERE h.asset_id = $1 AND h.owner = r.owner AND h.rank IS DISTINCT FROM r.rank
+"#;
+
+// ranks only change between the highest and the lowest position a changed
+// holder left or took. A holder added or removed shifts every rank below it,
+// the range then runs to the last holder.
+const RANK_CHANGED: &str = r#"
+    WITH keys AS (
+        SELECT old_balance AS balance, owner FROM applied_holder_changes WHERE old_balance > 0
+        UNION ALL
+        SELECT new_balance, owner FROM applied_holder_changes WHERE new_balance > 0
+    ),
+    top_key AS (
+        SELECT balance, owner FROM keys ORDER BY balance DESC, owner LIMIT 1
+    ),
+    bottom_key AS (
+        SELECT balance, owner FROM keys
+        WHERE NOT EXISTS (
+            SELECT 1 FROM applied_holder_changes
+            WHERE (old_balance > 0) <> (new_balance > 0)
+        )
+        ORDER BY balance, owner DESC LIMIT 1
+    ),
+    ahead AS (
+        SELECT COUNT(*) AS holders
+        FROM asset_holders h, top_key t
+        WHERE h.asset_id = $1 AND h.balance >= t.balance
+          AND (h.balance > t.balance OR h.owner < t.owner)
+    ),
+    ranked AS (
+        SELECT h.owner, a.holders + row_number() OVER (ORDER BY h.balance DESC, h.owner) AS rank
+        FROM asset_holders h
+        CROSS JOIN top_key t
+        CROSS JOIN ahead a
+        LEFT JOIN bottom_key b ON true
+        WHERE h.asset_id = $1 AND h.balance <= t.balance
+          AND (h.balance < t.balance OR h.owner >= t.owner)
+          AND (b.balance IS NULL OR h.balance > b.balance
+               OR (h.balance = b.balance AND h.owner <= b.owner))
+    )
+    UPDATE asset_holders h
+    SET rank = r.rank
+    FROM ranked r
+    WHERE h.asset_id = $1 AND h.owner = r.owner AND h.rank IS DISTINCT FROM r.rank
+"#;
+
+const COUNT_ALL: &str = r#"
+    INSERT INTO asset_holder_stats
+        (asset_id, height, holder_count, supply, top_10_share, top_100_share, buckets)
+    SELECT $1, $2, COUNT(*), COALESCE(SUM(balance), 0),
+           COALESCE(round(SUM(balance) FILTER (WHERE rank <= 10) / NULLIF(SUM(balance), 0), 18), 0),
+           COALESCE(round(SUM(balance) FILTER (WHERE rank <= 100) / NULLIF(SUM(balance), 0), 18), 0),
+           COALESCE((
+               SELECT jsonb_object_agg(magnitude, holders)
+               FROM (
+                   SELECT floor(log(balance))::int AS magnitude, COUNT(*) AS holders
+                   FROM asset_holders
+                   WHERE asset_id = $1
+                   GROUP BY 1
+               ) b
+           ), '{}'::jsonb)
+    FROM asset_holders
+    WHERE asset_id = $1
+    ON CONFLICT (asset_id, height) DO UPDATE
+    SET holder_count = excluded.holder_count,
+        supply = excluded.supply,
+        top_10_share = excluded.top_10_share,
+        top_100_share = excluded.top_100_share,
+        buckets = excluded.buckets,
+        inserted_at = now()
+"#;
+
+// the previous distribution moved by the applied changes, the top holders
+// are read from the rank index
+const COUNT_CHANGED: &str = r#"
+    WITH previous AS (
+        SELECT holder_count, supply, buckets
+        FROM asset_holder_stats
+        WHERE asset_id = $1
+        ORDER BY height DESC
+        LIMIT 1
+    ),
+    changed AS (
+        SELECT COUNT(*) FILTER (WHERE old_balance = 0 AND new_balance > 0)
+                 - COUNT(*) FILTER (WHERE old_balance > 0 AND new_balance = 0) AS holders,
+               COALESCE(SUM(new_balance - old_balance), 0) AS supply
+        FROM applied_holder_changes
+    ),
+    magnitudes AS (
+        SELECT key::int AS magnitude, value::bigint AS holders
+        FROM previous, jsonb_each_text(previous.buckets)
+        UNION ALL
+        SELECT floor(log(old_balance))::int, -1 FROM applied_holder_changes WHERE old_balance > 0
+        UNION ALL
+        SELECT floor(log(new_balance))::int, 1 FROM applied_holder_changes WHERE new_balance > 0
+    ),
+    buckets AS (
+        SELECT COALESCE(jsonb_object_agg(magnitude, holders), '{}'::jsonb) AS buckets
+        FROM (
+            SELECT magnitude, SUM(holders) AS holders
+            FROM magnitudes
+            GROUP BY 1
+            HAVING SUM(holders) > 0
+        ) m
+    ),
+    top AS (
+        SELECT COALESCE(SUM(balance) FILTER (WHERE rank <= 10), 0) AS top_10,
+               COALESCE(SUM(balance), 0) AS top_100
+        FROM asset_holders
+        WHERE asset_id = $1 AND rank <= 100
+    )
+    INSERT INTO asset_holder_stats
+        (asset_id, height, holder_count, supply, top_10_share, top_100_share, buckets)
+    SELECT $1, $2, p.holder_count + c.holders, p.supply + c.supply,
+           COALESCE(round(t.top_10 / NULLIF(p.supply + c.supply, 0), 18), 0),
+           COALESCE(round(t.top_100 / NULLIF(p.supply + c.supply, 0), 18), 0),
+           b.buckets
+    FROM previous p, changed c, buckets b, top t
+    ON CONFLICT (asset_id, height) DO UPDATE
+    SET holder_count = excluded.holder_count,
+        supply = excluded.supply,
+        top_10_share = excluded.top_10_share,
+        top_100_share = excluded.top_100_share,
+        buckets = excluded.buckets,
+        inserted_at = now()
+"#;
+
+/// Applies the changes of an asset queued at or below `height`, ranks its
+/// holders, drops the ones left with nothing and records the distribution.
+/// Every block up to `height` must be indexed, the balances are then the
+/// ones at the end of that block. Changes of later blocks stay queued.
+///
+/// Only the first refresh of an asset ranks and counts all its holders,
+/// later ones re-rank the range the changed holders moved through and move
+/// the previous distribution by the changes.
+pub fn refresh_asset_holders(
+    connection: &mut PgConnection,
+    asset_id: &str,
+    height: i64,
+) -> Result<()> {
+    connection.transaction::<_, anyhow::Error, _>(|conn| {
+        let refreshed = select(exists(
+            asset_holder_stats::table.filter(asset_holder_stats::asset_id.eq(asset_id)),
+        ))
+        .get_result::<bool>(conn)?;
+
+        sql_query(
+            r#"
+            CREATE TEMP TABLE applied_holder_changes (
+                owner varchar not null,
+                old_balance numeric not null,
+                new_balance numeric not null,
+                height BIGINT not null
+            ) ON COMMIT DROP
+            "#,
+        )
+        .execute(conn)?;
+        sql_query(
+            r#"
+            WITH applied AS (
+                DELETE FROM asset_holder_changes
+                WHERE asset_id = $1 AND height <= $2
+                RETURNING owner, balance, height
+            )
+            INSERT INTO applied_holder_changes (owner, old_balance, new_balance, height)
+            SELECT a.owner, COALESCE(h.balance, 0), COALESCE(h.balance, 0) + SUM(a.balance),
+                   MAX(a.height)
+            FROM applied a
+            LEFT JOIN asset_holders h ON h.asset_id = $1 AND h.owner = a.owner
+            GROUP BY a.owner, h.balance
+            "#,
+        )
+        .bind::<Varchar, _>(asset_id)
+        .bind::<BigInt, _>(height)
+        .execute(conn)?;
+
+        sql_query(
+            r#"
+            INSERT INTO asset_holders (asset_id, owner, balance, updated_height)
+            SELECT $1, owner, new_balance, height
+            FROM applied_holder_changes
+            WHERE new_balance > 0
+            ON CONFLICT (asset_id, owner) DO UPDATE
+            SET balance = excluded.balance,
+                updated_height = GREATEST(asset_holders.updated_height, excluded.updated_height)
+            "#,
+        )
+        .bind::<Varchar, _>(asset_id)
+        .execute(conn)?;
+        sql_query(
+            r#"
+            DELETE FROM asset_holders h
+            USING applied_holder_changes a
+            WHERE h.asset_id = $1 AND h.owner = a.owner AND a.new_balance <= 0
+            "#,
+        )
+        .bind::<Varchar, _>(asset_id)
+        .execute(conn)?;
+
+        let (rank, count) = if refreshed {
+            (RANK_CHANGED, COUNT_CHANGED)
+        } else {
+            (RANK_ALL, COUNT_ALL)
+        };
+        sql_query(rank).bind::<Varchar, _>(asset_id).execute(conn)?;
+        sql_query(count)
+            .bind::<Varchar, _>(asset_id)
+            .bind::<BigInt, _>(height)
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+/// The holders of an asset by rank, the page after rank `after`. The share
+/// is taken of the supply recorded by the last refresh, which ranked them.
+pub fn get_asset_holders(
+    connection: &mut PgConnection,
+    asset_id: &str,
+    after: Option<i64>,
+    limit: i64,
+) -> Result<Page<AssetHolder, i64>> {
+    sql_query(
+        r#"
+        SELECT h.asset_id, h.owner, h.balance, h.rank, h.updated_height,
+               round(h.balance / NULLIF(s.supply, 0), 18) AS share
+        FROM asset_holders h
+        LEFT JOIN LATERAL (
+            SELECT supply
+            FROM asset_holder_stats
+            WHERE asset_id = $1
+            ORDER BY height DESC
+            LIMIT 1
+        ) s ON true
+        WHERE h.asset_id = $1 AND h.rank > $2
+        ORDER BY h.rank
+        LIMIT $3
+        "#,
+    )
+    .bind::<Varchar, _>(asset_id)
+    .bind::<BigInt, _>(after.unwrap_or_default())
+    .bind::<BigInt, _>(limit + 1)
+    .load::<AssetHolder>(connection)
+    .map(|rows| Page::from_rows(rows, limit, |h: &AssetHolder| h.rank.unwrap_or_default()))
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The distribution history of an asset below `before`, newest first.
+pub fn get_asset_holder_stats(
+    connection: &mut PgConnection,
+    asset_id: &str,
+    before: Option<i64>,
+    limit: i64,
+) -> Result<Page<AssetHolderStats, i64>> {
+    let mut query = asset_holder_stats::table
+        .filter(asset_holder_stats::asset_id.eq(asset_id))
+        .select(AssetHolderStats::as_select())
+        .into_boxed();
+    if let Some(before) = before {
+        query = query.filter(asset_holder_stats::height.lt(before));
+    }
+
+    query
+        .order(asset_holder_stats::height.desc())
+        .limit(limit + 1)
+        .load(connection)
+        .map(|rows| Page::from_rows(rows, limit, |s: &AssetHolderStats| s.height))
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
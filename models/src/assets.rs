@@ -1,15 +1,22 @@
-#![allow(implied_bounds_entailment)]
-use std::time::SystemTime;
+use std::{collections::BTreeMap, time::SystemTime};
 
 use anyhow::Result;
+use bigdecimal::BigDecimal;
 
 use diesel::{
-    insert_into, upsert::excluded, ExpressionMethods, Insertable, PgConnection, RunQueryDsl,
+    insert_into, sql_query,
+    sql_types::{Array, BigInt, Nullable, Text},
+    upsert::excluded,
+    BoolExpressionMethods, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable,
+    QueryableByName, RunQueryDsl, Selectable, SelectableHelper,
 };
 use diesel_derive_enum::DbEnum;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::assets;
+use crate::{
+    pagination::{pages_by_parent, Page, ParentRow, UtxoCursor},
+    schema::assets,
+};
 
 #[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 #[ExistingTypePath = "crate::schema::sql_types::AssetStatus"]
@@ -19,8 +26,11 @@ pub enum AssetStatus {
     Delete,
 }
 
-#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[derive(
+    Insertable, Queryable, QueryableByName, Selectable, Debug, Clone, Serialize, Deserialize,
+)]
 #[diesel(table_name = assets)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Assets {
     pub assets_id: String,
     pub assets_utxo_id: String,
@@ -54,12 +64,30 @@ impl Default for Assets {
 }
 
 pub fn batch_insert_assets(connection: &mut PgConnection, records: &Vec<Assets>) -> Result<usize> {
+    // the spend of a utxo can be committed before its creation because blocks
+    // are handled concurrently, so only touch the creation side here.
     insert_into(assets::table)
         .values(records)
         .on_conflict(assets::assets_utxo_id)
         .do_update()
         .set((
+            assets::assets_id.eq(excluded(assets::assets_id)),
+            assets::assets_owner.eq(excluded(assets::assets_owner)),
             assets::amount.eq(excluded(assets::amount)),
+            assets::create_height.eq(excluded(assets::create_height)),
+            assets::create_tx_hash.eq(excluded(assets::create_tx_hash)),
+            assets::first_seen.eq(excluded(assets::first_seen)),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn batch_delete_assets(connection: &mut PgConnection, records: &Vec<Assets>) -> Result<usize> {
+    insert_into(assets::table)
+        .values(records)
+        .on_conflict(assets::assets_utxo_id)
+        .do_update()
+        .set((
             assets::block_height.eq(excluded(assets::block_height)),
             assets::delete_tx_hash.eq(excluded(assets::delete_tx_hash)),
             assets::last_seen.eq(excluded(assets::last_seen)),
@@ -68,3 +96,199 @@ pub fn batch_insert_assets(connection: &mut PgConnection, records: &Vec<Assets>)
         .execute(connection)
         .map_err(|e| anyhow::anyhow!(e.to_string()))
 }
+
+/// The unspent coins of an owner, newest first, optionally of one asset.
+pub fn get_alive_assets(
+    connection: &mut PgConnection,
+    owner: &str,
+    asset_id: Option<&str>,
+    before: Option<UtxoCursor>,
+    limit: i64,
+) -> Result<Page<Assets, UtxoCursor>> {
+    let mut query = assets::table
+        .filter(assets::assets_owner.eq(owner))
+        .filter(assets::asset_status.eq(AssetStatus::Alive))
+        .select(Assets::as_select())
+        .into_boxed();
+    if let Some(asset_id) = asset_id {
+        query = query.filter(assets::assets_id.eq(asset_id));
+    }
+    if let Some(before) = before {
+        query = query.filter(
+            assets::create_height
+                .lt(before.height)
+                .or(assets::create_height
+                    .eq(before.height)
+                    .and(assets::assets_utxo_id.lt(before.utxo_id))),
+        );
+    }
+
+    query
+        .order((assets::create_height.desc(), assets::assets_utxo_id.desc()))
+        .limit(limit + 1)
+        .load(connection)
+        .map(|rows| {
+            Page::from_rows(rows, limit, |a: &Assets| UtxoCursor {
+                height: a.create_height,
+                utxo_id: a.assets_utxo_id.clone(),
+            })
+        })
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// `get_alive_assets` for every owner in `owners` with a single query, keyed
+/// by owner. Owners without unspent coins have no page.
+pub fn get_alive_assets_by_owners(
+    connection: &mut PgConnection,
+    owners: &[String],
+    asset_id: Option<&str>,
+    before: Option<UtxoCursor>,
+    limit: i64,
+) -> Result<BTreeMap<String, Page<Assets, UtxoCursor>>> {
+    let (before_height, before_utxo_id) = before.map(|c| (c.height, c.utxo_id)).unzip();
+    sql_query(
+        r#"
+        SELECT * FROM (
+            SELECT a.*, a.assets_owner AS parent, row_number() OVER (
+                PARTITION BY a.assets_owner
+                ORDER BY a.create_height DESC, a.assets_utxo_id DESC
+            ) AS rn
+            FROM assets a
+            WHERE a.assets_owner = ANY($1) AND a.asset_status = 'alive'
+                AND ($2::text IS NULL OR a.assets_id = $2)
+                AND ($3::bigint IS NULL OR (a.create_height, a.assets_utxo_id) < ($3, $4))
+        ) a
+        WHERE rn <= $5
+        ORDER BY parent, rn
+        "#,
+    )
+    .bind::<Array<Text>, _>(owners)
+    .bind::<Nullable<Text>, _>(asset_id)
+    .bind::<Nullable<BigInt>, _>(before_height)
+    .bind::<Nullable<Text>, _>(before_utxo_id)
+    .bind::<BigInt, _>(limit + 1)
+    .load::<ParentRow<Assets>>(connection)
+    .map(|rows| {
+        pages_by_parent(rows, limit, |a: &Assets| UtxoCursor {
+            height: a.create_height,
+            utxo_id: a.assets_utxo_id.clone(),
+        })
+    })
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The sum of the unspent coins of an owner per asset.
+pub fn get_owner_balances(
+    connection: &mut PgConnection,
+    owner: &str,
+) -> Result<Vec<(String, BigDecimal)>> {
+    assets::table
+        .filter(assets::assets_owner.eq(owner))
+        .filter(assets::asset_status.eq(AssetStatus::Alive))
+        .group_by(assets::assets_id)
+        .select((assets::assets_id, diesel::dsl::sum(assets::amount)))
+        .order(assets::assets_id.asc())
+        .load::<(String, Option<BigDecimal>)>(connection)
+        .map(|balances| {
+            balances
+                .into_iter()
+                .map(|(asset_id, amount)| (asset_id, amount.unwrap_or_default()))
+                .collect()
+        })
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// `get_owner_balances` of many owners at once, as `(owner, asset, amount)`.
+pub fn get_balances_by_owners(
+    connection: &mut PgConnection,
+    owners: &[String],
+) -> Result<Vec<(String, String, BigDecimal)>> {
+    assets::table
+        .filter(assets::assets_owner.eq_any(owners))
+        .filter(assets::asset_status.eq(AssetStatus::Alive))
+        .group_by((assets::assets_owner, assets::assets_id))
+        .select((
+            assets::assets_owner,
+            assets::assets_id,
+            diesel::dsl::sum(assets::amount),
+        ))
+        .order((assets::assets_owner.asc(), assets::assets_id.asc()))
+        .load::<(String, String, Option<BigDecimal>)>(connection)
+        .map(|balances| {
+            balances
+                .into_iter()
+                .map(|(owner, asset_id, amount)| (owner, asset_id, amount.unwrap_or_default()))
+                .collect()
+        })
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+fn sum_by_asset(
+    rows: Vec<(String, Option<BigDecimal>)>,
+    balances: &mut BTreeMap<String, BigDecimal>,
+) {
+    for (asset_id, amount) in rows {
+        *balances.entry(asset_id).or_default() += amount.unwrap_or_default();
+    }
+}
+
+/// The balances of an owner at the end of block `height`, optionally of one
+/// asset: the coins created at or below `height` that were still unspent
+/// then. A coin created and spent in the same block never counts.
+pub fn get_owner_balances_at_height(
+    connection: &mut PgConnection,
+    owner: &str,
+    asset_id: Option<&str>,
+    height: i64,
+) -> Result<Vec<(String, BigDecimal)>> {
+    let mut alive = assets::table
+        .filter(assets::assets_owner.eq(owner))
+        .filter(assets::asset_status.eq(AssetStatus::Alive))
+        .filter(assets::create_height.le(height))
+        .group_by(assets::assets_id)
+        .select((assets::assets_id, diesel::dsl::sum(assets::amount)))
+        .into_boxed();
+    // spent rows keep the spend height in `block_height`
+    let mut spent = assets::table
+        .filter(assets::assets_owner.eq(owner))
+        .filter(assets::asset_status.eq(AssetStatus::Delete))
+        .filter(assets::block_height.gt(height))
+        .filter(assets::create_height.le(height))
+        .group_by(assets::assets_id)
+        .select((assets::assets_id, diesel::dsl::sum(assets::amount)))
+        .into_boxed();
+    if let Some(asset_id) = asset_id {
+        alive = alive.filter(assets::assets_id.eq(asset_id));
+        spent = spent.filter(assets::assets_id.eq(asset_id));
+    }
+
+    let mut balances = BTreeMap::new();
+    sum_by_asset(
+        alive
+            .load(connection)
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?,
+        &mut balances,
+    );
+    sum_by_asset(
+        spent
+            .load(connection)
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?,
+        &mut balances,
+    );
+    Ok(balances.into_iter().collect())
+}
+
+/// The balance of one asset of an owner at the end of block `height`.
+pub fn get_balance_at_height(
+    connection: &mut PgConnection,
+    owner: &str,
+    asset_id: &str,
+    height: i64,
+) -> Result<BigDecimal> {
+    Ok(
+        get_owner_balances_at_height(connection, owner, Some(asset_id), height)?
+            .pop()
+            .map(|(_, amount)| amount)
+            .unwrap_or_default(),
+    )
+}
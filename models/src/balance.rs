@@ -0,0 +1,144 @@
+use anyhow::Result;
+use std::time::SystemTime;
+
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use diesel::{
+    insert_into, sql_query, sql_types::BigInt, ExpressionMethods, Insertable, OptionalExtension,
+    PgConnection, QueryDsl, Queryable, RunQueryDsl,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{address_coin_balances, address_coin_balances_daily};
+
+/// An address touched a coin of `asset_hash` at `block_number`. The `value`
+/// is left empty and filled in later by [`fetch_address_coin_balances`].
+#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = address_coin_balances)]
+pub struct AddressCoinBalance {
+    pub address_hash: String,
+    pub asset_hash: String,
+    pub block_number: i64,
+    pub inserted_at: SystemTime,
+    pub updated_at: SystemTime,
+}
+
+#[derive(Queryable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = address_coin_balances_daily)]
+pub struct AddressCoinBalanceDaily {
+    pub address_hash: String,
+    pub asset_hash: String,
+    pub day: NaiveDate,
+    pub value: Option<BigDecimal>,
+    pub inserted_at: SystemTime,
+    pub updated_at: SystemTime,
+}
+
+pub fn batch_insert_address_coin_balances(
+    connection: &mut PgConnection,
+    records: &Vec<AddressCoinBalance>,
+) -> Result<usize> {
+    insert_into(address_coin_balances::table)
+        .values(records)
+        .on_conflict((
+            address_coin_balances::address_hash,
+            address_coin_balances::asset_hash,
+            address_coin_balances::block_number,
+        ))
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Compute the value of up to `limit` unfetched balances at or below
+/// `max_height` from the utxos in `assets`, and roll them up into
+/// `address_coin_balances_daily`.
+///
+/// `max_height` must not be above the contiguous indexed height, otherwise a
+/// utxo created or spent in a block that is not committed yet is missed.
+pub fn fetch_address_coin_balances(
+    connection: &mut PgConnection,
+    max_height: i64,
+    limit: i64,
+) -> Result<usize> {
+    sql_query(
+        r#"
+        WITH fetched AS (
+            UPDATE address_coin_balances acb
+            SET value = (
+                    SELECT COALESCE(SUM(a.amount), 0)
+                    FROM assets a
+                    WHERE a.assets_owner = acb.address_hash
+                      AND a.assets_id = acb.asset_hash
+                      AND a.create_height <= acb.block_number
+                      AND (a.asset_status = 'alive' OR a.block_height > acb.block_number)
+                ),
+                value_fetched_at = now(),
+                updated_at = now()
+            WHERE (acb.address_hash, acb.asset_hash, acb.block_number) IN (
+                SELECT address_hash, asset_hash, block_number
+                FROM address_coin_balances
+                WHERE value_fetched_at IS NULL AND block_number <= $1
+                ORDER BY block_number
+                LIMIT $2
+            )
+            RETURNING acb.address_hash, acb.asset_hash, acb.block_number, acb.value
+        )
+        INSERT INTO address_coin_balances_daily
+            (address_hash, asset_hash, day, value, inserted_at, updated_at)
+        SELECT DISTINCT ON (f.address_hash, f.asset_hash, day)
+            f.address_hash,
+            f.asset_hash,
+            (to_timestamp(b.timestamp) AT TIME ZONE 'UTC')::date AS day,
+            f.value,
+            now(),
+            now()
+        FROM fetched f
+        JOIN blocks b ON b.height = f.block_number
+        ORDER BY f.address_hash, f.asset_hash, day, f.block_number DESC
+        ON CONFLICT (address_hash, asset_hash, day) DO UPDATE
+        SET value = excluded.value, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind::<BigInt, _>(max_height)
+    .bind::<BigInt, _>(limit)
+    .execute(connection)
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The balance of `address` in `asset` as of block `height`, `None` when the
+/// address never held the asset or the balance has not been fetched yet.
+pub fn get_address_coin_balance(
+    connection: &mut PgConnection,
+    address: &str,
+    asset: &str,
+    height: i64,
+) -> Result<Option<BigDecimal>> {
+    address_coin_balances::table
+        .select(address_coin_balances::value)
+        .filter(address_coin_balances::address_hash.eq(address))
+        .filter(address_coin_balances::asset_hash.eq(asset))
+        .filter(address_coin_balances::block_number.le(height))
+        .filter(address_coin_balances::value_fetched_at.is_not_null())
+        .order(address_coin_balances::block_number.desc())
+        .first::<Option<BigDecimal>>(connection)
+        .optional()
+        .map(Option::flatten)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_address_coin_balances_daily(
+    connection: &mut PgConnection,
+    address: &str,
+    asset: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AddressCoinBalanceDaily>> {
+    address_coin_balances_daily::table
+        .filter(address_coin_balances_daily::address_hash.eq(address))
+        .filter(address_coin_balances_daily::asset_hash.eq(asset))
+        .filter(address_coin_balances_daily::day.between(from, to))
+        .order(address_coin_balances_daily::day.asc())
+        .load::<AddressCoinBalanceDaily>(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -1,13 +1,19 @@
-#![allow(implied_bounds_entailment)]
 use anyhow::Result;
 
-use diesel::{insert_into, Insertable, PgConnection, QueryDsl, RunQueryDsl};
+use diesel::{
+    insert_into, ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl,
+    Queryable, QueryableByName, RunQueryDsl, Selectable, SelectableHelper,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::schema::blocks;
+use crate::{
+    pagination::{Cursor, Page},
+    schema::blocks,
+};
 
-#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
 #[diesel(table_name = blocks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Block {
     pub id: String,
     pub height: i64,
@@ -22,6 +28,11 @@ pub struct Block {
     pub transaction_count: i64,
     pub output_message_count: i64,
     pub timestamp: i64,
+    pub event_inbox_root: String,
+    pub consensus_parameters_version: i64,
+    pub state_transition_bytecode_version: i64,
+    pub consensus: Option<serde_json::Value>,
+    pub producer: Option<String>,
 }
 
 pub fn batch_insert_block(connection: &mut PgConnection, records: &Vec<Block>) -> Result<usize> {
@@ -35,12 +46,92 @@ pub fn batch_insert_block(connection: &mut PgConnection, records: &Vec<Block>) -
 
 pub fn get_last_block_height(connection: &mut PgConnection) -> i64 {
     use crate::schema::blocks::dsl::*;
-    use diesel::dsl::max;
 
     blocks
-        .select(max(height))
+        .select(diesel::dsl::max(height))
         .first::<Option<i64>>(connection)
         .unwrap_or(Some(1000000))
         .map(|h| h + 1)
         .unwrap_or_default()
 }
+
+#[derive(QueryableByName)]
+struct IndexedHeight {
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    height: Option<i64>,
+}
+
+// the lowest block above the watermark whose next block is missing, the
+// watermark only trails the gap free height so the scan stays short
+const INDEXED_HEIGHT: &str = "SELECT MIN(b.height) AS height FROM blocks b, indexed_height w \
+     WHERE b.height >= w.height \
+     AND NOT EXISTS (SELECT 1 FROM blocks n WHERE n.height = b.height + 1)";
+
+/// The highest height below which every block has been committed. Handlers
+/// commit blocks concurrently, so `get_last_block_height` can be ahead of it.
+pub fn get_indexed_height(connection: &mut PgConnection) -> Result<Option<i64>> {
+    diesel::sql_query(INDEXED_HEIGHT)
+        .get_result::<IndexedHeight>(connection)
+        .map(|h| h.height)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Moves the watermark `get_indexed_height` scans from up to the gap free
+/// height. Handlers racing on it may each see an older height, it never
+/// moves back.
+pub fn advance_indexed_height(connection: &mut PgConnection) -> Result<usize> {
+    diesel::sql_query(format!(
+        "UPDATE indexed_height w SET height = GREATEST(w.height, s.height) \
+         FROM ({}) s WHERE s.height IS NOT NULL",
+        INDEXED_HEIGHT
+    ))
+    .execute(connection)
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_block_by_height(connection: &mut PgConnection, height: i64) -> Result<Option<Block>> {
+    blocks::table
+        .filter(blocks::height.eq(height))
+        .select(Block::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_block_by_id(connection: &mut PgConnection, id: &str) -> Result<Option<Block>> {
+    blocks::table
+        .find(id)
+        .select(Block::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The latest blocks below `before`, newest first.
+pub fn get_blocks(
+    connection: &mut PgConnection,
+    before: Option<Cursor>,
+    limit: i64,
+) -> Result<Page<Block>> {
+    let mut query = blocks::table.select(Block::as_select()).into_boxed();
+    if let Some(before) = before {
+        query = query.filter(blocks::height.lt(before.height));
+    }
+
+    query
+        .order(blocks::height.desc())
+        .limit(limit + 1)
+        .load(connection)
+        .map(|rows| Page::from_rows(rows, limit, |b: &Block| Cursor::new(b.height, 0)))
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The blocks at `keys`, for batched loading.
+pub fn get_blocks_by_heights(connection: &mut PgConnection, keys: &[i64]) -> Result<Vec<Block>> {
+    blocks::table
+        .filter(blocks::height.eq_any(keys))
+        .order(blocks::height.asc())
+        .select(Block::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -1,12 +1,21 @@
-#![allow(implied_bounds_entailment)]
 use anyhow::Result;
 
-use diesel::{insert_into, Insertable, PgConnection, RunQueryDsl};
+use std::collections::BTreeMap;
+
+use diesel::{
+    insert_into, sql_query,
+    sql_types::{Array, BigInt, Nullable, Text},
+    BoolExpressionMethods, ExpressionMethods, Insertable, OptionalExtension, PgConnection,
+    QueryDsl, Queryable, QueryableByName, RunQueryDsl, Selectable, SelectableHelper,
+};
 
 use diesel_derive_enum::DbEnum;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::calls;
+use crate::{
+    pagination::{pages_by_parent, Cursor, Page, ParentRow},
+    schema::calls,
+};
 
 #[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 #[ExistingTypePath = "crate::schema::sql_types::CallType"]
@@ -16,11 +25,15 @@ pub enum CallType {
     Transaction,
 }
 
-#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[derive(
+    Insertable, Queryable, QueryableByName, Selectable, Debug, Clone, Serialize, Deserialize,
+)]
 #[diesel(table_name = calls)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Call {
     pub transaction_id: String,
     pub height: i64,
+    pub tx_index: i64,
     pub da_height: i64,
     pub block_hash: String,
     pub call_type: CallType,
@@ -33,6 +46,8 @@ pub struct Call {
     pub asset_id: Option<String>,
     pub payload: Option<String>,
     pub payload_data: Option<String>,
+    /// The receipt the call was read from.
+    pub receipt_index: Option<i64>,
     pub timestamp: i64,
 }
 
@@ -44,3 +59,85 @@ pub fn batch_insert_calls(connection: &mut PgConnection, records: &Vec<Call>) ->
         .execute(connection)
         .map_err(|e| anyhow::anyhow!(e.to_string()))
 }
+
+pub fn get_transaction_call(
+    connection: &mut PgConnection,
+    transaction_id: &str,
+) -> Result<Option<Call>> {
+    calls::table
+        .find(transaction_id)
+        .select(Call::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The calls made to a contract, newest first.
+pub fn get_contract_calls(
+    connection: &mut PgConnection,
+    contract: &str,
+    before: Option<Cursor>,
+    limit: i64,
+) -> Result<Page<Call>> {
+    let mut query = calls::table
+        .filter(calls::receiver.eq(contract))
+        .select(Call::as_select())
+        .into_boxed();
+    if let Some(before) = before {
+        query = query.filter(
+            calls::height.lt(before.height).or(calls::height
+                .eq(before.height)
+                .and(calls::tx_index.lt(before.index))),
+        );
+    }
+
+    query
+        .order((calls::height.desc(), calls::tx_index.desc()))
+        .limit(limit + 1)
+        .load(connection)
+        .map(|rows| Page::from_rows(rows, limit, |c: &Call| Cursor::new(c.height, c.tx_index)))
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// `get_contract_calls` for every contract in `contracts` with a single
+/// query, keyed by contract. Contracts without calls have no page.
+pub fn get_contract_calls_by_contracts(
+    connection: &mut PgConnection,
+    contracts: &[String],
+    before: Option<Cursor>,
+    limit: i64,
+) -> Result<BTreeMap<String, Page<Call>>> {
+    sql_query(
+        r#"
+        SELECT * FROM (
+            SELECT c.*, c.receiver AS parent, row_number() OVER (
+                PARTITION BY c.receiver ORDER BY c.height DESC, c.tx_index DESC
+            ) AS rn
+            FROM calls c
+            WHERE c.receiver = ANY($1) AND ($2::bigint IS NULL OR (c.height, c.tx_index) < ($2, $3))
+        ) c
+        WHERE rn <= $4
+        ORDER BY parent, rn
+        "#,
+    )
+    .bind::<Array<Text>, _>(contracts)
+    .bind::<Nullable<BigInt>, _>(before.map(|c| c.height))
+    .bind::<Nullable<BigInt>, _>(before.map(|c| c.index))
+    .bind::<BigInt, _>(limit + 1)
+    .load::<ParentRow<Call>>(connection)
+    .map(|rows| pages_by_parent(rows, limit, |c: &Call| Cursor::new(c.height, c.tx_index)))
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The calls of the transactions in `keys`.
+pub fn get_calls_by_transactions(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<Call>> {
+    calls::table
+        .filter(calls::transaction_id.eq_any(keys))
+        .order(calls::transaction_id.asc())
+        .select(Call::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
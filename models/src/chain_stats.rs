@@ -0,0 +1,203 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use diesel::{
+    insert_into, sql_query, sql_types::BigInt, ExpressionMethods, Insertable, PgConnection,
+    QueryDsl, Queryable, QueryableByName, RunQueryDsl, Selectable, SelectableHelper,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pagination::Page,
+    schema::{chain_stats, chain_stats_pending},
+};
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[ExistingTypePath = "crate::schema::sql_types::StatsGranularity"]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Granularity {
+    /// The length of a bucket in seconds.
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Granularity::Minute => 60,
+            Granularity::Hour => 3600,
+            Granularity::Day => 86400,
+        }
+    }
+}
+
+impl FromStr for Granularity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minute" => Ok(Granularity::Minute),
+            "hour" => Ok(Granularity::Hour),
+            "day" => Ok(Granularity::Day),
+            _ => Err(anyhow::anyhow!("invalid granularity {}", s)),
+        }
+    }
+}
+
+/// What a committed block adds to the statistics of its buckets, kept until
+/// the next fold. Mint transactions are not counted.
+#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = chain_stats_pending)]
+pub struct BlockStats {
+    pub height: i64,
+    pub timestamp: i64,
+    pub transaction_count: i64,
+    pub new_contracts: i64,
+    pub gas_used: BigDecimal,
+    pub fees: BigDecimal,
+    /// Every address the transactions of the block touch, once.
+    pub addresses: Vec<String>,
+}
+
+/// The blocks whose unix timestamp falls in `[bucket, bucket + seconds)`.
+/// Buckets without any block have no row.
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = chain_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ChainStats {
+    pub granularity: Granularity,
+    pub bucket: i64,
+    pub block_count: i64,
+    pub transaction_count: i64,
+    pub active_addresses: i64,
+    pub new_contracts: i64,
+    pub gas_used: BigDecimal,
+    pub fees: BigDecimal,
+    pub first_timestamp: i64,
+    pub last_timestamp: i64,
+}
+
+impl ChainStats {
+    /// Seconds between the blocks of the bucket, none with a single block.
+    pub fn average_block_time(&self) -> Option<f64> {
+        if self.block_count < 2 {
+            return None;
+        }
+        Some((self.last_timestamp - self.first_timestamp) as f64 / (self.block_count - 1) as f64)
+    }
+
+    /// Transactions per second over the whole bucket.
+    pub fn tps(&self) -> f64 {
+        self.transaction_count as f64 / self.granularity.seconds() as f64
+    }
+}
+
+/// Queues the statistics of a block. Must run in the transaction writing the
+/// block, a rolled back block is then never counted.
+pub fn insert_block_stats(connection: &mut PgConnection, stats: &BlockStats) -> Result<usize> {
+    insert_into(chain_stats_pending::table)
+        .values(stats)
+        .on_conflict_do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+#[derive(QueryableByName)]
+struct Folded {
+    #[diesel(sql_type = BigInt)]
+    blocks: i64,
+}
+
+/// Adds up to `limit` queued blocks to their minute, hour and day buckets
+/// and returns how many were folded. Runs as a single statement, the queue
+/// rows go with the buckets they were added to. Blocks touching the same
+/// buckets would conflict if every block transaction updated them, so only
+/// one folder should run.
+pub fn fold_chain_stats(connection: &mut PgConnection, limit: i64) -> Result<i64> {
+    sql_query(
+        r#"
+        WITH pending AS (
+            DELETE FROM chain_stats_pending
+            WHERE height IN (SELECT height FROM chain_stats_pending ORDER BY height LIMIT $1)
+            RETURNING *
+        ),
+        granularities (granularity, seconds) AS (
+            VALUES ('minute'::stats_granularity, 60::bigint), ('hour', 3600), ('day', 86400)
+        ),
+        new_addresses AS (
+            INSERT INTO chain_stats_addresses (granularity, bucket, address)
+            SELECT DISTINCT g.granularity, p.timestamp - p.timestamp % g.seconds, a.address
+            FROM pending p
+            CROSS JOIN granularities g
+            CROSS JOIN unnest(p.addresses) AS a(address)
+            ON CONFLICT DO NOTHING
+            RETURNING granularity, bucket
+        ),
+        active AS (
+            SELECT granularity, bucket, COUNT(*) AS addresses
+            FROM new_addresses
+            GROUP BY granularity, bucket
+        ),
+        buckets AS (
+            SELECT g.granularity, p.timestamp - p.timestamp % g.seconds AS bucket,
+                   COUNT(*) AS block_count, SUM(p.transaction_count) AS transaction_count,
+                   SUM(p.new_contracts) AS new_contracts, SUM(p.gas_used) AS gas_used,
+                   SUM(p.fees) AS fees, MIN(p.timestamp) AS first_timestamp,
+                   MAX(p.timestamp) AS last_timestamp
+            FROM pending p
+            CROSS JOIN granularities g
+            GROUP BY 1, 2
+        ),
+        upserted AS (
+            INSERT INTO chain_stats
+                (granularity, bucket, block_count, transaction_count, active_addresses,
+                 new_contracts, gas_used, fees, first_timestamp, last_timestamp)
+            SELECT b.granularity, b.bucket, b.block_count, b.transaction_count,
+                   COALESCE(a.addresses, 0), b.new_contracts, b.gas_used, b.fees,
+                   b.first_timestamp, b.last_timestamp
+            FROM buckets b
+            LEFT JOIN active a ON a.granularity = b.granularity AND a.bucket = b.bucket
+            ON CONFLICT (granularity, bucket) DO UPDATE
+            SET block_count = chain_stats.block_count + excluded.block_count,
+                transaction_count = chain_stats.transaction_count + excluded.transaction_count,
+                active_addresses = chain_stats.active_addresses + excluded.active_addresses,
+                new_contracts = chain_stats.new_contracts + excluded.new_contracts,
+                gas_used = chain_stats.gas_used + excluded.gas_used,
+                fees = chain_stats.fees + excluded.fees,
+                first_timestamp = LEAST(chain_stats.first_timestamp, excluded.first_timestamp),
+                last_timestamp = GREATEST(chain_stats.last_timestamp, excluded.last_timestamp)
+        )
+        SELECT COUNT(*) AS blocks FROM pending
+        "#,
+    )
+    .bind::<BigInt, _>(limit)
+    .get_result::<Folded>(connection)
+    .map(|f| f.blocks)
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The buckets of a granularity starting below `before`, newest first.
+pub fn get_chain_stats(
+    connection: &mut PgConnection,
+    granularity: Granularity,
+    before: Option<i64>,
+    limit: i64,
+) -> Result<Page<ChainStats, i64>> {
+    let mut query = chain_stats::table
+        .filter(chain_stats::granularity.eq(granularity))
+        .select(ChainStats::as_select())
+        .into_boxed();
+    if let Some(before) = before {
+        query = query.filter(chain_stats::bucket.lt(before));
+    }
+
+    query
+        .order(chain_stats::bucket.desc())
+        .limit(limit + 1)
+        .load(connection)
+        .map(|rows| Page::from_rows(rows, limit, |s: &ChainStats| s.bucket))
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
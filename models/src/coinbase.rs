@@ -1,17 +1,21 @@
-#![allow(implied_bounds_entailment)]
 use anyhow::Result;
 
-use diesel::{insert_into, Insertable, PgConnection, RunQueryDsl};
+use diesel::{
+    insert_into, ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl,
+    Queryable, RunQueryDsl, Selectable, SelectableHelper,
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::schema::coinbases;
 
-#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
 #[diesel(table_name = coinbases)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Coinbase {
     pub id: String,
     pub height: i64,
+    pub tx_index: i64,
     pub da_height: i64,
     pub block_hash: String,
     pub amount: Option<i64>,
@@ -30,3 +34,35 @@ pub fn batch_insert_coinbase(
         .execute(connection)
         .map_err(|e| anyhow::anyhow!(e.to_string()))
 }
+
+/// The mint transaction of the block at `height`.
+pub fn get_block_coinbase(connection: &mut PgConnection, height: i64) -> Result<Option<Coinbase>> {
+    coinbases::table
+        .filter(coinbases::height.eq(height))
+        .select(Coinbase::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_coinbase(connection: &mut PgConnection, id: &str) -> Result<Option<Coinbase>> {
+    coinbases::table
+        .find(id)
+        .select(Coinbase::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The mint transactions of the blocks at `keys`.
+pub fn get_coinbases_by_heights(
+    connection: &mut PgConnection,
+    keys: &[i64],
+) -> Result<Vec<Coinbase>> {
+    coinbases::table
+        .filter(coinbases::height.eq_any(keys))
+        .order(coinbases::height.asc())
+        .select(Coinbase::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -0,0 +1,224 @@
+use anyhow::Result;
+use std::time::SystemTime;
+
+use diesel::{
+    dsl::sql,
+    insert_into,
+    sql_types::{BigInt, Nullable, Varchar},
+    upsert::excluded,
+    ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl, Queryable,
+    RunQueryDsl, Selectable,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{consensus_parameters, state_transition_bytecodes};
+
+/// The consensus parameters in force for every block whose header carries
+/// `version`. The upgrade columns are empty for a version only known from
+/// the node, e.g. the one in force when the indexer started.
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = consensus_parameters)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ConsensusParameters {
+    pub version: i64,
+    pub parameters: serde_json::Value,
+    pub checksum: Option<String>,
+    pub transaction_id: Option<String>,
+    pub height: Option<i64>,
+    pub timestamp: Option<i64>,
+    pub inserted_at: SystemTime,
+}
+
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = state_transition_bytecodes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StateTransitionBytecode {
+    pub version: i64,
+    pub bytecode_root: String,
+    pub transaction_id: String,
+    pub height: i64,
+    pub timestamp: i64,
+}
+
+/// A single parameter that differs between two versions, `path` is the
+/// dotted path of the field, e.g. `fee_params.gas_price_factor`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterChange {
+    pub path: String,
+    pub from: Option<serde_json::Value>,
+    pub to: Option<serde_json::Value>,
+}
+
+pub fn batch_insert_consensus_parameters(
+    connection: &mut PgConnection,
+    records: &Vec<ConsensusParameters>,
+) -> Result<usize> {
+    // the node and the upgrade transaction may both report a version, keep
+    // whatever upgrade data one of them knows about.
+    insert_into(consensus_parameters::table)
+        .values(records)
+        .on_conflict(consensus_parameters::version)
+        .do_update()
+        .set((
+            consensus_parameters::parameters.eq(excluded(consensus_parameters::parameters)),
+            consensus_parameters::checksum.eq(sql::<Nullable<Varchar>>(
+                "COALESCE(excluded.checksum, consensus_parameters.checksum)",
+            )),
+            consensus_parameters::transaction_id.eq(sql::<Nullable<Varchar>>(
+                "COALESCE(excluded.transaction_id, consensus_parameters.transaction_id)",
+            )),
+            consensus_parameters::height.eq(sql::<Nullable<BigInt>>(
+                "COALESCE(excluded.height, consensus_parameters.height)",
+            )),
+            consensus_parameters::timestamp.eq(sql::<Nullable<BigInt>>(
+                "COALESCE(excluded.timestamp, consensus_parameters.timestamp)",
+            )),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn batch_insert_state_transition_bytecodes(
+    connection: &mut PgConnection,
+    records: &Vec<StateTransitionBytecode>,
+) -> Result<usize> {
+    insert_into(state_transition_bytecodes::table)
+        .values(records)
+        .on_conflict(state_transition_bytecodes::version)
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_consensus_parameters(
+    connection: &mut PgConnection,
+    version: i64,
+) -> Result<Option<ConsensusParameters>> {
+    consensus_parameters::table
+        .find(version)
+        .first::<ConsensusParameters>(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+fn diff_values(
+    path: &str,
+    from: Option<&serde_json::Value>,
+    to: Option<&serde_json::Value>,
+    changes: &mut Vec<ParameterChange>,
+) {
+    match (from, to) {
+        (Some(serde_json::Value::Object(from)), Some(serde_json::Value::Object(to))) => {
+            let mut keys = from.keys().chain(to.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_values(&path, from.get(key), to.get(key), changes);
+            }
+        }
+        (from, to) if from != to => changes.push(ParameterChange {
+            path: path.to_string(),
+            from: from.cloned(),
+            to: to.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+/// Every leaf parameter that differs between two parameter sets.
+pub fn diff_consensus_parameters(
+    from: &serde_json::Value,
+    to: &serde_json::Value,
+) -> Vec<ParameterChange> {
+    let mut changes = vec![];
+    diff_values("", Some(from), Some(to), &mut changes);
+    changes
+}
+
+/// What changed from version `from` to version `to`, `None` when one of
+/// them has not been indexed.
+pub fn get_consensus_parameters_changes(
+    connection: &mut PgConnection,
+    from: i64,
+    to: i64,
+) -> Result<Option<Vec<ParameterChange>>> {
+    let (Some(from), Some(to)) = (
+        get_consensus_parameters(connection, from)?,
+        get_consensus_parameters(connection, to)?,
+    ) else {
+        return Ok(None);
+    };
+
+    Ok(Some(diff_consensus_parameters(
+        &from.parameters,
+        &to.parameters,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn change(
+        path: &str,
+        from: Option<serde_json::Value>,
+        to: Option<serde_json::Value>,
+    ) -> ParameterChange {
+        ParameterChange {
+            path: path.to_string(),
+            from,
+            to,
+        }
+    }
+
+    #[test]
+    fn equal_parameters_have_no_changes() {
+        let parameters = json!({ "fee_params": { "gas_price_factor": 92 }, "chain_id": 0 });
+        assert!(diff_consensus_parameters(&parameters, &parameters).is_empty());
+    }
+
+    #[test]
+    fn changes_are_listed_by_dotted_path() {
+        let from = json!({
+            "fee_params": { "gas_price_factor": 92, "gas_per_byte": 63 },
+            "block_gas_limit": 100,
+        });
+        let to = json!({
+            "fee_params": { "gas_price_factor": 100, "gas_per_byte": 63 },
+            "block_gas_limit": 100,
+            "privileged_address": "ff",
+        });
+        assert_eq!(
+            diff_consensus_parameters(&from, &to),
+            vec![
+                change(
+                    "fee_params.gas_price_factor",
+                    Some(json!(92)),
+                    Some(json!(100))
+                ),
+                change("privileged_address", None, Some(json!("ff"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_field_changing_shape_is_one_change() {
+        let from = json!({ "gas_costs": { "V1": { "add": 1 } } });
+        let to = json!({ "gas_costs": [1] });
+        assert_eq!(
+            diff_consensus_parameters(&from, &to),
+            vec![change(
+                "gas_costs",
+                Some(json!({ "V1": { "add": 1 } })),
+                Some(json!([1]))
+            )]
+        );
+    }
+}
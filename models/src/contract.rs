@@ -1,14 +1,17 @@
-#![allow(implied_bounds_entailment)]
 use anyhow::Result;
 
-use diesel::{insert_into, Insertable, PgConnection, RunQueryDsl};
+use diesel::{
+    insert_into, ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl,
+    Queryable, RunQueryDsl, Selectable, SelectableHelper,
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::schema::smart_contracts;
 
-#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
 #[diesel(table_name = smart_contracts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Contract {
     pub contract_hash: String,
     pub transaction_id: String,
@@ -30,3 +33,25 @@ pub fn batch_insert_contracts(
         .execute(connection)
         .map_err(|e| anyhow::anyhow!(e.to_string()))
 }
+
+pub fn get_contract(connection: &mut PgConnection, contract: &str) -> Result<Option<Contract>> {
+    smart_contracts::table
+        .find(contract)
+        .select(Contract::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The contracts with the ids in `keys`, for batched loading.
+pub fn get_contracts_by_ids(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<Contract>> {
+    smart_contracts::table
+        .filter(smart_contracts::contract_hash.eq_any(keys))
+        .order(smart_contracts::contract_hash.asc())
+        .select(Contract::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
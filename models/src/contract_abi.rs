@@ -0,0 +1,48 @@
+use anyhow::Result;
+use std::{collections::HashMap, time::SystemTime};
+
+use diesel::{
+    insert_into, upsert::excluded, ExpressionMethods, Insertable, PgConnection, QueryDsl,
+    Queryable, RunQueryDsl, Selectable,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::contract_abis;
+
+/// The json ABI a contract was compiled with, registered by hand since the
+/// chain only knows the bytecode.
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = contract_abis)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ContractAbi {
+    pub contract_hash: String,
+    pub abi: serde_json::Value,
+    pub inserted_at: SystemTime,
+    pub updated_at: SystemTime,
+}
+
+pub fn insert_contract_abi(connection: &mut PgConnection, record: &ContractAbi) -> Result<usize> {
+    insert_into(contract_abis::table)
+        .values(record)
+        .on_conflict(contract_abis::contract_hash)
+        .do_update()
+        .set((
+            contract_abis::abi.eq(excluded(contract_abis::abi)),
+            contract_abis::updated_at.eq(excluded(contract_abis::updated_at)),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The registered ABIs of `contracts`, keyed by contract hash.
+pub fn get_contract_abis(
+    connection: &mut PgConnection,
+    contracts: &[String],
+) -> Result<HashMap<String, serde_json::Value>> {
+    contract_abis::table
+        .filter(contract_abis::contract_hash.eq_any(contracts))
+        .select((contract_abis::contract_hash, contract_abis::abi))
+        .load::<(String, serde_json::Value)>(connection)
+        .map(|abis| abis.into_iter().collect())
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use std::time::SystemTime;
+
+use bigdecimal::BigDecimal;
+use diesel::{
+    dsl::sql,
+    insert_into,
+    sql_types::{BigInt, Double},
+    upsert::excluded,
+    ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl, Queryable,
+    RunQueryDsl, Selectable, SelectableHelper,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::contract_balances;
+
+/// The amount of `asset_id` held by a contract as of `block_height`.
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = contract_balances)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ContractBalance {
+    pub contract_hash: String,
+    pub asset_id: String,
+    pub amount: BigDecimal,
+    pub block_height: i64,
+    pub inserted_at: SystemTime,
+    pub updated_at: SystemTime,
+}
+
+pub fn batch_insert_contract_balances(
+    connection: &mut PgConnection,
+    records: &Vec<ContractBalance>,
+) -> Result<usize> {
+    insert_into(contract_balances::table)
+        .values(records)
+        .on_conflict((
+            contract_balances::contract_hash,
+            contract_balances::asset_id,
+        ))
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Adds the change of a block to the balances, `amount` is a delta here.
+/// Blocks are committed out of order so only additions are safe.
+pub fn batch_add_contract_balances(
+    connection: &mut PgConnection,
+    records: &Vec<ContractBalance>,
+) -> Result<usize> {
+    insert_into(contract_balances::table)
+        .values(records)
+        .on_conflict((
+            contract_balances::contract_hash,
+            contract_balances::asset_id,
+        ))
+        .do_update()
+        .set((
+            contract_balances::amount
+                .eq(contract_balances::amount + excluded(contract_balances::amount)),
+            contract_balances::block_height.eq(sql::<BigInt>(
+                "GREATEST(excluded.block_height, contract_balances.block_height)",
+            )),
+            contract_balances::updated_at.eq(excluded(contract_balances::updated_at)),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_contract_balances(
+    connection: &mut PgConnection,
+    contract: &str,
+) -> Result<Vec<ContractBalance>> {
+    contract_balances::table
+        .filter(contract_balances::contract_hash.eq(contract))
+        .order(contract_balances::asset_id)
+        .load::<ContractBalance>(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Random balances to compare with the node.
+pub fn sample_contract_balances(
+    connection: &mut PgConnection,
+    limit: i64,
+) -> Result<Vec<ContractBalance>> {
+    contract_balances::table
+        .order(sql::<Double>("random()"))
+        .limit(limit)
+        .load::<ContractBalance>(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The balance of `asset_id` held by `contract`, with the height that last
+/// changed it.
+pub fn get_contract_balance(
+    connection: &mut PgConnection,
+    contract: &str,
+    asset_id: &str,
+) -> Result<Option<ContractBalance>> {
+    contract_balances::table
+        .filter(contract_balances::contract_hash.eq(contract))
+        .filter(contract_balances::asset_id.eq(asset_id))
+        .select(ContractBalance::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The balances of the contracts in `keys`.
+pub fn get_balances_by_contracts(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<ContractBalance>> {
+    contract_balances::table
+        .filter(contract_balances::contract_hash.eq_any(keys))
+        .order((
+            contract_balances::contract_hash.asc(),
+            contract_balances::asset_id.asc(),
+        ))
+        .select(ContractBalance::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
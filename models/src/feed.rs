@@ -0,0 +1,102 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use diesel::PgConnection;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::get_block_by_height,
+    call::get_calls_by_transactions,
+    token_transfer::get_token_transfers_by_transactions,
+    transaction::{get_block_transactions, TxStatus},
+    tx_input::get_inputs_by_transactions,
+    tx_output::get_outputs_by_transactions,
+};
+
+/// A transaction as published on the live feed, with every address,
+/// contract and asset it touched so subscribers can filter on them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionEvent {
+    pub id: String,
+    pub tx_index: i64,
+    pub status: TxStatus,
+    pub sender: Option<String>,
+    pub fee: i64,
+    pub addresses: BTreeSet<String>,
+    pub contracts: BTreeSet<String>,
+    pub assets: BTreeSet<String>,
+}
+
+/// A committed block as published on the live feed, the mint transaction
+/// is left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockEvent {
+    pub height: i64,
+    pub id: String,
+    pub timestamp: i64,
+    pub transaction_count: i64,
+    pub transactions: Vec<TransactionEvent>,
+}
+
+/// Builds the feed event of a committed block, `None` when the block is not
+/// indexed.
+pub fn get_block_event(connection: &mut PgConnection, height: i64) -> Result<Option<BlockEvent>> {
+    let Some(block) = get_block_by_height(connection, height)? else {
+        return Ok(None);
+    };
+
+    let transactions = get_block_transactions(connection, height)?;
+    let ids = transactions
+        .iter()
+        .map(|t| t.id.clone())
+        .collect::<Vec<_>>();
+    let inputs = get_inputs_by_transactions(connection, &ids)?;
+    let outputs = get_outputs_by_transactions(connection, &ids)?;
+    let transfers = get_token_transfers_by_transactions(connection, &ids)?;
+    let calls = get_calls_by_transactions(connection, &ids)?;
+
+    let transactions = transactions
+        .into_iter()
+        .map(|t| {
+            let mut event = TransactionEvent {
+                addresses: t.sender.iter().cloned().collect(),
+                contracts: BTreeSet::new(),
+                assets: BTreeSet::new(),
+                id: t.id,
+                tx_index: t.tx_index,
+                status: t.status,
+                sender: t.sender,
+                fee: t.fee,
+            };
+
+            for input in inputs.iter().filter(|i| i.transaction_id == event.id) {
+                event.addresses.extend(input.owner.clone());
+                event.addresses.extend(input.sender.clone());
+                event.contracts.extend(input.contract_id.clone());
+                event.assets.extend(input.asset_id.clone());
+            }
+            for output in outputs.iter().filter(|o| o.transaction_id == event.id) {
+                event.addresses.extend(output.to_address.clone());
+                event.contracts.extend(output.contract_id.clone());
+                event.assets.extend(output.asset_id.clone());
+            }
+            for transfer in transfers.iter().filter(|t| t.transaction_id == event.id) {
+                event.addresses.extend(transfer.from_address.clone());
+                event.addresses.extend(transfer.to_address.clone());
+                event.assets.insert(transfer.asset_id.clone());
+            }
+            for call in calls.iter().filter(|c| c.transaction_id == event.id) {
+                event.contracts.insert(call.receiver.clone());
+            }
+            event
+        })
+        .collect();
+
+    Ok(Some(BlockEvent {
+        height: block.height,
+        id: block.id,
+        timestamp: block.timestamp,
+        transaction_count: block.transaction_count,
+        transactions,
+    }))
+}
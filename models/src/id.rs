@@ -0,0 +1,58 @@
+use anyhow::Result;
+
+/// Hex length of an id.
+pub const ID_LENGTH: usize = 64;
+
+/// `value` the way ids are stored, as unprefixed lowercase hex. Any case is
+/// accepted with or without `0x`, `None` when it holds anything but hex
+/// digits.
+pub fn normalize_hex(value: &str) -> Option<String> {
+    let hex = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+        .to_lowercase();
+    hex.chars().all(|c| c.is_ascii_hexdigit()).then_some(hex)
+}
+
+/// A full 32 bytes id the way it is stored.
+pub fn normalize_id(value: &str) -> Result<String> {
+    normalize_hex(value)
+        .filter(|hex| hex.len() == ID_LENGTH)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a 32 bytes hex id", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_and_case_are_dropped() {
+        assert_eq!(normalize_hex("0xAbC1"), Some("abc1".to_string()));
+        assert_eq!(normalize_hex("0XABC1"), Some("abc1".to_string()));
+        assert_eq!(normalize_hex("abc1"), Some("abc1".to_string()));
+        assert_eq!(normalize_hex("0x"), Some("".to_string()));
+    }
+
+    #[test]
+    fn only_hex_digits_are_accepted() {
+        assert_eq!(normalize_hex("0xabcg"), None);
+        assert_eq!(normalize_hex("0x0x12"), None);
+        assert_eq!(normalize_hex(" 12"), None);
+    }
+
+    #[test]
+    fn ids_are_32_bytes() {
+        let id = "ab".repeat(32);
+        assert_eq!(
+            normalize_id(&format!("0x{}", id.to_uppercase())).unwrap(),
+            id
+        );
+        assert!(normalize_id(&id[2..]).is_err());
+        assert!(normalize_id(&format!("{}00", id)).is_err());
+        assert_eq!(
+            normalize_id("0x12").unwrap_err().to_string(),
+            "0x12 is not a 32 bytes hex id"
+        );
+    }
+}
@@ -7,13 +7,32 @@ use diesel::{
 extern crate derive_builder;
 
 pub mod account;
+pub mod address_transaction;
+pub mod asset_holder;
 pub mod assets;
+pub mod balance;
 pub mod block;
 pub mod call;
+pub mod chain_stats;
 pub mod coinbase;
+pub mod consensus_parameters;
 pub mod contract;
+pub mod contract_abi;
+pub mod contract_balance;
+pub mod feed;
+pub mod id;
+pub mod message;
+pub mod notify;
+pub mod pagination;
+pub mod predicate;
 pub mod schema;
+pub mod script;
+pub mod search;
+pub mod token_transfer;
 pub mod transaction;
+pub mod transaction_failure;
+pub mod tx_input;
+pub mod tx_output;
 
 pub type PgSql = ConnectionManager<PgConnection>;
 pub type PgSqlPool = Pool<PgSql>;
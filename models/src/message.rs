@@ -0,0 +1,232 @@
+use anyhow::Result;
+use std::time::SystemTime;
+
+use diesel::{
+    insert_into, sql_query,
+    sql_types::{BigInt, Nullable, Text},
+    update, BoolExpressionMethods, ExpressionMethods, Insertable, OptionalExtension, PgConnection,
+    QueryDsl, Queryable, QueryableByName, RunQueryDsl, Selectable,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pagination::{Page, UtxoCursor},
+    schema::{message_relays, messages},
+};
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::MessageDirection"]
+#[serde(rename_all = "snake_case")]
+pub enum MessageDirection {
+    /// L1 -> L2, spent by a message input.
+    Deposit,
+    /// L2 -> L1, emitted by a `MessageOut` receipt.
+    Withdrawal,
+}
+
+/// A bridge message. Deposits and withdrawals share the table so the bridge
+/// activity of an address is a single lookup on `sender` or `recipient`.
+#[derive(
+    Insertable, Queryable, QueryableByName, Selectable, Debug, Clone, Serialize, Deserialize,
+)]
+#[diesel(table_name = messages)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Message {
+    pub nonce: String,
+    pub message_id: String,
+    pub direction: MessageDirection,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: i64,
+    pub data: String,
+    /// The da height of the block the message was seen in, not the one a
+    /// deposit was relayed at.
+    pub block_da_height: i64,
+    /// The transaction spending a deposit or emitting a withdrawal.
+    pub transaction_id: String,
+    /// The message input of a deposit.
+    pub input_index: Option<i64>,
+    /// The `MessageOut` receipt of a withdrawal.
+    pub receipt_index: Option<i64>,
+    pub height: i64,
+    pub timestamp: i64,
+    /// The da height a deposit was relayed at, when the node was seen
+    /// holding it unspent.
+    pub da_height: Option<i64>,
+}
+
+pub fn batch_insert_messages(
+    connection: &mut PgConnection,
+    records: &Vec<Message>,
+) -> Result<usize> {
+    insert_into(messages::table)
+        .values(records)
+        .on_conflict(messages::nonce)
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The bridge messages an address sent or received below `before`, newest
+/// first.
+pub fn get_messages_by_address(
+    connection: &mut PgConnection,
+    address: &str,
+    before: Option<UtxoCursor>,
+    limit: i64,
+) -> Result<Page<Message, UtxoCursor>> {
+    // one index scan per side, an `or` of both would read every message of
+    // the address before sorting
+    sql_query(
+        r#"
+        SELECT * FROM (
+            (SELECT * FROM messages
+             WHERE sender = $1 AND ($2::bigint IS NULL OR (height, nonce) < ($2, $3))
+             ORDER BY height DESC, nonce DESC LIMIT $4)
+            UNION ALL
+            (SELECT * FROM messages
+             WHERE recipient = $1 AND sender <> $1 AND ($2::bigint IS NULL OR (height, nonce) < ($2, $3))
+             ORDER BY height DESC, nonce DESC LIMIT $4)
+        ) m
+        ORDER BY height DESC, nonce DESC
+        LIMIT $4
+        "#,
+    )
+    .bind::<Text, _>(address)
+    .bind::<Nullable<BigInt>, _>(before.as_ref().map(|c| c.height))
+    .bind::<Nullable<Text>, _>(before.map(|c| c.utxo_id))
+    .bind::<BigInt, _>(limit + 1)
+    .load::<Message>(connection)
+    .map(|rows| {
+        Page::from_rows(rows, limit, |m: &Message| UtxoCursor {
+            height: m.height,
+            utxo_id: m.nonce.clone(),
+        })
+    })
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Remembers the da height of messages the node holds unspent, the spent
+/// ones no longer say when they were relayed.
+pub fn batch_insert_message_relays(
+    connection: &mut PgConnection,
+    relays: &[(String, i64)],
+) -> Result<usize> {
+    insert_into(message_relays::table)
+        .values(
+            relays
+                .iter()
+                .map(|(nonce, da_height)| {
+                    (
+                        message_relays::nonce.eq(nonce),
+                        message_relays::da_height.eq(da_height),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )
+        .on_conflict(message_relays::nonce)
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Sets the relay height of the indexed deposits a relay was remembered
+/// for and forgets those relays, returns the deposits updated.
+pub fn apply_message_relays(connection: &mut PgConnection) -> Result<usize> {
+    sql_query(
+        r#"
+        WITH applied AS (
+            DELETE FROM message_relays r
+            USING messages m
+            WHERE m.nonce = r.nonce
+            RETURNING r.nonce, r.da_height
+        )
+        UPDATE messages m SET da_height = a.da_height
+        FROM applied a
+        WHERE m.nonce = a.nonce
+        "#,
+    )
+    .execute(connection)
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The cached proof of a withdrawal, relative to the block at
+/// `proof_commit_height`. Only a proof against a commit block finalized on
+/// L1 can be relayed.
+#[derive(Queryable, Debug, Clone, Serialize, Deserialize)]
+pub struct MessageProof {
+    pub nonce: String,
+    pub transaction_id: String,
+    pub proof: Option<serde_json::Value>,
+    pub proof_commit_height: Option<i64>,
+    pub proof_fetched_at: Option<SystemTime>,
+    pub proof_finalized: bool,
+}
+
+/// Withdrawals emitted below `max_height` with no proof yet, as
+/// `(transaction_id, nonce)`. With `stale_below`, the proofs against a
+/// commit block that is not finalized or below it are returned too.
+pub fn get_unproven_withdrawals(
+    connection: &mut PgConnection,
+    max_height: i64,
+    stale_below: Option<i64>,
+    limit: i64,
+) -> Result<Vec<(String, String)>> {
+    let mut query = messages::table
+        .select((messages::transaction_id, messages::nonce))
+        .filter(messages::direction.eq(MessageDirection::Withdrawal))
+        .filter(messages::height.lt(max_height))
+        .into_boxed();
+    query = match stale_below {
+        Some(stale_below) => query.filter(
+            messages::proof_finalized
+                .eq(false)
+                .or(messages::proof_commit_height.lt(stale_below)),
+        ),
+        None => query.filter(messages::proof.is_null()),
+    };
+    query
+        .order(messages::height.asc())
+        .limit(limit)
+        .load::<(String, String)>(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn update_message_proof(
+    connection: &mut PgConnection,
+    nonce: &str,
+    proof: &serde_json::Value,
+    commit_height: i64,
+    finalized: bool,
+) -> Result<usize> {
+    update(messages::table.filter(messages::nonce.eq(nonce)))
+        .set((
+            messages::proof.eq(proof),
+            messages::proof_commit_height.eq(commit_height),
+            messages::proof_fetched_at.eq(SystemTime::now()),
+            messages::proof_finalized.eq(finalized),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_message_proof(
+    connection: &mut PgConnection,
+    nonce: &str,
+) -> Result<Option<MessageProof>> {
+    messages::table
+        .select((
+            messages::nonce,
+            messages::transaction_id,
+            messages::proof,
+            messages::proof_commit_height,
+            messages::proof_fetched_at,
+            messages::proof_finalized,
+        ))
+        .filter(messages::nonce.eq(nonce))
+        .filter(messages::direction.eq(MessageDirection::Withdrawal))
+        .first::<MessageProof>(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use diesel::{sql_types::Text, PgConnection, RunQueryDsl};
+use futures::{channel::mpsc, stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::{AsyncMessage, NoTls};
+
+use crate::block::Block;
+
+/// Channel notified once per committed block.
+pub const BLOCK_CHANNEL: &str = "fuelscan_blocks";
+
+/// Payload of a `fuelscan_blocks` notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockNotification {
+    pub height: i64,
+    pub id: String,
+    pub transaction_count: i64,
+}
+
+impl From<&Block> for BlockNotification {
+    fn from(block: &Block) -> Self {
+        Self {
+            height: block.height,
+            id: block.id.clone(),
+            transaction_count: block.transaction_count,
+        }
+    }
+}
+
+/// Queues the notification of a block. Postgres delivers it when the
+/// surrounding transaction commits and drops it on rollback, so it must run
+/// in the transaction writing the block.
+pub fn notify_block(connection: &mut PgConnection, notification: &BlockNotification) -> Result<()> {
+    diesel::sql_query("select pg_notify($1, $2)")
+        .bind::<Text, _>(BLOCK_CHANNEL)
+        .bind::<Text, _>(serde_json::to_string(notification)?)
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    Ok(())
+}
+
+/// Receives the blocks committed by the indexer, for services that would
+/// otherwise poll `blocks`. Notifications sent while not listening are lost,
+/// a consumer catches up from `blocks` after (re)connecting.
+pub struct BlockListener {
+    // dropping the client closes the connection
+    _client: tokio_postgres::Client,
+    notifications: mpsc::UnboundedReceiver<BlockNotification>,
+}
+
+impl BlockListener {
+    /// Connects to `database_url` and listens on [`BLOCK_CHANNEL`], must be
+    /// called within a tokio runtime.
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let (client, mut connection) = tokio_postgres::connect(database_url, NoTls).await?;
+
+        let (tx, notifications) = mpsc::unbounded();
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = messages.next().await {
+                let AsyncMessage::Notification(notification) = message else {
+                    continue;
+                };
+                if notification.channel() != BLOCK_CHANNEL {
+                    continue;
+                }
+                match serde_json::from_str(notification.payload()) {
+                    Ok(block) => {
+                        if tx.unbounded_send(block).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => tracing::warn!("invalid block notification: {}", e),
+                }
+            }
+        });
+
+        client
+            .batch_execute(&format!("listen {}", BLOCK_CHANNEL))
+            .await?;
+
+        Ok(Self {
+            _client: client,
+            notifications,
+        })
+    }
+
+    /// The next committed block, `None` once the connection is lost.
+    pub async fn recv(&mut self) -> Option<BlockNotification> {
+        self.notifications.next().await
+    }
+}
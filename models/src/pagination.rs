@@ -0,0 +1,207 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use diesel::{sql_types::Text, QueryableByName};
+use serde::{Deserialize, Serialize};
+
+/// The most rows a single page may hold.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// A keyset position in chain order, `index` is the position inside the
+/// block at `height` and is 0 for lists of blocks. Pages run newest first
+/// and the next page starts right below the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Cursor {
+    pub height: i64,
+    pub index: i64,
+}
+
+impl Cursor {
+    pub fn new(height: i64, index: i64) -> Self {
+        Cursor { height, index }
+    }
+}
+
+/// `<height>-<index>`, the form the cursor takes in urls.
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.height, self.index)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (height, index) = s
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("invalid cursor {}", s))?;
+        Ok(Cursor {
+            height: height.parse()?,
+            index: index.parse()?,
+        })
+    }
+}
+
+/// The keyset position of a list of utxos or messages, newest first. They
+/// carry no index inside their block so ties are broken by id.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UtxoCursor {
+    pub height: i64,
+    pub utxo_id: String,
+}
+
+impl fmt::Display for UtxoCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.height, self.utxo_id)
+    }
+}
+
+impl FromStr for UtxoCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (height, utxo_id) = s
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("invalid cursor {}", s))?;
+        Ok(UtxoCursor {
+            height: height.parse()?,
+            utxo_id: utxo_id.to_string(),
+        })
+    }
+}
+
+/// A slice of a list, `next` is set when more rows follow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T, C = Cursor> {
+    pub items: Vec<T>,
+    pub next: Option<C>,
+}
+
+impl<T, C> Page<T, C> {
+    /// Builds a page from `limit + 1` fetched rows, the extra row only tells
+    /// whether another page follows.
+    pub fn from_rows(mut rows: Vec<T>, limit: i64, cursor: impl Fn(&T) -> C) -> Self {
+        let next = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(cursor)
+        } else {
+            None
+        };
+        Page { items: rows, next }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U, C> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next: self.next,
+        }
+    }
+}
+
+/// Clamps a requested page size to `1..=MAX_PAGE_SIZE`.
+pub fn page_size(limit: i64) -> i64 {
+    limit.clamp(1, MAX_PAGE_SIZE)
+}
+
+/// A row loaded for one of several parents at once, `parent` is the key it
+/// was partitioned by.
+#[derive(QueryableByName)]
+pub(crate) struct ParentRow<T> {
+    #[diesel(sql_type = Text)]
+    pub parent: String,
+    #[diesel(embed)]
+    pub row: T,
+}
+
+/// Splits the rows of several parents, at most `limit + 1` each in page
+/// order, into a page per parent. Parents without rows have no entry.
+pub(crate) fn pages_by_parent<T, C>(
+    rows: Vec<ParentRow<T>>,
+    limit: i64,
+    cursor: impl Fn(&T) -> C,
+) -> BTreeMap<String, Page<T, C>> {
+    let mut grouped = BTreeMap::<String, Vec<T>>::new();
+    for row in rows {
+        grouped.entry(row.parent).or_default().push(row.row);
+    }
+    grouped
+        .into_iter()
+        .map(|(parent, rows)| (parent, Page::from_rows(rows, limit, &cursor)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_rows_truncates_the_extra_row() {
+        let page = Page::from_rows(vec![5, 4, 3], 2, |r: &i64| Cursor::new(*r, 0));
+        assert_eq!(page.items, vec![5, 4]);
+        assert_eq!(page.next, Some(Cursor::new(4, 0)));
+    }
+
+    #[test]
+    fn from_rows_without_extra_row_is_the_last_page() {
+        let page = Page::from_rows(vec![5, 4], 2, |r: &i64| Cursor::new(*r, 0));
+        assert_eq!(page.items, vec![5, 4]);
+        assert_eq!(page.next, None);
+
+        let page = Page::from_rows(vec![], 2, |r: &i64| Cursor::new(*r, 0));
+        assert!(page.items.is_empty());
+        assert_eq!(page.next, None);
+    }
+
+    #[test]
+    fn pages_by_parent_pages_every_parent() {
+        let rows = [("a", 3), ("a", 2), ("a", 1), ("b", 7)]
+            .into_iter()
+            .map(|(parent, row)| ParentRow {
+                parent: parent.to_string(),
+                row,
+            })
+            .collect();
+        let pages = pages_by_parent(rows, 2, |r: &i64| *r);
+        assert_eq!(pages["a"].items, vec![3, 2]);
+        assert_eq!(pages["a"].next, Some(2));
+        assert_eq!(pages["b"].items, vec![7]);
+        assert_eq!(pages["b"].next, None);
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = Cursor::new(1200, 3);
+        assert_eq!(cursor.to_string(), "1200-3");
+        assert_eq!("1200-3".parse::<Cursor>().unwrap(), cursor);
+
+        assert!("1200".parse::<Cursor>().is_err());
+        assert!("a-3".parse::<Cursor>().is_err());
+        assert!("1200-".parse::<Cursor>().is_err());
+    }
+
+    #[test]
+    fn utxo_cursor_round_trip() {
+        let cursor = UtxoCursor {
+            height: 1200,
+            utxo_id: "0xab01".to_string(),
+        };
+        assert_eq!(cursor.to_string(), "1200-0xab01");
+        assert_eq!("1200-0xab01".parse::<UtxoCursor>().unwrap(), cursor);
+
+        // only the first dash splits, utxo ids are kept whole
+        let cursor = "7-0xab-01".parse::<UtxoCursor>().unwrap();
+        assert_eq!(cursor.height, 7);
+        assert_eq!(cursor.utxo_id, "0xab-01");
+
+        assert!("0xab01".parse::<UtxoCursor>().is_err());
+    }
+
+    #[test]
+    fn page_size_is_clamped() {
+        assert_eq!(page_size(0), 1);
+        assert_eq!(page_size(-5), 1);
+        assert_eq!(page_size(20), 20);
+        assert_eq!(page_size(MAX_PAGE_SIZE), MAX_PAGE_SIZE);
+        assert_eq!(page_size(MAX_PAGE_SIZE + 1), MAX_PAGE_SIZE);
+    }
+}
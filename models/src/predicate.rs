@@ -0,0 +1,81 @@
+use anyhow::Result;
+
+use diesel::{
+    dsl::sql, insert_into, sql_types::BigInt, sql_types::Varchar, upsert::excluded,
+    ExpressionMethods, Insertable, PgConnection, Queryable, RunQueryDsl, Selectable,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{predicate_spends, predicates};
+
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = predicates)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Predicate {
+    pub predicate_hash: String,
+    pub bytecode_root: String,
+    pub bytecode: String,
+    pub bytecode_length: i64,
+    pub first_transaction_id: String,
+    pub first_height: i64,
+    pub usage_count: i64,
+    pub timestamp: i64,
+}
+
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = predicate_spends)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PredicateSpend {
+    pub transaction_id: String,
+    pub input_index: i64,
+    pub predicate_hash: String,
+    pub utxo_id: Option<String>,
+    pub nonce: Option<String>,
+    pub amount: i64,
+    pub predicate_data: String,
+    pub predicate_gas_used: i64,
+    pub height: i64,
+    pub timestamp: i64,
+}
+
+pub fn batch_insert_predicates(
+    connection: &mut PgConnection,
+    records: &Vec<Predicate>,
+) -> Result<usize> {
+    // blocks are committed out of order, keep the earliest use we have seen.
+    insert_into(predicates::table)
+        .values(records)
+        .on_conflict(predicates::predicate_hash)
+        .do_update()
+        .set((
+            predicates::first_transaction_id.eq(sql::<Varchar>(
+                "CASE WHEN excluded.first_height < predicates.first_height \
+                 THEN excluded.first_transaction_id ELSE predicates.first_transaction_id END",
+            )),
+            predicates::timestamp.eq(sql::<BigInt>(
+                "CASE WHEN excluded.first_height < predicates.first_height \
+                 THEN excluded.timestamp ELSE predicates.timestamp END",
+            )),
+            predicates::first_height.eq(sql::<BigInt>(
+                "LEAST(excluded.first_height, predicates.first_height)",
+            )),
+            predicates::usage_count.eq(predicates::usage_count + excluded(predicates::usage_count)),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn batch_insert_predicate_spends(
+    connection: &mut PgConnection,
+    records: &Vec<PredicateSpend>,
+) -> Result<usize> {
+    insert_into(predicate_spends::table)
+        .values(records)
+        .on_conflict((
+            predicate_spends::transaction_id,
+            predicate_spends::input_index,
+        ))
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
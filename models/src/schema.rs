@@ -5,6 +5,10 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "account_type"))]
     pub struct AccountType;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "address_role"))]
+    pub struct AddressRole;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "asset_status"))]
     pub struct AssetStatus;
@@ -13,6 +17,34 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "call_type"))]
     pub struct CallType;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "failure_kind"))]
+    pub struct FailureKind;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "input_type"))]
+    pub struct InputType;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "message_direction"))]
+    pub struct MessageDirection;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "output_type"))]
+    pub struct OutputType;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "script_category"))]
+    pub struct ScriptCategory;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "stats_granularity"))]
+    pub struct StatsGranularity;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "transfer_type"))]
+    pub struct TransferType;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "tx_status"))]
     pub struct TxStatus;
@@ -40,6 +72,9 @@ diesel::table! {
         decompiled -> Bool,
         inserted_at -> Timestamp,
         updated_at -> Timestamp,
+        fee_paid -> Int8,
+        first_seen_height -> Nullable<Int8>,
+        last_seen_height -> Nullable<Int8>,
     }
 }
 
@@ -66,6 +101,67 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    address_transaction_counts (address) {
+        address -> Varchar,
+        transaction_count -> Int8,
+    }
+}
+
+diesel::table! {
+    address_transaction_counts_pending (height, address) {
+        height -> Int8,
+        address -> Varchar,
+        transaction_count -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::AddressRole;
+
+    address_transactions (address, transaction_id) {
+        address -> Varchar,
+        transaction_id -> Varchar,
+        roles -> Array<Nullable<AddressRole>>,
+        height -> Int8,
+        tx_index -> Int8,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    asset_holder_changes (height, asset_id, owner) {
+        height -> Int8,
+        asset_id -> Varchar,
+        owner -> Varchar,
+        balance -> Numeric,
+    }
+}
+
+diesel::table! {
+    asset_holder_stats (asset_id, height) {
+        asset_id -> Varchar,
+        height -> Int8,
+        holder_count -> Int8,
+        supply -> Numeric,
+        top_10_share -> Numeric,
+        top_100_share -> Numeric,
+        buckets -> Jsonb,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    asset_holders (asset_id, owner) {
+        asset_id -> Varchar,
+        owner -> Varchar,
+        balance -> Numeric,
+        rank -> Nullable<Int8>,
+        updated_height -> Int8,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::AssetStatus;
@@ -100,6 +196,11 @@ diesel::table! {
         timestamp -> Int8,
         transaction_count -> Int8,
         output_message_count -> Int8,
+        event_inbox_root -> Varchar,
+        consensus_parameters_version -> Int8,
+        state_transition_bytecode_version -> Int8,
+        consensus -> Nullable<Json>,
+        producer -> Nullable<Varchar>,
     }
 }
 
@@ -122,19 +223,147 @@ diesel::table! {
         asset_id -> Nullable<Varchar>,
         payload -> Nullable<Text>,
         payload_data -> Nullable<Text>,
-        timestamp -> Nullable<Int8>,
+        timestamp -> Int8,
+        tx_index -> Int8,
+        receipt_index -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::StatsGranularity;
+
+    chain_stats (granularity, bucket) {
+        granularity -> StatsGranularity,
+        bucket -> Int8,
+        block_count -> Int8,
+        transaction_count -> Int8,
+        active_addresses -> Int8,
+        new_contracts -> Int8,
+        gas_used -> Numeric,
+        fees -> Numeric,
+        first_timestamp -> Int8,
+        last_timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::StatsGranularity;
+
+    chain_stats_addresses (granularity, bucket, address) {
+        granularity -> StatsGranularity,
+        bucket -> Int8,
+        address -> Varchar,
+    }
+}
+
+diesel::table! {
+    chain_stats_pending (height) {
+        height -> Int8,
+        timestamp -> Int8,
+        transaction_count -> Int8,
+        new_contracts -> Int8,
+        gas_used -> Numeric,
+        fees -> Numeric,
+        addresses -> Array<Nullable<Text>>,
     }
 }
 
 diesel::table! {
     coinbases (id) {
         id -> Varchar,
-        height -> Nullable<Int8>,
-        da_height -> Nullable<Int8>,
+        height -> Int8,
+        da_height -> Int8,
         block_hash -> Varchar,
         amount -> Nullable<Int8>,
         coinbase -> Nullable<Varchar>,
         timestamp -> Nullable<Int8>,
+        tx_index -> Int8,
+    }
+}
+
+diesel::table! {
+    consensus_parameters (version) {
+        version -> Int8,
+        parameters -> Json,
+        checksum -> Nullable<Varchar>,
+        transaction_id -> Nullable<Varchar>,
+        height -> Nullable<Int8>,
+        timestamp -> Nullable<Int8>,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    contract_abis (contract_hash) {
+        contract_hash -> Varchar,
+        abi -> Json,
+        inserted_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    contract_balances (contract_hash, asset_id) {
+        contract_hash -> Varchar,
+        asset_id -> Varchar,
+        amount -> Numeric,
+        block_height -> Int8,
+        inserted_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    indexed_height (id) {
+        id -> Int4,
+        height -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ScriptCategory;
+
+    known_scripts (script_hash) {
+        script_hash -> Varchar,
+        category -> ScriptCategory,
+        name -> Varchar,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    message_relays (nonce) {
+        nonce -> Varchar,
+        da_height -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::MessageDirection;
+
+    messages (nonce) {
+        nonce -> Varchar,
+        message_id -> Varchar,
+        direction -> MessageDirection,
+        sender -> Varchar,
+        recipient -> Varchar,
+        amount -> Int8,
+        data -> Text,
+        block_da_height -> Int8,
+        transaction_id -> Varchar,
+        height -> Int8,
+        timestamp -> Int8,
+        proof -> Nullable<Json>,
+        proof_commit_height -> Nullable<Int8>,
+        proof_fetched_at -> Nullable<Timestamp>,
+        input_index -> Nullable<Int8>,
+        receipt_index -> Nullable<Int8>,
+        proof_finalized -> Bool,
+        da_height -> Nullable<Int8>,
     }
 }
 
@@ -145,6 +374,46 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    predicate_spends (transaction_id, input_index) {
+        transaction_id -> Varchar,
+        input_index -> Int8,
+        predicate_hash -> Varchar,
+        utxo_id -> Nullable<Varchar>,
+        nonce -> Nullable<Varchar>,
+        amount -> Int8,
+        predicate_data -> Text,
+        predicate_gas_used -> Int8,
+        height -> Int8,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    predicates (predicate_hash) {
+        predicate_hash -> Varchar,
+        bytecode_root -> Varchar,
+        bytecode -> Text,
+        bytecode_length -> Int8,
+        first_transaction_id -> Varchar,
+        first_height -> Int8,
+        usage_count -> Int8,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    scripts (script_hash) {
+        script_hash -> Varchar,
+        bytecode -> Text,
+        bytecode_length -> Int8,
+        first_transaction_id -> Varchar,
+        first_height -> Int8,
+        usage_count -> Int8,
+        timestamp -> Int8,
+    }
+}
+
 diesel::table! {
     smart_contracts (contract_hash) {
         contract_hash -> Varchar,
@@ -157,10 +426,61 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    state_transition_bytecodes (version) {
+        version -> Int8,
+        bytecode_root -> Varchar,
+        transaction_id -> Varchar,
+        height -> Int8,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TransferType;
+
+    token_transfers (transaction_id, transfer_index) {
+        transaction_id -> Varchar,
+        transfer_index -> Int8,
+        transfer_type -> TransferType,
+        output_index -> Nullable<Int8>,
+        receipt_index -> Nullable<Int8>,
+        asset_id -> Varchar,
+        from_address -> Nullable<Varchar>,
+        to_address -> Nullable<Varchar>,
+        amount -> Int8,
+        height -> Int8,
+        tx_index -> Int8,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::FailureKind;
+
+    transaction_failures (transaction_id) {
+        transaction_id -> Varchar,
+        kind -> FailureKind,
+        receipt_index -> Nullable<Int8>,
+        contract_id -> Nullable<Varchar>,
+        panic_reason -> Nullable<Varchar>,
+        revert_code -> Nullable<Varchar>,
+        signal -> Nullable<Varchar>,
+        logged_error -> Nullable<Json>,
+        message -> Text,
+        node_reason -> Text,
+        height -> Int8,
+        timestamp -> Int8,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::TxType;
     use super::sql_types::TxStatus;
+    use super::sql_types::ScriptCategory;
 
     transactions (id) {
         id -> Varchar,
@@ -178,6 +498,51 @@ diesel::table! {
         input -> Nullable<Json>,
         output -> Nullable<Json>,
         receipts -> Nullable<Json>,
+        fee -> Int8,
+        tx_index -> Int8,
+        script_hash -> Nullable<Varchar>,
+        script_category -> Nullable<ScriptCategory>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::InputType;
+
+    tx_inputs (transaction_id, input_index) {
+        transaction_id -> Varchar,
+        input_index -> Int8,
+        input_type -> InputType,
+        utxo_id -> Nullable<Varchar>,
+        owner -> Nullable<Varchar>,
+        sender -> Nullable<Varchar>,
+        asset_id -> Nullable<Varchar>,
+        amount -> Nullable<Int8>,
+        tx_pointer_height -> Nullable<Int8>,
+        tx_pointer_index -> Nullable<Int8>,
+        contract_id -> Nullable<Varchar>,
+        nonce -> Nullable<Varchar>,
+        height -> Int8,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::OutputType;
+
+    tx_outputs (transaction_id, output_index) {
+        transaction_id -> Varchar,
+        output_index -> Int8,
+        output_type -> OutputType,
+        utxo_id -> Nullable<Varchar>,
+        to_address -> Nullable<Varchar>,
+        asset_id -> Nullable<Varchar>,
+        amount -> Nullable<Int8>,
+        input_index -> Nullable<Int8>,
+        contract_id -> Nullable<Varchar>,
+        height -> Int8,
+        timestamp -> Int8,
     }
 }
 
@@ -185,11 +550,35 @@ diesel::allow_tables_to_appear_in_same_query!(
     accounts,
     address_coin_balances,
     address_coin_balances_daily,
+    address_transaction_counts,
+    address_transaction_counts_pending,
+    address_transactions,
+    asset_holder_changes,
+    asset_holder_stats,
+    asset_holders,
     assets,
     blocks,
     calls,
+    chain_stats,
+    chain_stats_addresses,
+    chain_stats_pending,
     coinbases,
+    consensus_parameters,
+    contract_abis,
+    contract_balances,
+    indexed_height,
+    known_scripts,
+    message_relays,
+    messages,
     nfts,
+    predicate_spends,
+    predicates,
+    scripts,
     smart_contracts,
+    state_transition_bytecodes,
+    token_transfers,
+    transaction_failures,
     transactions,
+    tx_inputs,
+    tx_outputs,
 );
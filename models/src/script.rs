@@ -0,0 +1,130 @@
+use anyhow::Result;
+use std::{collections::HashMap, time::SystemTime};
+
+use diesel::{
+    dsl::{not, sql},
+    insert_into,
+    sql_types::{BigInt, Varchar},
+    upsert::excluded,
+    ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, RunQueryDsl, Selectable,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{known_scripts, scripts};
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::ScriptCategory"]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptCategory {
+    /// Moves coins without running any code, e.g. the SDK transfer script.
+    Transfer,
+    ContractCall,
+    /// Calls more than one contract, e.g. an SDK multicall.
+    Multicall,
+    /// A transfer whose coins are all owned by predicates.
+    PredicateSpend,
+    Custom,
+}
+
+/// A script bytecode labeled by hand, it takes precedence over the category
+/// guessed from the instructions of the script.
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = known_scripts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct KnownScript {
+    pub script_hash: String,
+    pub category: ScriptCategory,
+    pub name: String,
+    pub inserted_at: SystemTime,
+}
+
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = scripts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Script {
+    pub script_hash: String,
+    pub bytecode: String,
+    pub bytecode_length: i64,
+    pub first_transaction_id: String,
+    pub first_height: i64,
+    pub usage_count: i64,
+    pub timestamp: i64,
+}
+
+#[derive(Queryable, Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptUsage {
+    pub script_hash: String,
+    pub bytecode_length: i64,
+    pub first_transaction_id: String,
+    pub usage_count: i64,
+}
+
+/// Adds the uses of a block to the usage counts. The first transaction of a
+/// script is the one at the lowest height, whichever block came in first.
+pub fn batch_insert_scripts(connection: &mut PgConnection, records: &Vec<Script>) -> Result<usize> {
+    insert_into(scripts::table)
+        .values(records)
+        .on_conflict(scripts::script_hash)
+        .do_update()
+        .set((
+            scripts::first_transaction_id.eq(sql::<Varchar>(
+                "CASE WHEN excluded.first_height < scripts.first_height \
+                 THEN excluded.first_transaction_id ELSE scripts.first_transaction_id END",
+            )),
+            scripts::timestamp.eq(sql::<BigInt>(
+                "CASE WHEN excluded.first_height < scripts.first_height \
+                 THEN excluded.timestamp ELSE scripts.timestamp END",
+            )),
+            scripts::first_height.eq(sql::<BigInt>(
+                "LEAST(excluded.first_height, scripts.first_height)",
+            )),
+            scripts::usage_count.eq(scripts::usage_count + excluded(scripts::usage_count)),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn insert_known_script(connection: &mut PgConnection, record: &KnownScript) -> Result<usize> {
+    insert_into(known_scripts::table)
+        .values(record)
+        .on_conflict(known_scripts::script_hash)
+        .do_update()
+        .set((
+            known_scripts::category.eq(excluded(known_scripts::category)),
+            known_scripts::name.eq(excluded(known_scripts::name)),
+        ))
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The labeled categories of `hashes`, keyed by script hash.
+pub fn get_known_scripts(
+    connection: &mut PgConnection,
+    hashes: &[String],
+) -> Result<HashMap<String, ScriptCategory>> {
+    known_scripts::table
+        .filter(known_scripts::script_hash.eq_any(hashes))
+        .select((known_scripts::script_hash, known_scripts::category))
+        .load::<(String, ScriptCategory)>(connection)
+        .map(|known| known.into_iter().collect())
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The most used scripts nobody has labeled yet.
+pub fn get_unknown_scripts(connection: &mut PgConnection, limit: i64) -> Result<Vec<ScriptUsage>> {
+    scripts::table
+        .filter(not(scripts::script_hash.eq_any(
+            known_scripts::table.select(known_scripts::script_hash),
+        )))
+        .order(scripts::usage_count.desc())
+        .limit(limit)
+        .select((
+            scripts::script_hash,
+            scripts::bytecode_length,
+            scripts::first_transaction_id,
+            scripts::usage_count,
+        ))
+        .load::<ScriptUsage>(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
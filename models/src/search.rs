@@ -0,0 +1,188 @@
+use anyhow::Result;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Text},
+    ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, QueryableByName, RunQueryDsl,
+    TextExpressionMethods,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    id::{normalize_hex, ID_LENGTH},
+    schema::{accounts, assets, blocks, coinbases, smart_contracts, transactions},
+};
+
+/// Shortest partial hash searched for, shorter prefixes match too much to be
+/// useful.
+pub const MIN_PREFIX: usize = 4;
+
+/// Hex length of a utxo id, an id and the 2 bytes output index.
+const UTXO_ID_LENGTH: usize = ID_LENGTH + 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Block,
+    Transaction,
+    Contract,
+    Address,
+    Asset,
+    Utxo,
+}
+
+/// An indexed entity matching a search, `height` is set for blocks and
+/// transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    pub id: String,
+    pub height: Option<i64>,
+}
+
+impl SearchResult {
+    fn new(kind: SearchKind, id: String, height: Option<i64>) -> Self {
+        SearchResult { kind, id, height }
+    }
+}
+
+#[derive(QueryableByName)]
+struct AssetId {
+    #[diesel(sql_type = Text)]
+    asset_id: String,
+}
+
+/// The distinct assets starting with `pattern`, walking the index one asset
+/// at a time instead of reading every transfer of a busy asset.
+fn search_assets(connection: &mut PgConnection, pattern: &str, limit: i64) -> Result<Vec<String>> {
+    sql_query(
+        r#"
+        WITH RECURSIVE found(asset_id, n) AS (
+            (SELECT asset_id::text, 1 FROM token_transfers
+             WHERE asset_id LIKE $1 ORDER BY asset_id USING ~<~ LIMIT 1)
+            UNION ALL
+            SELECT (SELECT t.asset_id::text FROM token_transfers t
+                    WHERE t.asset_id LIKE $1 AND t.asset_id ~>~ found.asset_id
+                    ORDER BY t.asset_id USING ~<~ LIMIT 1),
+                   n + 1
+            FROM found
+            WHERE found.asset_id IS NOT NULL AND n < $2
+        )
+        SELECT asset_id FROM found WHERE asset_id IS NOT NULL
+        "#,
+    )
+    .bind::<Text, _>(pattern)
+    .bind::<BigInt, _>(limit)
+    .load::<AssetId>(connection)
+    .map(|rows| rows.into_iter().map(|r| r.asset_id).collect())
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Everything `query` may identify: a block height, or a full or partial
+/// hash of a block, transaction, contract, address, asset or utxo. The same
+/// hash can be several kinds, all of them are returned, at most `limit` of
+/// each kind. Hashes are matched with or without `0x` in any case.
+pub fn search(connection: &mut PgConnection, query: &str, limit: i64) -> Result<Vec<SearchResult>> {
+    let query = query.trim();
+    let mut results = vec![];
+
+    if let Ok(height) = query.parse::<i64>() {
+        results.extend(
+            blocks::table
+                .filter(blocks::height.eq(height))
+                .select(blocks::id)
+                .first::<String>(connection)
+                .optional()?
+                .into_iter()
+                .map(|id| SearchResult::new(SearchKind::Block, id, Some(height))),
+        );
+    }
+
+    let Some(hex) =
+        normalize_hex(query).filter(|hex| (MIN_PREFIX..=UTXO_ID_LENGTH).contains(&hex.len()))
+    else {
+        return Ok(results);
+    };
+    // hex digits hold no `like` wildcards
+    let pattern = format!("{}%", hex);
+
+    if hex.len() <= ID_LENGTH {
+        let found = blocks::table
+            .filter(blocks::id.like(&pattern))
+            .order(blocks::id.asc())
+            .limit(limit)
+            .select((blocks::id, blocks::height))
+            .load::<(String, i64)>(connection)?;
+        results.extend(
+            found
+                .into_iter()
+                .map(|(id, height)| SearchResult::new(SearchKind::Block, id, Some(height))),
+        );
+
+        let mut found = transactions::table
+            .filter(transactions::id.like(&pattern))
+            .order(transactions::id.asc())
+            .limit(limit)
+            .select((transactions::id, transactions::height))
+            .load::<(String, i64)>(connection)?;
+        // mint transactions are only kept in `coinbases`
+        found.extend(
+            coinbases::table
+                .filter(coinbases::id.like(&pattern))
+                .order(coinbases::id.asc())
+                .limit(limit)
+                .select((coinbases::id, coinbases::height))
+                .load::<(String, i64)>(connection)?,
+        );
+        found.sort();
+        found.truncate(limit as usize);
+        results.extend(
+            found
+                .into_iter()
+                .map(|(id, height)| SearchResult::new(SearchKind::Transaction, id, Some(height))),
+        );
+
+        let found = smart_contracts::table
+            .filter(smart_contracts::contract_hash.like(&pattern))
+            .order(smart_contracts::contract_hash.asc())
+            .limit(limit)
+            .select(smart_contracts::contract_hash)
+            .load::<String>(connection)?;
+        results.extend(
+            found
+                .into_iter()
+                .map(|id| SearchResult::new(SearchKind::Contract, id, None)),
+        );
+
+        let found = accounts::table
+            .filter(accounts::account_hash.like(&pattern))
+            .order(accounts::account_hash.asc())
+            .limit(limit)
+            .select(accounts::account_hash)
+            .load::<String>(connection)?;
+        results.extend(
+            found
+                .into_iter()
+                .map(|id| SearchResult::new(SearchKind::Address, id, None)),
+        );
+
+        results.extend(
+            search_assets(connection, &pattern, limit)?
+                .into_iter()
+                .map(|id| SearchResult::new(SearchKind::Asset, id, None)),
+        );
+    }
+
+    let found = assets::table
+        .filter(assets::assets_utxo_id.like(&pattern))
+        .order(assets::assets_utxo_id.asc())
+        .limit(limit)
+        .select(assets::assets_utxo_id)
+        .load::<String>(connection)?;
+    results.extend(
+        found
+            .into_iter()
+            .map(|id| SearchResult::new(SearchKind::Utxo, id, None)),
+    );
+
+    Ok(results)
+}
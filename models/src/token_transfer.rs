@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use diesel::{
+    insert_into, sql_query,
+    sql_types::{Array, BigInt, Text},
+    BoolExpressionMethods, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable,
+    QueryableByName, RunQueryDsl, Selectable, SelectableHelper,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{pagination::ParentRow, schema::token_transfers};
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::TransferType"]
+#[serde(rename_all = "snake_case")]
+pub enum TransferType {
+    /// A coin output owned by someone else than the sender.
+    Coin,
+    /// A `Transfer` receipt, to a contract.
+    Transfer,
+    /// A `TransferOut` receipt, from a contract to an address.
+    TransferOut,
+    Mint,
+    Burn,
+}
+
+/// An amount of `asset_id` moving from one owner to another. A mint has no
+/// `from_address` and a burn no `to_address`.
+#[derive(
+    Insertable, Queryable, QueryableByName, Selectable, Debug, Clone, Serialize, Deserialize,
+)]
+#[diesel(table_name = token_transfers)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TokenTransfer {
+    pub transaction_id: String,
+    /// The position of the transfer in its transaction.
+    pub transfer_index: i64,
+    pub transfer_type: TransferType,
+    pub output_index: Option<i64>,
+    pub receipt_index: Option<i64>,
+    pub asset_id: String,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub amount: i64,
+    pub height: i64,
+    pub tx_index: i64,
+    pub timestamp: i64,
+}
+
+pub fn batch_insert_token_transfers(
+    connection: &mut PgConnection,
+    records: &Vec<TokenTransfer>,
+) -> Result<usize> {
+    insert_into(token_transfers::table)
+        .values(records)
+        .on_conflict((
+            token_transfers::transaction_id,
+            token_transfers::transfer_index,
+        ))
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The token transfers of a transaction in order.
+pub fn get_transaction_token_transfers(
+    connection: &mut PgConnection,
+    transaction_id: &str,
+) -> Result<Vec<TokenTransfer>> {
+    token_transfers::table
+        .filter(token_transfers::transaction_id.eq(transaction_id))
+        .order(token_transfers::transfer_index.asc())
+        .select(TokenTransfer::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The token transfers of the transactions in `keys` in order.
+pub fn get_token_transfers_by_transactions(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<TokenTransfer>> {
+    token_transfers::table
+        .filter(token_transfers::transaction_id.eq_any(keys))
+        .order((
+            token_transfers::transaction_id.asc(),
+            token_transfers::transfer_index.asc(),
+        ))
+        .select(TokenTransfer::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The latest transfers from or to an address, newest first.
+pub fn get_address_token_transfers(
+    connection: &mut PgConnection,
+    address: &str,
+    limit: i64,
+) -> Result<Vec<TokenTransfer>> {
+    token_transfers::table
+        .filter(
+            token_transfers::from_address
+                .eq(address)
+                .or(token_transfers::to_address.eq(address)),
+        )
+        .order((
+            token_transfers::height.desc(),
+            token_transfers::tx_index.desc(),
+            token_transfers::transfer_index.desc(),
+        ))
+        .limit(limit)
+        .select(TokenTransfer::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// `get_address_token_transfers` for every address in `addresses` with a
+/// single query, keyed by address. A transfer between two of them is listed
+/// for both.
+pub fn get_token_transfers_by_addresses(
+    connection: &mut PgConnection,
+    addresses: &[String],
+    limit: i64,
+) -> Result<BTreeMap<String, Vec<TokenTransfer>>> {
+    let rows = sql_query(
+        r#"
+        SELECT * FROM (
+            SELECT t.*, p.address AS parent, row_number() OVER (
+                PARTITION BY p.address
+                ORDER BY t.height DESC, t.tx_index DESC, t.transfer_index DESC
+            ) AS rn
+            FROM unnest($1::text[]) AS p(address)
+            JOIN token_transfers t ON t.from_address = p.address OR t.to_address = p.address
+        ) t
+        WHERE rn <= $2
+        ORDER BY parent, rn
+        "#,
+    )
+    .bind::<Array<Text>, _>(addresses)
+    .bind::<BigInt, _>(limit)
+    .load::<ParentRow<TokenTransfer>>(connection)
+    .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+    let mut transfers = BTreeMap::<String, Vec<TokenTransfer>>::new();
+    for row in rows {
+        transfers.entry(row.parent).or_default().push(row.row);
+    }
+    Ok(transfers)
+}
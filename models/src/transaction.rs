@@ -1,10 +1,19 @@
 use anyhow::Result;
 
-use diesel::{insert_into, Insertable, PgConnection, RunQueryDsl};
+use diesel::{
+    insert_into, sql_query,
+    sql_types::{BigInt, Varchar},
+    BoolExpressionMethods, ExpressionMethods, Insertable, OptionalExtension, PgConnection,
+    QueryDsl, Queryable, QueryableByName, RunQueryDsl, Selectable, SelectableHelper,
+};
 use diesel_derive_enum::DbEnum;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::transactions;
+use crate::{
+    pagination::{Cursor, Page},
+    schema::transactions,
+    script::ScriptCategory,
+};
 
 #[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 #[ExistingTypePath = "crate::schema::sql_types::TxStatus"]
@@ -20,26 +29,35 @@ pub enum TxStatus {
 pub enum TxType {
     Call,
     Deploy,
+    Upgrade,
+    Upload,
 }
 
-#[derive(Insertable, Debug, Clone, Serialize, Deserialize)]
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
 #[diesel(table_name = transactions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Transaction {
     pub id: String,
     pub height: i64,
+    /// The position of the transaction in its block.
+    pub tx_index: i64,
     pub da_height: i64,
     pub block_hash: String,
     pub tx_type: Option<TxType>,
     pub gas_limit: i64,
     pub gas_price: i64,
     pub gas_used: i64,
+    pub fee: i64,
     pub timestamp: i64,
+    #[diesel(deserialize_as = String)]
     pub sender: Option<String>,
     pub status: TxStatus,
     pub reason: String,
     pub input: Option<serde_json::Value>,
     pub output: Option<serde_json::Value>,
     pub receipts: Option<serde_json::Value>,
+    pub script_hash: Option<String>,
+    pub script_category: Option<ScriptCategory>,
 }
 
 pub fn batch_insert_transactions(
@@ -53,3 +71,123 @@ pub fn batch_insert_transactions(
         .execute(connection)
         .map_err(|e| anyhow::anyhow!(e.to_string()))
 }
+
+#[derive(QueryableByName)]
+struct TransactionId {
+    #[diesel(sql_type = Varchar)]
+    id: String,
+}
+
+// mint transactions are only kept in `coinbases`
+const BLOCK_TRANSACTIONS: &str = "SELECT id, tx_index FROM transactions WHERE height = $1 \
+     UNION ALL SELECT id, tx_index FROM coinbases WHERE height = $1";
+
+/// The ids of the transactions of a block in chain order.
+pub fn get_block_transaction_ids(
+    connection: &mut PgConnection,
+    height: i64,
+) -> Result<Vec<String>> {
+    sql_query(format!("{} ORDER BY tx_index", BLOCK_TRANSACTIONS))
+        .bind::<BigInt, _>(height)
+        .load::<TransactionId>(connection)
+        .map(|ids| ids.into_iter().map(|t| t.id).collect())
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Resolves the block height and index of a `TxPointer` to a transaction id.
+pub fn get_transaction_id_by_pointer(
+    connection: &mut PgConnection,
+    height: i64,
+    tx_index: i64,
+) -> Result<Option<String>> {
+    sql_query(format!(
+        "SELECT id FROM ({}) t WHERE tx_index = $2",
+        BLOCK_TRANSACTIONS
+    ))
+    .bind::<BigInt, _>(height)
+    .bind::<BigInt, _>(tx_index)
+    .get_result::<TransactionId>(connection)
+    .optional()
+    .map(|t| t.map(|t| t.id))
+    .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_transaction(connection: &mut PgConnection, id: &str) -> Result<Option<Transaction>> {
+    transactions::table
+        .find(id)
+        .select(Transaction::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The transactions of a block in chain order, the mint transaction is
+/// read with `get_block_coinbase`.
+pub fn get_block_transactions(
+    connection: &mut PgConnection,
+    height: i64,
+) -> Result<Vec<Transaction>> {
+    transactions::table
+        .filter(transactions::height.eq(height))
+        .order(transactions::tx_index.asc())
+        .select(Transaction::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+fn transaction_cursor(tx: &Transaction) -> Cursor {
+    Cursor::new(tx.height, tx.tx_index)
+}
+
+/// The latest transactions below `before`, newest first.
+pub fn get_transactions(
+    connection: &mut PgConnection,
+    before: Option<Cursor>,
+    limit: i64,
+) -> Result<Page<Transaction>> {
+    let mut query = transactions::table
+        .select(Transaction::as_select())
+        .into_boxed();
+    if let Some(before) = before {
+        query = query.filter(
+            transactions::height
+                .lt(before.height)
+                .or(transactions::height
+                    .eq(before.height)
+                    .and(transactions::tx_index.lt(before.index))),
+        );
+    }
+
+    query
+        .order((transactions::height.desc(), transactions::tx_index.desc()))
+        .limit(limit + 1)
+        .load(connection)
+        .map(|rows| Page::from_rows(rows, limit, transaction_cursor))
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The transactions of the blocks at `keys` in chain order.
+pub fn get_transactions_by_heights(
+    connection: &mut PgConnection,
+    keys: &[i64],
+) -> Result<Vec<Transaction>> {
+    transactions::table
+        .filter(transactions::height.eq_any(keys))
+        .order((transactions::height.asc(), transactions::tx_index.asc()))
+        .select(Transaction::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The transactions with the ids in `keys`, for batched loading.
+pub fn get_transactions_by_ids(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<Transaction>> {
+    transactions::table
+        .filter(transactions::id.eq_any(keys))
+        .order(transactions::id.asc())
+        .select(Transaction::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+
+use diesel::{
+    insert_into, ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl,
+    Queryable, RunQueryDsl, Selectable, SelectableHelper,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::transaction_failures;
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::FailureKind"]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    Panic,
+    Revert,
+    /// Failed without a panic or revert receipt.
+    Unknown,
+}
+
+/// Why a transaction failed, decoded from its last panic or revert receipt.
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = transaction_failures)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TransactionFailure {
+    pub transaction_id: String,
+    pub kind: FailureKind,
+    pub receipt_index: Option<i64>,
+    /// The contract that failed, empty when the script itself failed.
+    pub contract_id: Option<String>,
+    pub panic_reason: Option<String>,
+    /// The `ra` register of a revert, hex encoded.
+    pub revert_code: Option<String>,
+    /// The Sway error signal the revert code stands for, e.g. `failed_require`.
+    pub signal: Option<String>,
+    /// The error logged before a revert, decoded when the contract has an ABI.
+    pub logged_error: Option<serde_json::Value>,
+    pub message: String,
+    /// The reason string reported by the node.
+    pub node_reason: String,
+    pub height: i64,
+    pub timestamp: i64,
+}
+
+pub fn batch_insert_transaction_failures(
+    connection: &mut PgConnection,
+    records: &Vec<TransactionFailure>,
+) -> Result<usize> {
+    insert_into(transaction_failures::table)
+        .values(records)
+        .on_conflict(transaction_failures::transaction_id)
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+pub fn get_transaction_failure(
+    connection: &mut PgConnection,
+    transaction_id: &str,
+) -> Result<Option<TransactionFailure>> {
+    transaction_failures::table
+        .find(transaction_id)
+        .select(TransactionFailure::as_select())
+        .first(connection)
+        .optional()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The failures of the transactions in `keys`.
+pub fn get_failures_by_transactions(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<TransactionFailure>> {
+    transaction_failures::table
+        .filter(transaction_failures::transaction_id.eq_any(keys))
+        .order(transaction_failures::transaction_id.asc())
+        .select(TransactionFailure::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+
+use diesel::{
+    insert_into, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, RunQueryDsl,
+    Selectable, SelectableHelper,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::tx_inputs;
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::InputType"]
+#[serde(rename_all = "snake_case")]
+pub enum InputType {
+    CoinSigned,
+    CoinPredicate,
+    Contract,
+    MessageCoinSigned,
+    MessageCoinPredicate,
+    MessageDataSigned,
+    MessageDataPredicate,
+}
+
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = tx_inputs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TxInput {
+    pub transaction_id: String,
+    pub input_index: i64,
+    pub input_type: InputType,
+    pub utxo_id: Option<String>,
+    /// Coin owner or message recipient.
+    pub owner: Option<String>,
+    /// Message sender on the L1.
+    pub sender: Option<String>,
+    pub asset_id: Option<String>,
+    pub amount: Option<i64>,
+    pub tx_pointer_height: Option<i64>,
+    pub tx_pointer_index: Option<i64>,
+    pub contract_id: Option<String>,
+    pub nonce: Option<String>,
+    pub height: i64,
+    pub timestamp: i64,
+}
+
+pub fn batch_insert_tx_inputs(
+    connection: &mut PgConnection,
+    records: &Vec<TxInput>,
+) -> Result<usize> {
+    insert_into(tx_inputs::table)
+        .values(records)
+        .on_conflict((tx_inputs::transaction_id, tx_inputs::input_index))
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The inputs of a transaction in order.
+pub fn get_transaction_inputs(
+    connection: &mut PgConnection,
+    transaction_id: &str,
+) -> Result<Vec<TxInput>> {
+    tx_inputs::table
+        .filter(tx_inputs::transaction_id.eq(transaction_id))
+        .order(tx_inputs::input_index.asc())
+        .select(TxInput::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The inputs of the transactions in `keys` in order.
+pub fn get_inputs_by_transactions(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<TxInput>> {
+    tx_inputs::table
+        .filter(tx_inputs::transaction_id.eq_any(keys))
+        .order((
+            tx_inputs::transaction_id.asc(),
+            tx_inputs::input_index.asc(),
+        ))
+        .select(TxInput::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use diesel::{
+    insert_into, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, RunQueryDsl,
+    Selectable, SelectableHelper,
+};
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::tx_outputs;
+
+#[derive(DbEnum, Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::OutputType"]
+#[serde(rename_all = "snake_case")]
+pub enum OutputType {
+    Coin,
+    Contract,
+    Change,
+    Variable,
+    ContractCreated,
+}
+
+#[derive(Insertable, Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = tx_outputs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TxOutput {
+    pub transaction_id: String,
+    pub output_index: i64,
+    pub output_type: OutputType,
+    pub utxo_id: Option<String>,
+    pub to_address: Option<String>,
+    pub asset_id: Option<String>,
+    pub amount: Option<i64>,
+    /// Index of the contract input a contract output belongs to.
+    pub input_index: Option<i64>,
+    pub contract_id: Option<String>,
+    pub height: i64,
+    pub timestamp: i64,
+}
+
+pub fn batch_insert_tx_outputs(
+    connection: &mut PgConnection,
+    records: &Vec<TxOutput>,
+) -> Result<usize> {
+    insert_into(tx_outputs::table)
+        .values(records)
+        .on_conflict((tx_outputs::transaction_id, tx_outputs::output_index))
+        .do_nothing()
+        .execute(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The outputs of a transaction in order.
+pub fn get_transaction_outputs(
+    connection: &mut PgConnection,
+    transaction_id: &str,
+) -> Result<Vec<TxOutput>> {
+    tx_outputs::table
+        .filter(tx_outputs::transaction_id.eq(transaction_id))
+        .order(tx_outputs::output_index.asc())
+        .select(TxOutput::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// The outputs of the transactions in `keys` in order.
+pub fn get_outputs_by_transactions(
+    connection: &mut PgConnection,
+    keys: &[String],
+) -> Result<Vec<TxOutput>> {
+    tx_outputs::table
+        .filter(tx_outputs::transaction_id.eq_any(keys))
+        .order((
+            tx_outputs::transaction_id.asc(),
+            tx_outputs::output_index.asc(),
+        ))
+        .select(TxOutput::as_select())
+        .load(connection)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}